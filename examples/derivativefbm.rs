@@ -0,0 +1,14 @@
+//! An example of using the `DerivativeFbm` noise function
+
+extern crate noise;
+
+use noise::utils::*;
+use noise::DerivativeFbm;
+
+fn main() {
+    let derivative_fbm = DerivativeFbm::new();
+
+    PlaneMapBuilder::new(&derivative_fbm)
+        .build()
+        .write_to_file("derivative_fbm.png");
+}
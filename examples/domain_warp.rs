@@ -0,0 +1,15 @@
+//! An example of using the `DomainWarp` noise function
+
+extern crate noise;
+
+use noise::utils::*;
+use noise::DomainWarp;
+
+fn main() {
+    let domain_warp = DomainWarp::new();
+
+    PlaneMapBuilder::new(&domain_warp)
+        .set_size(256, 256)
+        .build()
+        .write_to_file("domain_warp.png");
+}
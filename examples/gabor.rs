@@ -0,0 +1,24 @@
+extern crate noise;
+
+use noise::utils::*;
+use noise::{Gabor, Orientation};
+
+fn main() {
+    PlaneMapBuilder::new(&Gabor::new())
+        .build()
+        .write_to_file("gabor.png");
+
+    PlaneMapBuilder::new(&Gabor::new().set_orientation(Orientation::Random))
+        .build()
+        .write_to_file("gabor_random.png");
+
+    PlaneMapBuilder::new(&Gabor::new().set_frequency(8.0).set_bandwidth(1.0))
+        .build()
+        .write_to_file("gabor_narrow_band.png");
+
+    SphereMapBuilder::new(&Gabor::new().set_frequency(2.0).set_bandwidth(1.0))
+        .set_size(200, 100)
+        .set_bounds(-90.0, 90.0, -180.0, 180.0)
+        .build()
+        .write_to_file("gabor_sphere.png");
+}
@@ -0,0 +1,14 @@
+//! An example of using the `JordanTurbulence` noise function
+
+extern crate noise;
+
+use noise::utils::*;
+use noise::JordanTurbulence;
+
+fn main() {
+    let jordan_turbulence = JordanTurbulence::new();
+
+    PlaneMapBuilder::new(&jordan_turbulence)
+        .build()
+        .write_to_file("jordan_turbulence.png");
+}
@@ -0,0 +1,20 @@
+//! An example of using Simplex noise
+
+extern crate noise;
+
+use noise::utils::*;
+use noise::{Seedable, Simplex};
+
+fn main() {
+    let simplex = Simplex::new();
+
+    PlaneMapBuilder::new(&simplex)
+        .build()
+        .write_to_file("simplex.png");
+
+    let simplex = simplex.set_seed(1);
+
+    PlaneMapBuilder::new(&simplex)
+        .build()
+        .write_to_file("simplex_seed=1.png");
+}
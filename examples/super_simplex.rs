@@ -117,6 +117,73 @@ fn main() {
     }
     println!("\x08]");
 
+    // The 4D lookup table only covers the simplex where x >= y >= z >= w in simplex space. The
+    // noise function ranks the coordinates and permutes the axes of these points to cover the other
+    // 23 simplices of the hypercube. A lattice point is needed if it lies within the kernel radius
+    // (squared: 0.8, the squared distance between neighbouring lattice points) of any point in the
+    // simplex, so sample the simplex and keep every lattice point that falls within range of a
+    // sample. The included points are at most 0.75 away and the excluded ones at least 0.8, so
+    // the sampling error doesn't change the result.
+    let to_real_constant_4d = -0.138196601125011;
+    let kernel_radius_squared_4d = 0.8;
+    let real_dist_sq_4d = |d: [f64; 4]| {
+        let offset = (d[0] + d[1] + d[2] + d[3]) * to_real_constant_4d;
+        d.iter().map(|v| (v + offset) * (v + offset)).sum::<f64>()
+    };
+
+    let steps = 32;
+    let mut samples = Vec::new();
+    for a in 0..=steps {
+        for b in 0..=a {
+            for c in 0..=b {
+                for d in 0..=c {
+                    let steps = steps as f64;
+                    samples.push([
+                        a as f64 / steps,
+                        b as f64 / steps,
+                        c as f64 / steps,
+                        d as f64 / steps,
+                    ]);
+                }
+            }
+        }
+    }
+
+    let mut lookup_4d: Vec<([i8; 4], f64)> = Vec::new();
+    for i in -1..=2 {
+        for j in -1..=2 {
+            for k in -1..=2 {
+                for l in -1..=2 {
+                    let lattice_point = [i as f64, j as f64, k as f64, l as f64];
+                    let min_dist_sq = samples
+                        .iter()
+                        .map(|s| {
+                            real_dist_sq_4d([
+                                s[0] - lattice_point[0],
+                                s[1] - lattice_point[1],
+                                s[2] - lattice_point[2],
+                                s[3] - lattice_point[3],
+                            ])
+                        })
+                        .fold(f64::MAX, f64::min);
+                    // Points right on the edge of the kernel contribute nothing, so leave a
+                    // little room for rounding.
+                    if min_dist_sq < kernel_radius_squared_4d - 1e-9 {
+                        lookup_4d.push(([i, j, k, l], min_dist_sq));
+                    }
+                }
+            }
+        }
+    }
+    // Closest points first, so that the simplex's own vertices lead the table.
+    lookup_4d.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+    print!("lookup_4d = [");
+    for x in &lookup_4d {
+        print!("[{}, {}, {}, {}],", (x.0)[0], (x.0)[1], (x.0)[2], (x.0)[3]);
+    }
+    println!("\x08]");
+
     // Calculation of maximum value:
     // x => real_rel_coords[0], y => real_rel_coords[1]
     // a-h, components of gradient vectors for 4 closest points
@@ -199,6 +266,146 @@ fn main() {
     // {xout, yout, zout} = P0;
     // eq3dsp[xout, yout, zout]
 
+    // The computation for the maximum in 4D is shown below. It follows the 2D and 3D computations:
+    // each lattice point is given a fixed gradient, chosen as the one (out of the 64 in the gradient
+    // table) with the largest dot product with its delta position from the centre of the
+    // hypercube, [1/2,1/2,1/2,1/2] in simplex space, so that all of them point towards it. Then
+    // Newton's method is run on the resulting sum from that centre, the same as the Mathematica
+    // code does. With 30 contributing lattice points, this is done here instead of in Mathematica.
+    let mut gradients_4d = Vec::new();
+    let diag = 1.0 / 3.0f64.sqrt();
+    for zero_axis in 0..4 {
+        for signs in 0..8 {
+            let mut gradient = [0.0; 4];
+            let mut bit = 0;
+            for (axis, component) in gradient.iter_mut().enumerate() {
+                if axis != zero_axis {
+                    *component = if signs & (1 << bit) == 0 { diag } else { -diag };
+                    bit += 1;
+                }
+            }
+            gradients_4d.push(gradient);
+        }
+    }
+    for signs in 0..16 {
+        let mut gradient = [0.5; 4];
+        for (axis, component) in gradient.iter_mut().enumerate() {
+            if signs & (1 << axis) != 0 {
+                *component = -0.5;
+            }
+        }
+        gradients_4d.push(gradient);
+    }
+
+    let to_real_4d = |point: [f64; 4], lattice_point: [i8; 4]| {
+        let mut delta = [0.0; 4];
+        for axis in 0..4 {
+            delta[axis] = point[axis] - lattice_point[axis] as f64;
+        }
+        let offset = (delta[0] + delta[1] + delta[2] + delta[3]) * to_real_constant_4d;
+        [
+            delta[0] + offset,
+            delta[1] + offset,
+            delta[2] + offset,
+            delta[3] + offset,
+        ]
+    };
+    let dot_4d = |a: [f64; 4], b: [f64; 4]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3];
+
+    let centre = [0.5; 4];
+    let fixed_gradients: Vec<([i8; 4], [f64; 4])> = lookup_4d
+        .iter()
+        .map(|x| {
+            let dpos = to_real_4d(centre, x.0);
+            let gradient = gradients_4d.iter().cloned().fold([0.0; 4], |best, g| {
+                if dot_4d(g, dpos) > dot_4d(best, dpos) {
+                    g
+                } else {
+                    best
+                }
+            });
+            (x.0, gradient)
+        })
+        .collect();
+
+    // The sum of the contributions, and its derivative with respect to the point in simplex space.
+    let eq4d = |point: [f64; 4]| {
+        let mut value = 0.0;
+        let mut derivative = [0.0; 4];
+        for &(lattice_point, gradient) in &fixed_gradients {
+            let dpos = to_real_4d(point, lattice_point);
+            let attn = kernel_radius_squared_4d - dot_4d(dpos, dpos);
+            if attn > 0.0 {
+                let dot = dot_4d(gradient, dpos);
+                value += attn.powi(4) * dot;
+
+                // Derivative with respect to the real space delta, then back through the skew,
+                // which adds the sum of the components times the constant to each of them.
+                let mut real_derivative = [0.0; 4];
+                for axis in 0..4 {
+                    real_derivative[axis] =
+                        attn.powi(4) * gradient[axis] - 8.0 * attn.powi(3) * dot * dpos[axis];
+                }
+                let offset = real_derivative.iter().sum::<f64>() * to_real_constant_4d;
+                for axis in 0..4 {
+                    derivative[axis] += real_derivative[axis] + offset;
+                }
+            }
+        }
+        (value, derivative)
+    };
+
+    let mut point = centre;
+    println!("f[{:?}] = {}", point, eq4d(point).0);
+    for _ in 0..10 {
+        // The Hessian, from central differences of the derivative.
+        let step = 1e-5;
+        let mut hessian = [[0.0; 4]; 4];
+        for axis in 0..4 {
+            let (mut above, mut below) = (point, point);
+            above[axis] += step;
+            below[axis] -= step;
+            let (d_above, d_below) = (eq4d(above).1, eq4d(below).1);
+            for other in 0..4 {
+                hessian[other][axis] = (d_above[other] - d_below[other]) / (2.0 * step);
+            }
+        }
+
+        // Solve hessian * delta = derivative by Gaussian elimination, and step by -delta.
+        let mut rows = [[0.0; 5]; 4];
+        let derivative = eq4d(point).1;
+        for axis in 0..4 {
+            rows[axis][..4].copy_from_slice(&hessian[axis]);
+            rows[axis][4] = derivative[axis];
+        }
+        for pivot in 0..4 {
+            let best = (pivot..4)
+                .max_by(|&a, &b| {
+                    rows[a][pivot]
+                        .abs()
+                        .partial_cmp(&rows[b][pivot].abs())
+                        .unwrap()
+                })
+                .unwrap();
+            rows.swap(pivot, best);
+            let pivot_row = rows[pivot];
+            for (row, values) in rows.iter_mut().enumerate() {
+                if row != pivot {
+                    let factor = values[pivot] / pivot_row[pivot];
+                    for (value, pivot_value) in values.iter_mut().zip(pivot_row.iter()) {
+                        *value -= factor * pivot_value;
+                    }
+                }
+            }
+        }
+        for axis in 0..4 {
+            point[axis] -= rows[axis][4] / rows[axis][axis];
+        }
+        println!("f[{:?}] = {}", point, eq4d(point).0);
+    }
+    let max_4d = eq4d(point).0;
+    println!("4D maximum = {}", max_4d);
+
     let super_simplex = SuperSimplex::new();
 
     PlaneMapBuilder::new(&super_simplex)
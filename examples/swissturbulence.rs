@@ -0,0 +1,14 @@
+//! An example of using the `SwissTurbulence` noise function
+
+extern crate noise;
+
+use noise::utils::*;
+use noise::SwissTurbulence;
+
+fn main() {
+    let swiss_turbulence = SwissTurbulence::new();
+
+    PlaneMapBuilder::new(&swiss_turbulence)
+        .build()
+        .write_to_file("swiss_turbulence.png");
+}
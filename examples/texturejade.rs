@@ -12,37 +12,29 @@ fn main() {
         .set_lacunarity(2.20703125)
         .set_octaves(6);
 
-    // Base of the secondary jade texture. The base texture uses concentric
-    // cylinders aligned on the z axis, which will eventually be perturbed.
-    let base_secondary_jade = Cylinders::new().set_frequency(2.0);
-
-    // Rotate the base secondary jade texture so that the cylinders are not
-    // aligned with any axis. This produces more variation in the secondary
-    // jade texture since the texture is parallel to the y-axis.
-    let rotated_base_secondary_jade =
-        RotatePoint::new(base_secondary_jade).set_angles(90.0, 25.0, 5.0, 0.0);
-
-    // Slightly perturb the secondary jade texture for more realism.
-    let perturbed_base_secondary_jade = Turbulence::new(rotated_base_secondary_jade)
+    // Secondary jade texture. The base texture uses concentric cylinders
+    // aligned on the z axis. These are rotated so that they are not aligned
+    // with any axis, which produces more variation since the texture is
+    // parallel to the y-axis. They are then slightly perturbed for more
+    // realism, and scaled so that they make a small contribution to the final
+    // jade texture.
+    let secondary_jade = Cylinders::new()
+        .set_frequency(2.0)
+        .rotate_point(90.0, 25.0, 5.0, 0.0)
+        .turbulence()
         .set_seed(1)
         .set_frequency(4.0)
         .set_power(1.0 / 4.0)
-        .set_roughness(4);
-
-    // Scale the secondary jade texture so it makes a small contribution to the
-    // final jade texture.
-    let secondary_jade = ScaleBias::new(&perturbed_base_secondary_jade)
-        .set_scale(0.25)
-        .set_bias(0.0);
+        .set_roughness(4)
+        .scale_bias(0.25, 0.0);
 
     // Add the two jade textures together. These two textures were produced
     // using different combinations of coherent noise, so the final texture
-    // will have a lot of variation.
-    let combined_jade = Add::new(&primary_jade, &secondary_jade);
-
-    // Finally, perturb the combined jade texture to produce the final jade
-    // texture. A low roughness produces nice veins.
-    let final_jade = Turbulence::new(combined_jade)
+    // will have a lot of variation. Finally, perturb the combined jade texture
+    // to produce the final jade texture. A low roughness produces nice veins.
+    let final_jade = primary_jade
+        .add_noise(secondary_jade)
+        .turbulence()
         .set_seed(2)
         .set_frequency(4.0)
         .set_power(1.0 / 16.0)
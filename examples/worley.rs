@@ -1,7 +1,7 @@
 extern crate noise;
 
 use noise::utils::*;
-use noise::{RangeFunction, Worley};
+use noise::{Lattice, RangeFunction, ReturnType, Worley};
 
 fn main() {
     PlaneMapBuilder::new(&Worley::new())
@@ -43,4 +43,93 @@ fn main() {
     )
     .build()
     .write_to_file("worley_chebyshev_range.png");
+
+    PlaneMapBuilder::new(&Worley::new().set_range_function(RangeFunction::Minkowski(3.0)))
+        .build()
+        .write_to_file("worley_minkowski.png");
+
+    PlaneMapBuilder::new(
+        &Worley::new()
+            .enable_range(true)
+            .set_range_function(RangeFunction::Minkowski(3.0)),
+    )
+    .build()
+    .write_to_file("worley_minkowski_range.png");
+
+    PlaneMapBuilder::new(
+        &Worley::new()
+            .enable_range(true)
+            .set_range_function(RangeFunction::Custom(range_weighted)),
+    )
+    .build()
+    .write_to_file("worley_custom_range.png");
+
+    PlaneMapBuilder::new(&Worley::new().set_jitter(0.5))
+        .build()
+        .write_to_file("worley_jitter.png");
+
+    PlaneMapBuilder::new(
+        &Worley::new()
+            .set_lattice(Lattice::Hexagonal)
+            .set_jitter(0.0)
+            .set_frequency(2.0),
+    )
+    .build()
+    .write_to_file("worley_honeycomb.png");
+
+    PlaneMapBuilder::new(
+        &Worley::new()
+            .set_lattice(Lattice::Hexagonal)
+            .set_jitter(0.3)
+            .set_return_type(ReturnType::Distance2Sub)
+            .set_frequency(2.0),
+    )
+    .build()
+    .write_to_file("worley_scales.png");
+
+    PlaneMapBuilder::new(
+        &Worley::new()
+            .set_points_per_cell(3)
+            .set_return_type(ReturnType::Distance2Sub),
+    )
+    .build()
+    .write_to_file("worley_cobblestone.png");
+
+    PlaneMapBuilder::new(&Worley::new().set_return_type(ReturnType::Distance2))
+        .build()
+        .write_to_file("worley_distance2.png");
+
+    PlaneMapBuilder::new(&Worley::new().set_return_type(ReturnType::Distance2Add))
+        .build()
+        .write_to_file("worley_distance2_add.png");
+
+    PlaneMapBuilder::new(&Worley::new().set_return_type(ReturnType::Distance2Sub))
+        .build()
+        .write_to_file("worley_distance2_sub.png");
+
+    PlaneMapBuilder::new(&Worley::new().set_return_type(ReturnType::Distance2Mul))
+        .build()
+        .write_to_file("worley_distance2_mul.png");
+
+    PlaneMapBuilder::new(&Worley::new().set_return_type(ReturnType::Distance2Div))
+        .build()
+        .write_to_file("worley_distance2_div.png");
+
+    PlaneMapBuilder::new(&Worley::new().set_return_type(ReturnType::ValueAndDistance))
+        .build()
+        .write_to_file("worley_value_and_distance.png");
+
+    PlaneMapBuilder::new(&Worley::new().set_return_type(ReturnType::DistanceN(3)))
+        .build()
+        .write_to_file("worley_distance3.png");
+}
+
+// Distance that weighs the first axis twice as much as the others, stretching
+// the cells along the remaining axes.
+fn range_weighted(p1: &[f64], p2: &[f64]) -> f64 {
+    p1.iter()
+        .zip(p2)
+        .enumerate()
+        .map(|(i, (a, b))| if i == 0 { 2.0 } else { 1.0 } * (a - b).abs())
+        .sum()
 }
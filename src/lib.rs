@@ -18,6 +18,7 @@ extern crate rand;
 
 pub use math::{Point2, Point3, Point4};
 pub use noise_fns::*;
+pub use permutationtable::{LatticeHash, LatticeHashable};
 
 mod gradient;
 mod math;
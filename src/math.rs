@@ -330,6 +330,43 @@ pub fn to_isize4(x: Point4<f64>) -> Point4<isize> {
     [x[0] as isize, x[1] as isize, x[2] as isize, x[3] as isize]
 }
 
+/// Wraps a lattice coordinate into the range `0..period`, so that the lattice
+/// repeats every `period` cells. A period of zero leaves the coordinate as it
+/// is.
+#[inline]
+pub fn wrap(x: isize, period: usize) -> isize {
+    if period == 0 {
+        x
+    } else {
+        x.rem_euclid(period as isize)
+    }
+}
+
+#[inline]
+pub fn wrap2(x: Point2<isize>, period: Vector2<usize>) -> Point2<isize> {
+    zip_with2(x, period, wrap)
+}
+
+#[inline]
+pub fn wrap3(x: Point3<isize>, period: Vector3<usize>) -> Point3<isize> {
+    zip_with3(x, period, wrap)
+}
+
+#[inline]
+pub fn wrap4(x: Point4<isize>, period: Vector4<usize>) -> Point4<isize> {
+    zip_with4(x, period, wrap)
+}
+
+/// The finalizer from SplitMix64, which spreads every bit of the input across
+/// the whole output. The hashes of lattice points, seed points and derived
+/// seeds are all built from it.
+#[inline]
+pub(crate) fn split_mix64(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
 pub mod interpolate {
     /// Performs linear interpolation between two values.
     #[cfg(not(target_os = "emscripten"))]
@@ -377,4 +414,10 @@ pub mod interpolate {
     pub fn s_curve5(x: f64) -> f64 {
         x * x * x * (x * (x * 6.0 - 15.0) + 10.0)
     }
+
+    /// Derivative of the quintic S-curve.
+    #[inline]
+    pub fn s_curve5_derivative(x: f64) -> f64 {
+        x * x * (x * (x * 30.0 - 60.0) + 30.0)
+    }
 }
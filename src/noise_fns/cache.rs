@@ -1,5 +1,5 @@
 use math::{Point2, Point3, Point4};
-use noise_fns::NoiseFn;
+use noise_fns::{NoiseFn, Seedable};
 use std::cell::{Cell, RefCell};
 
 /// Noise function that caches the last output value generated by the source
@@ -9,12 +9,16 @@ use std::cell::{Cell, RefCell};
 /// call, the function returns the cached result of the previous call to
 /// `Source::get`. Otherwise, `Source::get` is called with the new coordinates,
 /// overwriting the cache with the result, and returning the result to the
-/// caller.
+/// caller. `Cache::get_filtered` works the same way, but only reuses a value
+/// sampled with the same filter width, where `get` counts as a width of zero.
 ///
 /// Caching a noise function is useful if it is used as a source function for
 /// multiple noise functions. If a source function is not cached, the source
 /// function will redundantly calculate the same output value once for each
 /// noise function in which it is included.
+///
+/// The cached value is kept in a `Cell`, so a `Cache` can't be shared between
+/// threads. Use `SyncCache` for graphs that are sampled from several threads.
 #[derive(Clone, Debug)]
 pub struct Cache<Source> {
     /// Outputs the value to be cached.
@@ -23,6 +27,8 @@ pub struct Cache<Source> {
     value: Cell<Option<f64>>,
 
     point: RefCell<Vec<f64>>,
+
+    filter_width: Cell<f64>,
 }
 
 impl<Source> Cache<Source> {
@@ -31,20 +37,24 @@ impl<Source> Cache<Source> {
             source,
             value: Cell::new(None),
             point: RefCell::new(Vec::new()),
+            filter_width: Cell::new(0.0),
         }
     }
-}
 
-impl<Source> NoiseFn<Point2<f64>> for Cache<Source>
-where
-    Source: NoiseFn<Point2<f64>>,
-{
-    fn get(&self, point: Point2<f64>) -> f64 {
+    fn get_cached<const N: usize>(&self, point: [f64; N], filter_width: f64) -> f64
+    where
+        Source: NoiseFn<[f64; N]>,
+    {
         match self.value.get() {
-            Some(value) if *self.point.borrow() == point => value,
+            Some(value)
+                if *self.point.borrow() == point && self.filter_width.get() == filter_width =>
+            {
+                value
+            },
             Some(_) | None => {
-                let value = self.source.get(point);
+                let value = self.source.get_filtered(point, filter_width);
                 self.value.set(Some(value));
+                self.filter_width.set(filter_width);
 
                 let mut cached_point = self.point.borrow_mut();
                 cached_point.clear();
@@ -56,24 +66,43 @@ where
     }
 }
 
+impl<Source> Seedable for Cache<Source>
+where
+    Source: Seedable,
+{
+    /// Reseeds the source, and clears the cached value.
+    fn set_seed(self, seed: u32) -> Self {
+        Self::new(self.source.set_seed(seed))
+    }
+
+    fn seed(&self) -> u32 {
+        self.source.seed()
+    }
+}
+
+impl<Source> NoiseFn<Point2<f64>> for Cache<Source>
+where
+    Source: NoiseFn<Point2<f64>>,
+{
+    fn get(&self, point: Point2<f64>) -> f64 {
+        self.get_cached(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point2<f64>, filter_width: f64) -> f64 {
+        self.get_cached(point, filter_width)
+    }
+}
+
 impl<Source> NoiseFn<Point3<f64>> for Cache<Source>
 where
     Source: NoiseFn<Point3<f64>>,
 {
     fn get(&self, point: Point3<f64>) -> f64 {
-        match self.value.get() {
-            Some(value) if *self.point.borrow() == point => value,
-            Some(_) | None => {
-                let value = self.source.get(point);
-                self.value.set(Some(value));
-
-                let mut cached_point = self.point.borrow_mut();
-                cached_point.clear();
-                cached_point.extend_from_slice(&point);
+        self.get_cached(point, 0.0)
+    }
 
-                value
-            },
-        }
+    fn get_filtered(&self, point: Point3<f64>, filter_width: f64) -> f64 {
+        self.get_cached(point, filter_width)
     }
 }
 
@@ -82,18 +111,10 @@ where
     Source: NoiseFn<Point4<f64>>,
 {
     fn get(&self, point: Point4<f64>) -> f64 {
-        match self.value.get() {
-            Some(value) if *self.point.borrow() == point => value,
-            Some(_) | None => {
-                let value = self.source.get(point);
-                self.value.set(Some(value));
-
-                let mut cached_point = self.point.borrow_mut();
-                cached_point.clear();
-                cached_point.extend_from_slice(&point);
+        self.get_cached(point, 0.0)
+    }
 
-                value
-            },
-        }
+    fn get_filtered(&self, point: Point4<f64>, filter_width: f64) -> f64 {
+        self.get_cached(point, filter_width)
     }
 }
@@ -3,9 +3,11 @@ pub use self::max::*;
 pub use self::min::*;
 pub use self::multiply::*;
 pub use self::power::*;
+pub use self::zip_with::*;
 
 mod add;
 mod max;
 mod min;
 mod multiply;
 mod power;
+mod zip_with;
@@ -1,26 +1,59 @@
-use noise_fns::NoiseFn;
+use noise_fns::{derive_seed, NoiseFn, Seedable};
 
 /// Noise function that outputs the product of the two output values from two source
 /// functions.
-pub struct Multiply<'a, T: 'a> {
+#[derive(Clone, Debug)]
+pub struct Multiply<Source1, Source2> {
     /// Outputs a value.
-    pub source1: &'a dyn NoiseFn<T>,
+    pub source1: Source1,
 
     /// Outputs a value.
-    pub source2: &'a dyn NoiseFn<T>,
+    pub source2: Source2,
+
+    seed: u32,
 }
 
-impl<'a, T> Multiply<'a, T> {
-    pub fn new(source1: &'a dyn NoiseFn<T>, source2: &'a dyn NoiseFn<T>) -> Self {
-        Self { source1, source2 }
+impl<Source1, Source2> Multiply<Source1, Source2> {
+    pub fn new(source1: Source1, source2: Source2) -> Self {
+        Self {
+            source1,
+            source2,
+            seed: 0,
+        }
+    }
+}
+
+/// Setting the seed reseeds each source with a seed derived from it.
+impl<Source1, Source2> Seedable for Multiply<Source1, Source2>
+where
+    Source1: Seedable,
+    Source2: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source1: self.source1.set_seed(derive_seed(seed, 0)),
+            source2: self.source2.set_seed(derive_seed(seed, 1)),
+            seed,
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
     }
 }
 
-impl<'a, T> NoiseFn<T> for Multiply<'a, T>
+impl<T, Source1, Source2> NoiseFn<T> for Multiply<Source1, Source2>
 where
     T: Copy,
+    Source1: NoiseFn<T>,
+    Source2: NoiseFn<T>,
 {
     fn get(&self, point: T) -> f64 {
-        self.source1.get(point) * self.source2.get(point)
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        self.source1.get_filtered(point, filter_width)
+            * self.source2.get_filtered(point, filter_width)
     }
 }
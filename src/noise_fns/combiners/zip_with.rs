@@ -0,0 +1,85 @@
+use noise_fns::{derive_seed, NoiseFn, Seedable};
+use std::fmt;
+
+/// Noise function that combines the two output values from two source
+/// functions with a closure.
+///
+/// This covers any combiner that doesn't have a type of its own, such as a
+/// weighted sum or a smooth minimum.
+#[derive(Clone)]
+pub struct ZipWith<Source1, Source2, F> {
+    /// Outputs a value.
+    pub source1: Source1,
+
+    /// Outputs a value.
+    pub source2: Source2,
+
+    /// Combines the output values from the two source functions.
+    pub zip: F,
+
+    seed: u32,
+}
+
+impl<Source1, Source2, F> ZipWith<Source1, Source2, F> {
+    pub fn new(source1: Source1, source2: Source2, zip: F) -> Self {
+        Self {
+            source1,
+            source2,
+            zip,
+            seed: 0,
+        }
+    }
+}
+
+/// Setting the seed reseeds each source with a seed derived from it.
+impl<Source1, Source2, F> Seedable for ZipWith<Source1, Source2, F>
+where
+    Source1: Seedable,
+    Source2: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source1: self.source1.set_seed(derive_seed(seed, 0)),
+            source2: self.source2.set_seed(derive_seed(seed, 1)),
+            seed,
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+impl<T, Source1, Source2, F> NoiseFn<T> for ZipWith<Source1, Source2, F>
+where
+    T: Copy,
+    Source1: NoiseFn<T>,
+    Source2: NoiseFn<T>,
+    F: Fn(f64, f64) -> f64,
+{
+    fn get(&self, point: T) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        (self.zip)(
+            self.source1.get_filtered(point, filter_width),
+            self.source2.get_filtered(point, filter_width),
+        )
+    }
+}
+
+impl<Source1, Source2, F> fmt::Debug for ZipWith<Source1, Source2, F>
+where
+    Source1: fmt::Debug,
+    Source2: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ZipWith {{ source1: {:?}, source2: {:?}, .. }}",
+            self.source1, self.source2
+        )
+    }
+}
@@ -0,0 +1,196 @@
+use math;
+use math::{Point2, Point3, Vector2, Vector3};
+use noise_fns::{derive_seed, FiniteDifference, NoiseFnWithGradient, Seedable};
+
+/// Offsets that the y and z potential functions are sampled at, so the
+/// components of the potential field are decorrelated even when the potential
+/// functions are identical.
+const Y_POTENTIAL_OFFSET: Vector3<f64> = [123.456, -78.901, 34.567];
+const Z_POTENTIAL_OFFSET: Vector3<f64> = [-91.234, 56.789, -12.345];
+
+/// Vector field that outputs 2/3-dimensional curl noise.
+///
+/// Curl noise is the curl of a potential field made of noise functions. The
+/// curl of any field has no divergence, so particles moved along curl noise
+/// swirl around like an incompressible fluid without bunching up or thinning
+/// out. This makes it useful for smoke, flow and flocking effects.
+///
+/// 2D curl noise treats the x potential function as a stream function and
+/// ignores the others. 3D curl noise uses all three potential functions as the
+/// components of a vector potential.
+///
+/// The curl is built from the gradients of the potential functions, which use
+/// exact derivatives for noise functions such as `Perlin`. Noise functions that
+/// can't calculate their gradients directly, such as the fractals, have them
+/// estimated with finite differences instead, by building the curl noise with
+/// `Curl::finite_difference`.
+#[derive(Clone, Debug)]
+pub struct Curl<Source> {
+    /// Potential function for the x component of the potential field.
+    pub x_potential: Source,
+
+    /// Potential function for the y component of the potential field. Only
+    /// used by 3D curl noise.
+    pub y_potential: Source,
+
+    /// Potential function for the z component of the potential field. Only
+    /// used by 3D curl noise.
+    pub z_potential: Source,
+}
+
+impl<Source> Curl<Source>
+where
+    Source: Clone,
+{
+    /// Creates curl noise with all three potential functions copied from
+    /// `source`. They're sampled at different offsets, and setting the seed
+    /// gives each copy a different seed as well.
+    pub fn new(source: Source) -> Self {
+        Self {
+            x_potential: source.clone(),
+            y_potential: source.clone(),
+            z_potential: source,
+        }
+    }
+}
+
+impl<Source> Curl<FiniteDifference<Source>>
+where
+    Source: Clone,
+{
+    /// Creates curl noise from a source function that can't calculate its
+    /// gradient directly, by wrapping it in `FiniteDifference` with the given
+    /// step.
+    pub fn finite_difference(source: Source, step: f64) -> Self {
+        Self::new(FiniteDifference::new(source).set_step(step))
+    }
+}
+
+impl<Source> Curl<Source>
+where
+    Source: NoiseFnWithGradient<Point2<f64>>,
+{
+    /// Returns the velocity of the 2-dimensional field at the point.
+    pub fn get2(&self, point: Point2<f64>) -> Vector2<f64> {
+        self.get2_filtered(point, 0.0)
+    }
+
+    /// Returns the velocity of the 2-dimensional field at the point, without
+    /// any detail too fine to be represented by samples spaced `filter_width`
+    /// apart. See `NoiseFn::get_filtered`.
+    pub fn get2_filtered(&self, point: Point2<f64>, filter_width: f64) -> Vector2<f64> {
+        let (_, gradient) = self
+            .x_potential
+            .get_filtered_with_gradient(point, filter_width);
+
+        [gradient[1], -gradient[0]]
+    }
+}
+
+impl<Source> Curl<Source>
+where
+    Source: NoiseFnWithGradient<Point3<f64>>,
+{
+    /// Returns the velocity of the 3-dimensional field at the point.
+    pub fn get3(&self, point: Point3<f64>) -> Vector3<f64> {
+        self.get3_filtered(point, 0.0)
+    }
+
+    /// Returns the velocity of the 3-dimensional field at the point, without
+    /// any detail too fine to be represented by samples spaced `filter_width`
+    /// apart. See `NoiseFn::get_filtered`.
+    pub fn get3_filtered(&self, point: Point3<f64>, filter_width: f64) -> Vector3<f64> {
+        let (_, x_gradient) = self
+            .x_potential
+            .get_filtered_with_gradient(point, filter_width);
+        let (_, y_gradient) = self
+            .y_potential
+            .get_filtered_with_gradient(math::add3(point, Y_POTENTIAL_OFFSET), filter_width);
+        let (_, z_gradient) = self
+            .z_potential
+            .get_filtered_with_gradient(math::add3(point, Z_POTENTIAL_OFFSET), filter_width);
+
+        [
+            z_gradient[1] - y_gradient[2],
+            x_gradient[2] - z_gradient[0],
+            y_gradient[0] - x_gradient[1],
+        ]
+    }
+}
+
+impl<Source> Seedable for Curl<Source>
+where
+    Source: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            x_potential: self.x_potential.set_seed(seed),
+            y_potential: self.y_potential.set_seed(derive_seed(seed, 1)),
+            z_potential: self.z_potential.set_seed(derive_seed(seed, 2)),
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.x_potential.seed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noise_fns::{Fbm, Perlin};
+
+    // Matching the step of the finite differences makes the estimated divergence of
+    // its curl cancel exactly, rather than only up to the truncation error.
+    const STEP: f64 = 1e-3;
+
+    fn divergence2<F>(field: F, point: Point2<f64>) -> f64
+    where
+        F: Fn(Point2<f64>) -> Vector2<f64>,
+    {
+        (field([point[0] + STEP, point[1]])[0] - field([point[0] - STEP, point[1]])[0]
+            + field([point[0], point[1] + STEP])[1]
+            - field([point[0], point[1] - STEP])[1])
+            / (2.0 * STEP)
+    }
+
+    fn divergence3<F>(field: F, point: Point3<f64>) -> f64
+    where
+        F: Fn(Point3<f64>) -> Vector3<f64>,
+    {
+        let mut divergence = 0.0;
+        for axis in 0..3 {
+            let (mut high, mut low) = (point, point);
+            high[axis] += STEP;
+            low[axis] -= STEP;
+            divergence += (field(high)[axis] - field(low)[axis]) / (2.0 * STEP);
+        }
+
+        divergence
+    }
+
+    #[test]
+    fn test_divergence_free() {
+        let curl = Curl::new(Perlin::new()).set_seed(1);
+        let fractal_curl = Curl::finite_difference(Fbm::new(), STEP);
+
+        for i in 0..128 {
+            let t = f64::from(i) * 0.173;
+            let point = [t, 1.3 - t * 0.6, t * 0.8 + 0.4];
+
+            assert!(divergence2(|p| curl.get2(p), [point[0], point[1]]).abs() < 1e-3);
+            assert!(divergence3(|p| curl.get3(p), point).abs() < 1e-3);
+            assert!(divergence2(|p| fractal_curl.get2(p), [point[0], point[1]]).abs() < 1e-3);
+            assert!(divergence3(|p| fractal_curl.get3(p), point).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_potential_seeds() {
+        let curl = Curl::new(Perlin::new()).set_seed(u32::MAX);
+
+        assert_eq!(curl.seed(), u32::MAX);
+        assert_eq!(curl.y_potential.seed(), derive_seed(u32::MAX, 1));
+        assert_eq!(curl.z_potential.seed(), derive_seed(u32::MAX, 2));
+    }
+}
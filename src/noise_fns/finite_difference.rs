@@ -0,0 +1,199 @@
+use math::{Point2, Point3, Point4};
+use noise_fns::{NoiseFn, NoiseFnWithGradient, Seedable};
+
+/// Noise function that estimates the gradient of the source function with
+/// central finite differences.
+///
+/// This lets noise functions that can't calculate their gradient directly,
+/// such as the fractals or combinations of other noise functions, be used
+/// wherever a `NoiseFnWithGradient` is needed. Each gradient takes two extra
+/// evaluations of the source function per axis.
+#[derive(Clone, Debug)]
+pub struct FiniteDifference<Source> {
+    /// Source function that outputs a value.
+    pub source: Source,
+
+    /// Distance from the input point to the points sampled on either side of
+    /// it along each axis. Smaller steps are more accurate until rounding
+    /// errors take over.
+    pub step: f64,
+}
+
+impl<Source> FiniteDifference<Source> {
+    pub const DEFAULT_STEP: f64 = 1e-4;
+
+    pub fn new(source: Source) -> Self {
+        Self {
+            source,
+            step: Self::DEFAULT_STEP,
+        }
+    }
+
+    pub fn set_step(self, step: f64) -> Self {
+        Self { step, ..self }
+    }
+}
+
+impl<Source> Seedable for FiniteDifference<Source>
+where
+    Source: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source: self.source.set_seed(seed),
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.source.seed()
+    }
+}
+
+impl<Source> NoiseFn<Point2<f64>> for FiniteDifference<Source>
+where
+    Source: NoiseFn<Point2<f64>>,
+{
+    fn get(&self, point: Point2<f64>) -> f64 {
+        self.source.get(point)
+    }
+
+    fn get_filtered(&self, point: Point2<f64>, filter_width: f64) -> f64 {
+        self.source.get_filtered(point, filter_width)
+    }
+}
+
+impl<Source> NoiseFn<Point3<f64>> for FiniteDifference<Source>
+where
+    Source: NoiseFn<Point3<f64>>,
+{
+    fn get(&self, point: Point3<f64>) -> f64 {
+        self.source.get(point)
+    }
+
+    fn get_filtered(&self, point: Point3<f64>, filter_width: f64) -> f64 {
+        self.source.get_filtered(point, filter_width)
+    }
+}
+
+impl<Source> NoiseFn<Point4<f64>> for FiniteDifference<Source>
+where
+    Source: NoiseFn<Point4<f64>>,
+{
+    fn get(&self, point: Point4<f64>) -> f64 {
+        self.source.get(point)
+    }
+
+    fn get_filtered(&self, point: Point4<f64>, filter_width: f64) -> f64 {
+        self.source.get_filtered(point, filter_width)
+    }
+}
+
+impl<Source> NoiseFnWithGradient<Point2<f64>> for FiniteDifference<Source>
+where
+    Source: NoiseFn<Point2<f64>>,
+{
+    fn get_with_gradient(&self, point: Point2<f64>) -> (f64, Point2<f64>) {
+        self.get_filtered_with_gradient(point, 0.0)
+    }
+
+    fn get_filtered_with_gradient(
+        &self,
+        point: Point2<f64>,
+        filter_width: f64,
+    ) -> (f64, Point2<f64>) {
+        (
+            self.source.get_filtered(point, filter_width),
+            central_differences(&self.source, point, self.step, filter_width),
+        )
+    }
+}
+
+impl<Source> NoiseFnWithGradient<Point3<f64>> for FiniteDifference<Source>
+where
+    Source: NoiseFn<Point3<f64>>,
+{
+    fn get_with_gradient(&self, point: Point3<f64>) -> (f64, Point3<f64>) {
+        self.get_filtered_with_gradient(point, 0.0)
+    }
+
+    fn get_filtered_with_gradient(
+        &self,
+        point: Point3<f64>,
+        filter_width: f64,
+    ) -> (f64, Point3<f64>) {
+        (
+            self.source.get_filtered(point, filter_width),
+            central_differences(&self.source, point, self.step, filter_width),
+        )
+    }
+}
+
+impl<Source> NoiseFnWithGradient<Point4<f64>> for FiniteDifference<Source>
+where
+    Source: NoiseFn<Point4<f64>>,
+{
+    fn get_with_gradient(&self, point: Point4<f64>) -> (f64, Point4<f64>) {
+        self.get_filtered_with_gradient(point, 0.0)
+    }
+
+    fn get_filtered_with_gradient(
+        &self,
+        point: Point4<f64>,
+        filter_width: f64,
+    ) -> (f64, Point4<f64>) {
+        (
+            self.source.get_filtered(point, filter_width),
+            central_differences(&self.source, point, self.step, filter_width),
+        )
+    }
+}
+
+fn central_differences<Source, const N: usize>(
+    source: &Source,
+    point: [f64; N],
+    step: f64,
+    filter_width: f64,
+) -> [f64; N]
+where
+    Source: NoiseFn<[f64; N]>,
+{
+    let mut gradient = [0.0; N];
+
+    for (axis, derivative) in gradient.iter_mut().enumerate() {
+        let (mut high, mut low) = (point, point);
+        high[axis] += step;
+        low[axis] -= step;
+
+        *derivative = (source.get_filtered(high, filter_width)
+            - source.get_filtered(low, filter_width))
+            / (2.0 * step);
+    }
+
+    gradient
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noise_fns::Perlin;
+
+    #[test]
+    fn test_matches_analytic_gradient() {
+        let perlin = Perlin::new();
+        let finite_difference = FiniteDifference::new(perlin);
+
+        for i in 0..256 {
+            let t = f64::from(i) * 0.137;
+            let point = [t, 0.7 - t * 0.4, t * 1.3 - 2.0];
+
+            let (value, gradient) = perlin.get_with_gradient(point);
+            let (estimate, estimated_gradient) = finite_difference.get_with_gradient(point);
+
+            assert_eq!(value, estimate);
+            for (derivative, estimate) in gradient.iter().zip(estimated_gradient.iter()) {
+                assert!((derivative - estimate).abs() < 1e-4);
+            }
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use math::{Point2, Point3, Point4};
-use noise_fns::NoiseFn;
+use noise_fns::{NoiseFn, Seedable};
 
 /// Noise function that outputs a checkerboard pattern.
 ///
@@ -10,6 +10,7 @@ use noise_fns::NoiseFn;
 /// This noise function is not very useful by itself, but it can be used for
 /// debugging purposes.
 #[derive(Clone, Copy, Debug)]
+#[allow(clippy::manual_non_exhaustive)]
 pub struct Checkerboard {
     /// Controls the size of the block in 2^(size).
     pub size: usize,
@@ -43,6 +44,17 @@ impl Default for Checkerboard {
     }
 }
 
+impl Seedable for Checkerboard {
+    /// There are no random values, so the seed is ignored and stays zero.
+    fn set_seed(self, _seed: u32) -> Self {
+        self
+    }
+
+    fn seed(&self) -> u32 {
+        0
+    }
+}
+
 // These impl's should be made generic over Point, but there is no higher Point
 // type. Keep the code the same anyway.
 impl NoiseFn<Point2<f64>> for Checkerboard {
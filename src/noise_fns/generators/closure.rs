@@ -0,0 +1,52 @@
+use noise_fns::{NoiseFn, Seedable};
+use std::fmt;
+
+/// Noise function that outputs the value of a closure at the input point.
+///
+/// This is the quickest way to add a custom formula to a graph, without
+/// writing a new type that implements `NoiseFn`. The closure takes the input
+/// point, so it decides which dimensions it can be sampled in:
+///
+/// ```rust
+/// use noise::{NoiseFn, NoiseFnFromClosure};
+///
+/// let rings = NoiseFnFromClosure::new(|[x, y]: [f64; 2]| (x.hypot(y) * 4.0).sin());
+/// let val = rings.get([0.5, 0.25]);
+/// ```
+#[derive(Clone, Copy)]
+pub struct NoiseFnFromClosure<F> {
+    /// Outputs the value at each point.
+    pub closure: F,
+}
+
+impl<F> NoiseFnFromClosure<F> {
+    pub fn new(closure: F) -> Self {
+        Self { closure }
+    }
+}
+
+impl<F> Seedable for NoiseFnFromClosure<F> {
+    /// The closure can't be reseeded, so the seed is ignored and stays zero.
+    fn set_seed(self, _seed: u32) -> Self {
+        self
+    }
+
+    fn seed(&self) -> u32 {
+        0
+    }
+}
+
+impl<T, F> NoiseFn<T> for NoiseFnFromClosure<F>
+where
+    F: Fn(T) -> f64,
+{
+    fn get(&self, point: T) -> f64 {
+        (self.closure)(point)
+    }
+}
+
+impl<F> fmt::Debug for NoiseFnFromClosure<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NoiseFnFromClosure {{ .. }}")
+    }
+}
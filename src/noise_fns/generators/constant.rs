@@ -1,4 +1,4 @@
-use noise_fns::NoiseFn;
+use noise_fns::{NoiseFn, Seedable};
 
 /// Noise function that outputs a constant value.
 ///
@@ -19,6 +19,17 @@ impl Constant {
     }
 }
 
+impl Seedable for Constant {
+    /// There are no random values, so the seed is ignored and stays zero.
+    fn set_seed(self, _seed: u32) -> Self {
+        self
+    }
+
+    fn seed(&self) -> u32 {
+        0
+    }
+}
+
 impl<T: Copy> NoiseFn<T> for Constant {
     fn get(&self, _point: T) -> f64 {
         self.value
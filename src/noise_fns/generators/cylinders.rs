@@ -1,5 +1,5 @@
 use math::{Point2, Point3, Point4};
-use noise_fns::NoiseFn;
+use noise_fns::{NoiseFn, Seedable};
 
 /// Noise function that outputs concentric cylinders.
 ///
@@ -32,6 +32,17 @@ impl Default for Cylinders {
     }
 }
 
+impl Seedable for Cylinders {
+    /// There are no random values, so the seed is ignored and stays zero.
+    fn set_seed(self, _seed: u32) -> Self {
+        self
+    }
+
+    fn seed(&self) -> u32 {
+        0
+    }
+}
+
 impl NoiseFn<Point2<f64>> for Cylinders {
     fn get(&self, point: Point2<f64>) -> f64 {
         calculate_cylinders(&point, self.frequency)
@@ -0,0 +1,268 @@
+use math::{Point2, Point3, Point4};
+use noise_fns::{derive_seed, warped_filter_width, Fbm, NoiseFn, Seedable};
+
+/// Noise function that outputs domain-warped noise.
+///
+/// Domain warping, or pattern warping, samples the outer function at a point
+/// that has been displaced by the inner function, whose input has been
+/// displaced by the inner function again, and so on. With a depth of two this
+/// is `outer(p + k * inner(p + k * inner(p)))`, where `k` is the strength.
+///
+/// Every level and every axis of the displacement uses its own copy of the
+/// inner function with a different seed, so the displacements aren't
+/// correlated with each other. The result is the swirling, folded look that's
+/// popular for terrain and marbled textures.
+///
+/// The copies of the inner function are made whenever the seed or the depth
+/// changes, with one for each of the four axes at every level. 2D and 3D
+/// points only use the copies for their first two or three axes.
+#[derive(Clone, Debug)]
+pub struct DomainWarp<Inner = Fbm, Outer = Fbm> {
+    /// How far the inner functions displace the input point at each level.
+    pub strength: f64,
+
+    seed: u32,
+    depth: usize,
+    inner: Inner,
+    outer: Outer,
+    warp_functions: Vec<Inner>,
+}
+
+impl DomainWarp {
+    pub const DEFAULT_SEED: u32 = 0;
+    pub const DEFAULT_DEPTH: usize = 2;
+    pub const DEFAULT_STRENGTH: f64 = 1.0;
+
+    pub fn new() -> Self {
+        Self::with_sources(Fbm::new(), Fbm::new())
+    }
+}
+
+impl<Inner, Outer> DomainWarp<Inner, Outer>
+where
+    Inner: Clone + Seedable,
+    Outer: Seedable,
+{
+    /// Creates domain-warped noise that samples `outer`, displaced by copies
+    /// of `inner`.
+    ///
+    /// The seeds of both functions are replaced: `outer` is reseeded with
+    /// `DEFAULT_SEED`, and the copies of `inner` with seeds derived from it.
+    /// Call `set_seed` afterwards to use a different seed.
+    pub fn with_sources(inner: Inner, outer: Outer) -> Self {
+        Self {
+            strength: DomainWarp::DEFAULT_STRENGTH,
+            seed: DomainWarp::DEFAULT_SEED,
+            depth: DomainWarp::DEFAULT_DEPTH,
+            warp_functions: build_warp_functions(
+                &inner,
+                DomainWarp::DEFAULT_SEED,
+                DomainWarp::DEFAULT_DEPTH,
+            ),
+            inner,
+            outer: outer.set_seed(DomainWarp::DEFAULT_SEED),
+        }
+    }
+
+    /// Sets the number of times the input point is displaced. A depth of zero
+    /// outputs the outer function unchanged.
+    pub fn set_depth(self, depth: usize) -> Self {
+        Self {
+            depth,
+            warp_functions: build_warp_functions(&self.inner, self.seed, depth),
+            ..self
+        }
+    }
+}
+
+impl<Inner, Outer> DomainWarp<Inner, Outer> {
+    pub fn set_strength(self, strength: f64) -> Self {
+        Self { strength, ..self }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the point displaced by every level of the inner functions.
+    fn warp<const N: usize>(&self, point: [f64; N], filter_width: f64) -> [f64; N]
+    where
+        Inner: NoiseFn<[f64; N]>,
+    {
+        let mut warped_point = point;
+
+        // Each level samples the inner functions at the point displaced by the
+        // level before it, starting from the undisplaced point. Only the
+        // copies for the first N axes of each level are used.
+        for functions in self.warp_functions.chunks(4) {
+            let mut displacement = [0.0; N];
+            for (offset, function) in displacement.iter_mut().zip(functions.iter()) {
+                *offset = function.get_filtered(warped_point, filter_width);
+            }
+
+            for ((coordinate, original), offset) in warped_point
+                .iter_mut()
+                .zip(point.iter())
+                .zip(displacement.iter())
+            {
+                *coordinate = original + self.strength * offset;
+            }
+        }
+
+        warped_point
+    }
+
+    fn get_warped<const N: usize>(&self, point: [f64; N], filter_width: f64) -> f64
+    where
+        Inner: NoiseFn<[f64; N]>,
+        Outer: NoiseFn<[f64; N]>,
+    {
+        // The inner functions are all filtered to the original filter width,
+        // and the outer function to the spacing that the samples end up with
+        // once warped.
+        let warped_point = self.warp(point, filter_width);
+        let filter_width = warped_filter_width(
+            |point| self.warp(point, filter_width),
+            point,
+            warped_point,
+            filter_width,
+        );
+
+        self.outer.get_filtered(warped_point, filter_width)
+    }
+}
+
+/// Makes a copy of `inner` for each of the four axes at every level, ordered by
+/// level and then by axis.
+fn build_warp_functions<Inner>(inner: &Inner, seed: u32, depth: usize) -> Vec<Inner>
+where
+    Inner: Clone + Seedable,
+{
+    let mut warp_functions = Vec::with_capacity(depth * 4);
+    for level in 0..depth {
+        for axis in 0..4 {
+            let child = 1 + 4 * level as u32 + axis;
+            warp_functions.push(inner.clone().set_seed(derive_seed(seed, child)));
+        }
+    }
+    warp_functions
+}
+
+impl Default for DomainWarp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Inner, Outer> Seedable for DomainWarp<Inner, Outer>
+where
+    Inner: Clone + Seedable,
+    Outer: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        if self.seed == seed {
+            return self;
+        }
+
+        Self {
+            seed,
+            warp_functions: build_warp_functions(&self.inner, seed, self.depth),
+            outer: self.outer.set_seed(seed),
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+/// 2-dimensional `DomainWarp` noise
+impl<Inner, Outer> NoiseFn<Point2<f64>> for DomainWarp<Inner, Outer>
+where
+    Inner: NoiseFn<Point2<f64>>,
+    Outer: NoiseFn<Point2<f64>>,
+{
+    fn get(&self, point: Point2<f64>) -> f64 {
+        self.get_warped(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point2<f64>, filter_width: f64) -> f64 {
+        self.get_warped(point, filter_width)
+    }
+}
+
+/// 3-dimensional `DomainWarp` noise
+impl<Inner, Outer> NoiseFn<Point3<f64>> for DomainWarp<Inner, Outer>
+where
+    Inner: NoiseFn<Point3<f64>>,
+    Outer: NoiseFn<Point3<f64>>,
+{
+    fn get(&self, point: Point3<f64>) -> f64 {
+        self.get_warped(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point3<f64>, filter_width: f64) -> f64 {
+        self.get_warped(point, filter_width)
+    }
+}
+
+/// 4-dimensional `DomainWarp` noise
+impl<Inner, Outer> NoiseFn<Point4<f64>> for DomainWarp<Inner, Outer>
+where
+    Inner: NoiseFn<Point4<f64>>,
+    Outer: NoiseFn<Point4<f64>>,
+{
+    fn get(&self, point: Point4<f64>) -> f64 {
+        self.get_warped(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point4<f64>, filter_width: f64) -> f64 {
+        self.get_warped(point, filter_width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noise_fns::MultiFractal;
+
+    #[test]
+    fn test_matches_nested_displacement() {
+        let inner = Fbm::new().set_octaves(3);
+        let outer = Fbm::new().set_seed(7);
+        let domain_warp = DomainWarp::with_sources(inner.clone(), outer.clone())
+            .set_seed(7)
+            .set_strength(2.0);
+
+        // Displaces the point by the inner function sampled at another point,
+        // using the seeds derived for the axes of a level.
+        let displace = |level: u32, point: [f64; 2], sample_point: [f64; 2]| {
+            let seed = |axis| derive_seed(7, 1 + 4 * level + axis);
+            [
+                point[0] + 2.0 * inner.clone().set_seed(seed(0)).get(sample_point),
+                point[1] + 2.0 * inner.clone().set_seed(seed(1)).get(sample_point),
+            ]
+        };
+
+        for i in 0..32 {
+            let t = f64::from(i) * 0.173;
+            let point = [t, 0.5 - t * 0.3];
+
+            let q = displace(0, point, point);
+            let r = displace(1, point, q);
+
+            assert_eq!(domain_warp.get(point), outer.get(r));
+            assert_eq!(
+                domain_warp.clone().set_depth(0).get(point),
+                outer.get(point)
+            );
+
+            // Without any warping, the outer function is filtered as it is.
+            let unwarped = domain_warp.clone().set_depth(0);
+            assert!(
+                (unwarped.get_filtered(point, 0.1) - outer.get_filtered(point, 0.1)).abs() < 1e-9
+            );
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use super::SpectralWeights;
 use math;
 use math::{Point2, Point3, Point4};
 use noise_fns::{MultiFractal, NoiseFn, Perlin, Seedable};
@@ -14,7 +15,7 @@ use std;
 /// not be as damped and thus will grow more jagged as iteration progresses.
 ///
 #[derive(Clone, Debug)]
-pub struct BasicMulti {
+pub struct BasicMulti<Source = Perlin> {
     /// Total number of frequency octaves to generate the noise with.
     ///
     /// The number of octaves control the _amount of detail_ in the noise
@@ -41,10 +42,14 @@ pub struct BasicMulti {
     /// The amplitude of each successive octave is equal to the product of the
     /// previous octave's amplitude and the persistence value. Increasing the
     /// persistence produces "rougher" noise.
+    ///
+    /// This is ignored if a spectral exponent has been set.
     pub persistence: f64,
 
     seed: u32,
-    sources: Vec<Perlin>,
+    sources: Vec<Source>,
+    last_octave_fraction: f64,
+    spectral_weights: Option<SpectralWeights>,
 }
 
 impl BasicMulti {
@@ -56,33 +61,98 @@ impl BasicMulti {
     pub const MAX_OCTAVES: usize = 32;
 
     pub fn new() -> Self {
+        Self::with_source(Perlin::new())
+    }
+}
+
+impl<Source> BasicMulti<Source>
+where
+    Source: Clone + Seedable,
+{
+    /// Creates basic multifractal noise using a copy of `source` for each octave.
+    ///
+    /// The copies are seeded the same way as the default `Perlin` octaves.
+    pub fn with_source(source: Source) -> Self {
         Self {
-            seed: Self::DEFAULT_SEED,
-            octaves: Self::DEFAULT_OCTAVES,
-            frequency: Self::DEFAULT_FREQUENCY,
-            lacunarity: Self::DEFAULT_LACUNARITY,
-            persistence: Self::DEFAULT_PERSISTENCE,
-            sources: super::build_sources(Self::DEFAULT_SEED, Self::DEFAULT_OCTAVES),
+            seed: BasicMulti::DEFAULT_SEED,
+            octaves: BasicMulti::DEFAULT_OCTAVES,
+            last_octave_fraction: 1.0,
+            frequency: BasicMulti::DEFAULT_FREQUENCY,
+            lacunarity: BasicMulti::DEFAULT_LACUNARITY,
+            persistence: BasicMulti::DEFAULT_PERSISTENCE,
+            sources: super::build_sources(
+                &source,
+                BasicMulti::DEFAULT_SEED,
+                BasicMulti::DEFAULT_OCTAVES,
+            ),
+            spectral_weights: None,
         }
     }
 }
 
+impl<Source> BasicMulti<Source> {
+    /// Weights the octaves with a spectral exponent `H` instead of the
+    /// persistence. The weights are precomputed for every octave, as described
+    /// on `SpectralWeights`. Setting the persistence switches back to
+    /// persistence weighting.
+    pub fn set_spectral_exponent(self, exponent: f64) -> Self {
+        Self {
+            spectral_weights: Some(SpectralWeights::new(
+                exponent,
+                self.lacunarity,
+                BasicMulti::MAX_OCTAVES,
+            )),
+            ..self
+        }
+    }
+
+    /// Returns the spectral exponent `H`, if the octaves are weighted by one.
+    pub fn spectral_exponent(&self) -> Option<f64> {
+        self.spectral_weights
+            .as_ref()
+            .map(|weights| weights.exponent)
+    }
+
+    fn octave_weight(&self, octave: usize) -> f64 {
+        match self.spectral_weights {
+            Some(ref spectral_weights) => spectral_weights.weights[octave],
+            None => self.persistence.powi(octave as i32),
+        }
+    }
+
+    fn filtered_octaves(&self, filter_width: f64) -> (usize, f64) {
+        super::filtered_octaves(
+            self.octaves,
+            self.last_octave_fraction,
+            self.frequency,
+            self.lacunarity,
+            filter_width,
+        )
+    }
+}
+
 impl Default for BasicMulti {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl MultiFractal for BasicMulti {
-    fn set_octaves(self, mut octaves: usize) -> Self {
-        if self.octaves == octaves {
-            return self;
-        }
+impl<Source> MultiFractal for BasicMulti<Source>
+where
+    Source: Clone + Seedable,
+{
+    fn set_octaves(self, octaves: usize) -> Self {
+        self.set_fractional_octaves(octaves as f64)
+    }
+
+    fn set_fractional_octaves(self, octaves: f64) -> Self {
+        let (octaves, last_octave_fraction, sources) =
+            super::fractional_octaves(self.sources, self.seed, octaves, BasicMulti::MAX_OCTAVES);
 
-        octaves = math::clamp(octaves, 1, Self::MAX_OCTAVES);
         Self {
             octaves,
-            sources: super::build_sources(self.seed, octaves),
+            last_octave_fraction,
+            sources,
             ..self
         }
     }
@@ -92,18 +162,28 @@ impl MultiFractal for BasicMulti {
     }
 
     fn set_lacunarity(self, lacunarity: f64) -> Self {
-        Self { lacunarity, ..self }
+        Self {
+            lacunarity,
+            spectral_weights: self.spectral_weights.as_ref().map(|weights| {
+                SpectralWeights::new(weights.exponent, lacunarity, BasicMulti::MAX_OCTAVES)
+            }),
+            ..self
+        }
     }
 
     fn set_persistence(self, persistence: f64) -> Self {
         Self {
             persistence,
+            spectral_weights: None,
             ..self
         }
     }
 }
 
-impl Seedable for BasicMulti {
+impl<Source> Seedable for BasicMulti<Source>
+where
+    Source: Clone + Seedable,
+{
     fn set_seed(self, seed: u32) -> Self {
         if self.seed == seed {
             return self;
@@ -111,7 +191,7 @@ impl Seedable for BasicMulti {
 
         Self {
             seed,
-            sources: super::build_sources(seed, self.octaves),
+            sources: super::build_sources(&self.sources[0], seed, self.octaves),
             ..self
         }
     }
@@ -122,14 +202,23 @@ impl Seedable for BasicMulti {
 }
 
 /// 2-dimensional `BasicMulti` noise
-impl NoiseFn<Point2<f64>> for BasicMulti {
-    fn get(&self, mut point: Point2<f64>) -> f64 {
+impl<Source> NoiseFn<Point2<f64>> for BasicMulti<Source>
+where
+    Source: NoiseFn<Point2<f64>>,
+{
+    fn get(&self, point: Point2<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, mut point: Point2<f64>, filter_width: f64) -> f64 {
+        let (octaves, last_octave_fraction) = self.filtered_octaves(filter_width);
+
         // First unscaled octave of function; later octaves are scaled.
         point = math::mul2(point, self.frequency);
         let mut result = self.sources[0].get(point);
 
         // Spectral construction inner loop, where the fractal is built.
-        for x in 1..self.octaves {
+        for x in 1..octaves {
             // Raise the spatial frequency.
             point = math::mul2(point, self.lacunarity);
 
@@ -137,7 +226,13 @@ impl NoiseFn<Point2<f64>> for BasicMulti {
             let mut signal = self.sources[x].get(point);
 
             // Scale the amplitude appropriately for this frequency.
-            signal *= self.persistence.powi(x as i32);
+            signal *= self.octave_weight(x);
+
+            // Fade in the last octave by the fractional part of the octave
+            // count.
+            if x == octaves - 1 {
+                signal *= last_octave_fraction;
+            }
 
             // Scale the signal by the current 'altitude' of the function.
             signal *= result;
@@ -152,14 +247,23 @@ impl NoiseFn<Point2<f64>> for BasicMulti {
 }
 
 /// 3-dimensional `BasicMulti` noise
-impl NoiseFn<Point3<f64>> for BasicMulti {
-    fn get(&self, mut point: Point3<f64>) -> f64 {
+impl<Source> NoiseFn<Point3<f64>> for BasicMulti<Source>
+where
+    Source: NoiseFn<Point3<f64>>,
+{
+    fn get(&self, point: Point3<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, mut point: Point3<f64>, filter_width: f64) -> f64 {
+        let (octaves, last_octave_fraction) = self.filtered_octaves(filter_width);
+
         // First unscaled octave of function; later octaves are scaled.
         point = math::mul3(point, self.frequency);
         let mut result = self.sources[0].get(point);
 
         // Spectral construction inner loop, where the fractal is built.
-        for x in 1..self.octaves {
+        for x in 1..octaves {
             // Raise the spatial frequency.
             point = math::mul3(point, self.lacunarity);
 
@@ -167,7 +271,13 @@ impl NoiseFn<Point3<f64>> for BasicMulti {
             let mut signal = self.sources[x].get(point);
 
             // Scale the amplitude appropriately for this frequency.
-            signal *= self.persistence.powi(x as i32);
+            signal *= self.octave_weight(x);
+
+            // Fade in the last octave by the fractional part of the octave
+            // count.
+            if x == octaves - 1 {
+                signal *= last_octave_fraction;
+            }
 
             // Scale the signal by the current 'altitude' of the function.
             signal *= result;
@@ -182,14 +292,23 @@ impl NoiseFn<Point3<f64>> for BasicMulti {
 }
 
 /// 4-dimensional `BasicMulti` noise
-impl NoiseFn<Point4<f64>> for BasicMulti {
-    fn get(&self, mut point: Point4<f64>) -> f64 {
+impl<Source> NoiseFn<Point4<f64>> for BasicMulti<Source>
+where
+    Source: NoiseFn<Point4<f64>>,
+{
+    fn get(&self, point: Point4<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, mut point: Point4<f64>, filter_width: f64) -> f64 {
+        let (octaves, last_octave_fraction) = self.filtered_octaves(filter_width);
+
         // First unscaled octave of function; later octaves are scaled.
         point = math::mul4(point, self.frequency);
         let mut result = self.sources[0].get(point);
 
         // Spectral construction inner loop, where the fractal is built.
-        for x in 1..self.octaves {
+        for x in 1..octaves {
             // Raise the spatial frequency.
             point = math::mul4(point, self.lacunarity);
 
@@ -197,7 +316,13 @@ impl NoiseFn<Point4<f64>> for BasicMulti {
             let mut signal = self.sources[x].get(point);
 
             // Scale the amplitude appropriately for this frequency.
-            signal *= self.persistence.powi(x as i32);
+            signal *= self.octave_weight(x);
+
+            // Fade in the last octave by the fractional part of the octave
+            // count.
+            if x == octaves - 1 {
+                signal *= last_octave_fraction;
+            }
 
             // Scale the signal by the current 'altitude' of the function.
             signal *= result;
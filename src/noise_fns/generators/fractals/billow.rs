@@ -10,7 +10,7 @@ use std;
 /// function modifies each octave with an absolute-value function. See the
 /// documentation for fBm for more information.
 #[derive(Clone, Debug)]
-pub struct Billow {
+pub struct Billow<Source = Perlin> {
     /// Total number of frequency octaves to generate the noise with.
     ///
     /// The number of octaves control the _amount of detail_ in the noise
@@ -40,7 +40,8 @@ pub struct Billow {
     pub persistence: f64,
 
     seed: u32,
-    sources: Vec<Perlin>,
+    sources: Vec<Source>,
+    last_octave_fraction: f64,
 }
 
 impl Billow {
@@ -52,33 +53,68 @@ impl Billow {
     pub const MAX_OCTAVES: usize = 32;
 
     pub fn new() -> Self {
+        Self::with_source(Perlin::new())
+    }
+}
+
+impl<Source> Billow<Source>
+where
+    Source: Clone + Seedable,
+{
+    /// Creates billow noise using a copy of `source` for each octave.
+    ///
+    /// The copies are seeded the same way as the default `Perlin` octaves.
+    pub fn with_source(source: Source) -> Self {
         Self {
-            seed: Self::DEFAULT_SEED,
-            octaves: Self::DEFAULT_OCTAVE_COUNT,
-            frequency: Self::DEFAULT_FREQUENCY,
-            lacunarity: Self::DEFAULT_LACUNARITY,
-            persistence: Self::DEFAULT_PERSISTENCE,
-            sources: super::build_sources(Self::DEFAULT_SEED, Self::DEFAULT_OCTAVE_COUNT),
+            seed: Billow::DEFAULT_SEED,
+            octaves: Billow::DEFAULT_OCTAVE_COUNT,
+            last_octave_fraction: 1.0,
+            frequency: Billow::DEFAULT_FREQUENCY,
+            lacunarity: Billow::DEFAULT_LACUNARITY,
+            persistence: Billow::DEFAULT_PERSISTENCE,
+            sources: super::build_sources(
+                &source,
+                Billow::DEFAULT_SEED,
+                Billow::DEFAULT_OCTAVE_COUNT,
+            ),
         }
     }
 }
 
+impl<Source> Billow<Source> {
+    fn filtered_octaves(&self, filter_width: f64) -> (usize, f64) {
+        super::filtered_octaves(
+            self.octaves,
+            self.last_octave_fraction,
+            self.frequency,
+            self.lacunarity,
+            filter_width,
+        )
+    }
+}
+
 impl Default for Billow {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl MultiFractal for Billow {
-    fn set_octaves(self, mut octaves: usize) -> Self {
-        if self.octaves == octaves {
-            return self;
-        }
+impl<Source> MultiFractal for Billow<Source>
+where
+    Source: Clone + Seedable,
+{
+    fn set_octaves(self, octaves: usize) -> Self {
+        self.set_fractional_octaves(octaves as f64)
+    }
+
+    fn set_fractional_octaves(self, octaves: f64) -> Self {
+        let (octaves, last_octave_fraction, sources) =
+            super::fractional_octaves(self.sources, self.seed, octaves, Billow::MAX_OCTAVES);
 
-        octaves = math::clamp(octaves, 1, Self::MAX_OCTAVES);
         Self {
             octaves,
-            sources: super::build_sources(self.seed, octaves),
+            last_octave_fraction,
+            sources,
             ..self
         }
     }
@@ -99,7 +135,10 @@ impl MultiFractal for Billow {
     }
 }
 
-impl Seedable for Billow {
+impl<Source> Seedable for Billow<Source>
+where
+    Source: Clone + Seedable,
+{
     fn set_seed(self, seed: u32) -> Self {
         if self.seed == seed {
             return self;
@@ -107,7 +146,7 @@ impl Seedable for Billow {
 
         Self {
             seed,
-            sources: super::build_sources(seed, self.octaves),
+            sources: super::build_sources(&self.sources[0], seed, self.octaves),
             ..self
         }
     }
@@ -118,13 +157,22 @@ impl Seedable for Billow {
 }
 
 /// 2-dimensional Billow noise
-impl NoiseFn<Point2<f64>> for Billow {
-    fn get(&self, mut point: Point2<f64>) -> f64 {
+impl<Source> NoiseFn<Point2<f64>> for Billow<Source>
+where
+    Source: NoiseFn<Point2<f64>>,
+{
+    fn get(&self, point: Point2<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, mut point: Point2<f64>, filter_width: f64) -> f64 {
+        let (octaves, last_octave_fraction) = self.filtered_octaves(filter_width);
+
         let mut result = 0.0;
 
         point = math::mul2(point, self.frequency);
 
-        for x in 0..self.octaves {
+        for x in 0..octaves {
             // Get the signal.
             let mut signal = self.sources[x].get(point);
 
@@ -135,6 +183,12 @@ impl NoiseFn<Point2<f64>> for Billow {
             // Scale the amplitude appropriately for this frequency.
             signal *= self.persistence.powi(x as i32);
 
+            // Fade in the last octave by the fractional part of the octave
+            // count.
+            if x == octaves - 1 {
+                signal *= last_octave_fraction;
+            }
+
             // Add the signal to the result.
             result += signal;
 
@@ -148,13 +202,22 @@ impl NoiseFn<Point2<f64>> for Billow {
 }
 
 /// 3-dimensional Billow noise
-impl NoiseFn<Point3<f64>> for Billow {
-    fn get(&self, mut point: Point3<f64>) -> f64 {
+impl<Source> NoiseFn<Point3<f64>> for Billow<Source>
+where
+    Source: NoiseFn<Point3<f64>>,
+{
+    fn get(&self, point: Point3<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, mut point: Point3<f64>, filter_width: f64) -> f64 {
+        let (octaves, last_octave_fraction) = self.filtered_octaves(filter_width);
+
         let mut result = 0.0;
 
         point = math::mul3(point, self.frequency);
 
-        for x in 0..self.octaves {
+        for x in 0..octaves {
             // Get the signal.
             let mut signal = self.sources[x].get(point);
 
@@ -165,6 +228,12 @@ impl NoiseFn<Point3<f64>> for Billow {
             // Scale the amplitude appropriately for this frequency.
             signal *= self.persistence.powi(x as i32);
 
+            // Fade in the last octave by the fractional part of the octave
+            // count.
+            if x == octaves - 1 {
+                signal *= last_octave_fraction;
+            }
+
             // Add the signal to the result.
             result += signal;
 
@@ -178,13 +247,22 @@ impl NoiseFn<Point3<f64>> for Billow {
 }
 
 /// 4-dimensional Billow noise
-impl NoiseFn<Point4<f64>> for Billow {
-    fn get(&self, mut point: Point4<f64>) -> f64 {
+impl<Source> NoiseFn<Point4<f64>> for Billow<Source>
+where
+    Source: NoiseFn<Point4<f64>>,
+{
+    fn get(&self, point: Point4<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, mut point: Point4<f64>, filter_width: f64) -> f64 {
+        let (octaves, last_octave_fraction) = self.filtered_octaves(filter_width);
+
         let mut result = 0.0;
 
         point = math::mul4(point, self.frequency);
 
-        for x in 0..self.octaves {
+        for x in 0..octaves {
             // Get the signal.
             let mut signal = self.sources[x].get(point);
 
@@ -195,6 +273,12 @@ impl NoiseFn<Point4<f64>> for Billow {
             // Scale the amplitude appropriately for this frequency.
             signal *= self.persistence.powi(x as i32);
 
+            // Fade in the last octave by the fractional part of the octave
+            // count.
+            if x == octaves - 1 {
+                signal *= last_octave_fraction;
+            }
+
             // Add the signal to the output value.
             result += signal;
 
@@ -0,0 +1,282 @@
+use math::interpolate;
+use math::{Point2, Point3, Point4};
+use noise_fns::{MultiFractal, NoiseFn, NoiseFnWithGradient, Perlin, Seedable};
+use std;
+
+/// Noise function that outputs derivative fBm noise, as described by Inigo
+/// Quilez.
+///
+/// This is fBm where the gradients of the octaves are summed as the octaves
+/// are added, and each octave's amplitude is divided by one plus the squared
+/// length of that sum. Octaves are damped on slopes and kept on flat areas
+/// such as peaks and valley floors, which gives an eroded look with smooth
+/// valleys and detailed plateaus.
+///
+/// The source must be able to calculate its gradient, so it must implement
+/// `NoiseFnWithGradient`.
+///
+/// The values output from this function will usually range from about -0.65
+/// to 0.65 with default values for the parameters, as the damping keeps the
+/// octaves from adding up to the full range of `Fbm`.
+#[derive(Clone, Debug)]
+pub struct DerivativeFbm<Source = Perlin> {
+    /// Total number of frequency octaves to generate the noise with.
+    ///
+    /// The number of octaves control the _amount of detail_ in the noise
+    /// function. Adding more octaves increases the detail, with the drawback
+    /// of increasing the calculation time.
+    pub octaves: usize,
+
+    /// The number of cycles per unit length that the noise function outputs.
+    pub frequency: f64,
+
+    /// A multiplier that determines how quickly the frequency increases for
+    /// each successive octave in the noise function.
+    ///
+    /// The frequency of each successive octave is equal to the product of the
+    /// previous octave's frequency and the lacunarity value.
+    ///
+    /// A lacunarity of 2.0 results in the frequency doubling every octave. For
+    /// almost all cases, 2.0 is a good value to use.
+    pub lacunarity: f64,
+
+    /// A multiplier that determines how quickly the amplitudes diminish for
+    /// each successive octave in the noise function.
+    ///
+    /// The amplitude of each successive octave is equal to the product of the
+    /// previous octave's amplitude and the persistence value. Increasing the
+    /// persistence produces "rougher" noise.
+    pub persistence: f64,
+
+    seed: u32,
+    sources: Vec<Source>,
+    last_octave_fraction: f64,
+}
+
+impl DerivativeFbm {
+    pub const DEFAULT_SEED: u32 = 0;
+    pub const DEFAULT_OCTAVE_COUNT: usize = 6;
+    pub const DEFAULT_FREQUENCY: f64 = 1.0;
+    pub const DEFAULT_LACUNARITY: f64 = std::f64::consts::PI * 2.0 / 3.0;
+    pub const DEFAULT_PERSISTENCE: f64 = 0.5;
+    pub const MAX_OCTAVES: usize = 32;
+
+    pub fn new() -> Self {
+        Self::with_source(Perlin::new())
+    }
+}
+
+impl<Source> DerivativeFbm<Source>
+where
+    Source: Clone + Seedable,
+{
+    /// Creates derivative fBm noise using a copy of `source` for each octave.
+    ///
+    /// The copies are seeded the same way as the default `Perlin` octaves.
+    pub fn with_source(source: Source) -> Self {
+        Self {
+            seed: DerivativeFbm::DEFAULT_SEED,
+            octaves: DerivativeFbm::DEFAULT_OCTAVE_COUNT,
+            last_octave_fraction: 1.0,
+            frequency: DerivativeFbm::DEFAULT_FREQUENCY,
+            lacunarity: DerivativeFbm::DEFAULT_LACUNARITY,
+            persistence: DerivativeFbm::DEFAULT_PERSISTENCE,
+            sources: super::build_sources(
+                &source,
+                DerivativeFbm::DEFAULT_SEED,
+                DerivativeFbm::DEFAULT_OCTAVE_COUNT,
+            ),
+        }
+    }
+}
+
+impl<Source> DerivativeFbm<Source> {
+    fn get_octaves<const N: usize>(&self, mut point: [f64; N], filter_width: f64) -> f64
+    where
+        Source: NoiseFnWithGradient<[f64; N]>,
+    {
+        let (octaves, last_octave_fraction) = self.filtered_octaves(filter_width);
+
+        let mut result = 0.0;
+        let mut gradient_sum = [0.0; N];
+
+        for coordinate in point.iter_mut() {
+            *coordinate *= self.frequency;
+        }
+
+        for x in 0..octaves {
+            // Get the signal and its gradient.
+            let (mut signal, gradient) = self.sources[x].get_with_gradient(point);
+
+            // Accumulate the gradient, and damp the signal on steep slopes.
+            for (sum, derivative) in gradient_sum.iter_mut().zip(gradient.iter()) {
+                *sum += derivative;
+            }
+            let slope: f64 = gradient_sum.iter().map(|sum| sum * sum).sum();
+            signal /= 1.0 + slope;
+
+            // Scale the amplitude appropriately for this frequency.
+            signal *= self.persistence.powi(x as i32);
+
+            // Fade in the last octave by the fractional part of the octave
+            // count.
+            if x == octaves - 1 {
+                signal *= last_octave_fraction;
+            }
+
+            // Add the signal to the result.
+            result += signal;
+
+            // Increase the frequency for the next octave.
+            for coordinate in point.iter_mut() {
+                *coordinate *= self.lacunarity;
+            }
+        }
+
+        // Scale the result by the same factor as `Fbm`, blending towards the
+        // scale for one octave fewer while the last octave fades in. The
+        // damping keeps the result well inside the [-1,1] range.
+        let mut scale = 2.0 - self.persistence.powi(octaves as i32 - 1);
+        if last_octave_fraction < 1.0 {
+            let previous_scale = 2.0 - self.persistence.powi(octaves as i32 - 2);
+            scale = interpolate::linear(previous_scale, scale, last_octave_fraction);
+        }
+        result / scale
+    }
+
+    fn filtered_octaves(&self, filter_width: f64) -> (usize, f64) {
+        super::filtered_octaves(
+            self.octaves,
+            self.last_octave_fraction,
+            self.frequency,
+            self.lacunarity,
+            filter_width,
+        )
+    }
+}
+
+impl Default for DerivativeFbm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Source> MultiFractal for DerivativeFbm<Source>
+where
+    Source: Clone + Seedable,
+{
+    fn set_octaves(self, octaves: usize) -> Self {
+        self.set_fractional_octaves(octaves as f64)
+    }
+
+    fn set_fractional_octaves(self, octaves: f64) -> Self {
+        let (octaves, last_octave_fraction, sources) =
+            super::fractional_octaves(self.sources, self.seed, octaves, DerivativeFbm::MAX_OCTAVES);
+
+        Self {
+            octaves,
+            last_octave_fraction,
+            sources,
+            ..self
+        }
+    }
+
+    fn set_frequency(self, frequency: f64) -> Self {
+        Self { frequency, ..self }
+    }
+
+    fn set_lacunarity(self, lacunarity: f64) -> Self {
+        Self { lacunarity, ..self }
+    }
+
+    fn set_persistence(self, persistence: f64) -> Self {
+        Self {
+            persistence,
+            ..self
+        }
+    }
+}
+
+impl<Source> Seedable for DerivativeFbm<Source>
+where
+    Source: Clone + Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        if self.seed == seed {
+            return self;
+        }
+
+        Self {
+            seed,
+            sources: super::build_sources(&self.sources[0], seed, self.octaves),
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+/// 2-dimensional `DerivativeFbm` noise
+impl<Source> NoiseFn<Point2<f64>> for DerivativeFbm<Source>
+where
+    Source: NoiseFnWithGradient<Point2<f64>>,
+{
+    fn get(&self, point: Point2<f64>) -> f64 {
+        self.get_octaves(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point2<f64>, filter_width: f64) -> f64 {
+        self.get_octaves(point, filter_width)
+    }
+}
+
+/// 3-dimensional `DerivativeFbm` noise
+impl<Source> NoiseFn<Point3<f64>> for DerivativeFbm<Source>
+where
+    Source: NoiseFnWithGradient<Point3<f64>>,
+{
+    fn get(&self, point: Point3<f64>) -> f64 {
+        self.get_octaves(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point3<f64>, filter_width: f64) -> f64 {
+        self.get_octaves(point, filter_width)
+    }
+}
+
+/// 4-dimensional `DerivativeFbm` noise
+impl<Source> NoiseFn<Point4<f64>> for DerivativeFbm<Source>
+where
+    Source: NoiseFnWithGradient<Point4<f64>>,
+{
+    fn get(&self, point: Point4<f64>) -> f64 {
+        self.get_octaves(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point4<f64>, filter_width: f64) -> f64 {
+        self.get_octaves(point, filter_width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_octaves_damped_by_slope() {
+        let perlin = Perlin::new();
+        let derivative_fbm = DerivativeFbm::new().set_octaves(1);
+
+        for i in 0..64 {
+            let t = f64::from(i) * 0.173;
+            let point = [t, 0.5 - t * 0.3];
+
+            let (value, gradient) = perlin.get_with_gradient(point);
+            let slope = gradient[0] * gradient[0] + gradient[1] * gradient[1];
+
+            assert_eq!(derivative_fbm.get(point), value / (1.0 + slope));
+        }
+    }
+}
@@ -1,4 +1,6 @@
+use super::SpectralWeights;
 use math;
+use math::interpolate;
 use math::{Point2, Point3, Point4};
 use noise_fns::{MultiFractal, NoiseFn, Perlin, Seedable};
 use std;
@@ -20,7 +22,7 @@ use std;
 ///
 /// fBm is commonly referred to as Perlin noise.
 #[derive(Clone, Debug)]
-pub struct Fbm {
+pub struct Fbm<Source = Perlin> {
     /// Total number of frequency octaves to generate the noise with.
     ///
     /// The number of octaves control the _amount of detail_ in the noise
@@ -47,10 +49,14 @@ pub struct Fbm {
     /// The amplitude of each successive octave is equal to the product of the
     /// previous octave's amplitude and the persistence value. Increasing the
     /// persistence produces "rougher" noise.
+    ///
+    /// This is ignored if a spectral exponent has been set.
     pub persistence: f64,
 
     seed: u32,
-    sources: Vec<Perlin>,
+    sources: Vec<Source>,
+    last_octave_fraction: f64,
+    spectral_weights: Option<SpectralWeights>,
 }
 
 impl Fbm {
@@ -62,33 +68,94 @@ impl Fbm {
     pub const MAX_OCTAVES: usize = 32;
 
     pub fn new() -> Self {
+        Self::with_source(Perlin::new())
+    }
+}
+
+impl<Source> Fbm<Source>
+where
+    Source: Clone + Seedable,
+{
+    /// Creates fBm noise using a copy of `source` for each octave.
+    ///
+    /// The copies are seeded the same way as the default `Perlin` octaves.
+    pub fn with_source(source: Source) -> Self {
         Self {
-            seed: Self::DEFAULT_SEED,
-            octaves: Self::DEFAULT_OCTAVE_COUNT,
-            frequency: Self::DEFAULT_FREQUENCY,
-            lacunarity: Self::DEFAULT_LACUNARITY,
-            persistence: Self::DEFAULT_PERSISTENCE,
-            sources: super::build_sources(Self::DEFAULT_SEED, Self::DEFAULT_OCTAVE_COUNT),
+            seed: Fbm::DEFAULT_SEED,
+            octaves: Fbm::DEFAULT_OCTAVE_COUNT,
+            last_octave_fraction: 1.0,
+            frequency: Fbm::DEFAULT_FREQUENCY,
+            lacunarity: Fbm::DEFAULT_LACUNARITY,
+            persistence: Fbm::DEFAULT_PERSISTENCE,
+            sources: super::build_sources(&source, Fbm::DEFAULT_SEED, Fbm::DEFAULT_OCTAVE_COUNT),
+            spectral_weights: None,
         }
     }
 }
 
+impl<Source> Fbm<Source> {
+    /// Weights the octaves with a spectral exponent `H` instead of the
+    /// persistence. The weights are precomputed for every octave, as described
+    /// on `SpectralWeights`. Setting the persistence switches back to
+    /// persistence weighting.
+    pub fn set_spectral_exponent(self, exponent: f64) -> Self {
+        Self {
+            spectral_weights: Some(SpectralWeights::new(
+                exponent,
+                self.lacunarity,
+                Fbm::MAX_OCTAVES,
+            )),
+            ..self
+        }
+    }
+
+    /// Returns the spectral exponent `H`, if the octaves are weighted by one.
+    pub fn spectral_exponent(&self) -> Option<f64> {
+        self.spectral_weights
+            .as_ref()
+            .map(|weights| weights.exponent)
+    }
+
+    fn octave_weight(&self, octave: usize) -> f64 {
+        match self.spectral_weights {
+            Some(ref spectral_weights) => spectral_weights.weights[octave],
+            None => self.persistence.powi(octave as i32),
+        }
+    }
+
+    fn filtered_octaves(&self, filter_width: f64) -> (usize, f64) {
+        super::filtered_octaves(
+            self.octaves,
+            self.last_octave_fraction,
+            self.frequency,
+            self.lacunarity,
+            filter_width,
+        )
+    }
+}
+
 impl Default for Fbm {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl MultiFractal for Fbm {
-    fn set_octaves(self, mut octaves: usize) -> Self {
-        if self.octaves == octaves {
-            return self;
-        }
+impl<Source> MultiFractal for Fbm<Source>
+where
+    Source: Clone + Seedable,
+{
+    fn set_octaves(self, octaves: usize) -> Self {
+        self.set_fractional_octaves(octaves as f64)
+    }
+
+    fn set_fractional_octaves(self, octaves: f64) -> Self {
+        let (octaves, last_octave_fraction, sources) =
+            super::fractional_octaves(self.sources, self.seed, octaves, Fbm::MAX_OCTAVES);
 
-        octaves = math::clamp(octaves, 1, Self::MAX_OCTAVES);
         Self {
             octaves,
-            sources: super::build_sources(self.seed, octaves),
+            last_octave_fraction,
+            sources,
             ..self
         }
     }
@@ -98,18 +165,28 @@ impl MultiFractal for Fbm {
     }
 
     fn set_lacunarity(self, lacunarity: f64) -> Self {
-        Self { lacunarity, ..self }
+        Self {
+            lacunarity,
+            spectral_weights: self.spectral_weights.as_ref().map(|weights| {
+                SpectralWeights::new(weights.exponent, lacunarity, Fbm::MAX_OCTAVES)
+            }),
+            ..self
+        }
     }
 
     fn set_persistence(self, persistence: f64) -> Self {
         Self {
             persistence,
+            spectral_weights: None,
             ..self
         }
     }
 }
 
-impl Seedable for Fbm {
+impl<Source> Seedable for Fbm<Source>
+where
+    Source: Clone + Seedable,
+{
     fn set_seed(self, seed: u32) -> Self {
         if self.seed == seed {
             return self;
@@ -117,7 +194,7 @@ impl Seedable for Fbm {
 
         Self {
             seed,
-            sources: super::build_sources(seed, self.octaves),
+            sources: super::build_sources(&self.sources[0], seed, self.octaves),
             ..self
         }
     }
@@ -128,18 +205,33 @@ impl Seedable for Fbm {
 }
 
 /// 2-dimensional Fbm noise
-impl NoiseFn<Point2<f64>> for Fbm {
-    fn get(&self, mut point: Point2<f64>) -> f64 {
+impl<Source> NoiseFn<Point2<f64>> for Fbm<Source>
+where
+    Source: NoiseFn<Point2<f64>>,
+{
+    fn get(&self, point: Point2<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, mut point: Point2<f64>, filter_width: f64) -> f64 {
+        let (octaves, last_octave_fraction) = self.filtered_octaves(filter_width);
+
         let mut result = 0.0;
 
         point = math::mul2(point, self.frequency);
 
-        for x in 0..self.octaves {
+        for x in 0..octaves {
             // Get the signal.
             let mut signal = self.sources[x].get(point);
 
             // Scale the amplitude appropriately for this frequency.
-            signal *= self.persistence.powi(x as i32);
+            signal *= self.octave_weight(x);
+
+            // Fade in the last octave by the fractional part of the octave
+            // count.
+            if x == octaves - 1 {
+                signal *= last_octave_fraction;
+            }
 
             // Add the signal to the result.
             result += signal;
@@ -148,25 +240,45 @@ impl NoiseFn<Point2<f64>> for Fbm {
             point = math::mul2(point, self.lacunarity);
         }
 
-        // Scale and shift the result into the [-1,1] range
-        let scale = 2.0 - self.persistence.powi(self.octaves as i32 - 1);
+        // Scale and shift the result into the [-1,1] range, blending towards
+        // the scale for one octave fewer while the last octave fades in.
+        let mut scale = 2.0 - self.octave_weight(octaves - 1);
+        if last_octave_fraction < 1.0 {
+            let previous_scale = 2.0 - self.octave_weight(octaves - 2);
+            scale = interpolate::linear(previous_scale, scale, last_octave_fraction);
+        }
         result / scale
     }
 }
 
 /// 3-dimensional Fbm noise
-impl NoiseFn<Point3<f64>> for Fbm {
-    fn get(&self, mut point: Point3<f64>) -> f64 {
+impl<Source> NoiseFn<Point3<f64>> for Fbm<Source>
+where
+    Source: NoiseFn<Point3<f64>>,
+{
+    fn get(&self, point: Point3<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, mut point: Point3<f64>, filter_width: f64) -> f64 {
+        let (octaves, last_octave_fraction) = self.filtered_octaves(filter_width);
+
         let mut result = 0.0;
 
         point = math::mul3(point, self.frequency);
 
-        for x in 0..self.octaves {
+        for x in 0..octaves {
             // Get the signal.
             let mut signal = self.sources[x].get(point);
 
             // Scale the amplitude appropriately for this frequency.
-            signal *= self.persistence.powi(x as i32);
+            signal *= self.octave_weight(x);
+
+            // Fade in the last octave by the fractional part of the octave
+            // count.
+            if x == octaves - 1 {
+                signal *= last_octave_fraction;
+            }
 
             // Add the signal to the result.
             result += signal;
@@ -175,25 +287,45 @@ impl NoiseFn<Point3<f64>> for Fbm {
             point = math::mul3(point, self.lacunarity);
         }
 
-        // Scale and shift the result into the [-1,1] range
-        let scale = 2.0 - self.persistence.powi(self.octaves as i32 - 1);
+        // Scale and shift the result into the [-1,1] range, blending towards
+        // the scale for one octave fewer while the last octave fades in.
+        let mut scale = 2.0 - self.octave_weight(octaves - 1);
+        if last_octave_fraction < 1.0 {
+            let previous_scale = 2.0 - self.octave_weight(octaves - 2);
+            scale = interpolate::linear(previous_scale, scale, last_octave_fraction);
+        }
         result / scale
     }
 }
 
 /// 4-dimensional Fbm noise
-impl NoiseFn<Point4<f64>> for Fbm {
-    fn get(&self, mut point: Point4<f64>) -> f64 {
+impl<Source> NoiseFn<Point4<f64>> for Fbm<Source>
+where
+    Source: NoiseFn<Point4<f64>>,
+{
+    fn get(&self, point: Point4<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, mut point: Point4<f64>, filter_width: f64) -> f64 {
+        let (octaves, last_octave_fraction) = self.filtered_octaves(filter_width);
+
         let mut result = 0.0;
 
         point = math::mul4(point, self.frequency);
 
-        for x in 0..self.octaves {
+        for x in 0..octaves {
             // Get the signal.
             let mut signal = self.sources[x].get(point);
 
             // Scale the amplitude appropriately for this frequency.
-            signal *= self.persistence.powi(x as i32);
+            signal *= self.octave_weight(x);
+
+            // Fade in the last octave by the fractional part of the octave
+            // count.
+            if x == octaves - 1 {
+                signal *= last_octave_fraction;
+            }
 
             // Add the signal to the result.
             result += signal;
@@ -202,8 +334,52 @@ impl NoiseFn<Point4<f64>> for Fbm {
             point = math::mul4(point, self.lacunarity);
         }
 
-        // Scale and shift the result into the [-1,1] range
-        let scale = 2.0 - self.persistence.powi(self.octaves as i32 - 1);
+        // Scale and shift the result into the [-1,1] range, blending towards
+        // the scale for one octave fewer while the last octave fades in.
+        let mut scale = 2.0 - self.octave_weight(octaves - 1);
+        if last_octave_fraction < 1.0 {
+            let previous_scale = 2.0 - self.octave_weight(octaves - 2);
+            scale = interpolate::linear(previous_scale, scale, last_octave_fraction);
+        }
         result / scale
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noise_fns::tests::assert_tiles;
+    use noise_fns::OpenSimplex;
+
+    #[test]
+    fn test_with_source_seeds_each_octave() {
+        let fbm = Fbm::with_source(OpenSimplex::new())
+            .set_seed(5)
+            .set_octaves(3);
+        let octaves: Vec<_> = (0..3).map(|x| OpenSimplex::new().set_seed(5 + x)).collect();
+
+        for i in 0..64 {
+            let t = f64::from(i) * 0.173;
+            let mut point = [t, 0.5 - t * 0.3, t * 0.7 + 1.1];
+
+            let mut expected = 0.0;
+            point = math::mul3(point, fbm.frequency);
+            for (x, octave) in octaves.iter().enumerate() {
+                expected += octave.get(point) * fbm.persistence.powi(x as i32);
+                point = math::mul3(point, fbm.lacunarity);
+            }
+            expected /= 2.0 - fbm.persistence.powi(2);
+
+            assert_eq!(fbm.get([t, 0.5 - t * 0.3, t * 0.7 + 1.1]), expected);
+        }
+    }
+
+    #[test]
+    fn test_periodic_noise_tiles() {
+        // Every octave repeats over the period when the lacunarity is a whole
+        // number, so the fractal does too.
+        let fbm = Fbm::with_source(Perlin::new().set_period([4; 4])).set_lacunarity(2.0);
+        assert_tiles(&fbm, [4.0; 2]);
+        assert_tiles(&fbm, [4.0; 3]);
+    }
+}
@@ -0,0 +1,358 @@
+use math::{Point2, Point3, Point4};
+use noise_fns::{MultiFractal, NoiseFn, NoiseFnWithGradient, Perlin, Seedable};
+use std;
+
+/// Noise function that outputs Jordan turbulence, as described by Giliam de
+/// Carpentier.
+///
+/// Each octave is squared, which gives rounded hills with sharp creases
+/// between them. The octaves are offset by the sum of the previous octaves'
+/// gradients, and their amplitudes are damped where that sum is small. This
+/// keeps flat areas smooth while steep areas get eroded-looking detail.
+///
+/// The first octave has its own gain, warp and damping, which control the
+/// overall shape of the terrain separately from the finer detail.
+///
+/// The source must be able to calculate its gradient, so it must implement
+/// `NoiseFnWithGradient`.
+///
+/// The values output from this function never go below -1.0, and will reach
+/// up to about 0.35 with default values for the parameters. If the parameters
+/// are modified from their defaults, then the output may need to be scaled to
+/// keep the peaks in range.
+#[derive(Clone, Debug)]
+pub struct JordanTurbulence<Source = Perlin> {
+    /// Total number of frequency octaves to generate the noise with.
+    ///
+    /// The number of octaves control the _amount of detail_ in the noise
+    /// function. Adding more octaves increases the detail, with the drawback
+    /// of increasing the calculation time.
+    pub octaves: usize,
+
+    /// The number of cycles per unit length that the noise function outputs.
+    pub frequency: f64,
+
+    /// A multiplier that determines how quickly the frequency increases for
+    /// each successive octave in the noise function.
+    ///
+    /// The frequency of each successive octave is equal to the product of the
+    /// previous octave's frequency and the lacunarity value.
+    ///
+    /// A lacunarity of 2.0 results in the frequency doubling every octave. For
+    /// almost all cases, 2.0 is a good value to use.
+    pub lacunarity: f64,
+
+    /// A multiplier that determines how quickly the amplitudes diminish for
+    /// each successive octave in the noise function.
+    ///
+    /// The amplitude of each successive octave is equal to the product of the
+    /// previous octave's amplitude and the persistence value. Increasing the
+    /// persistence produces "rougher" noise.
+    pub persistence: f64,
+
+    /// Amplitude of the second octave, relative to the first octave.
+    pub initial_gain: f64,
+
+    /// How far the octaves are offset by the gradient of the first octave.
+    pub initial_warp: f64,
+
+    /// How far the octaves are offset by the gradients of the octaves after
+    /// the first one.
+    pub warp: f64,
+
+    /// How much the gradient of the first octave damps the amplitudes of the
+    /// octaves after it.
+    pub initial_damping: f64,
+
+    /// How much the gradients of the octaves after the first one damp the
+    /// amplitudes of the octaves after them.
+    pub damping: f64,
+
+    /// The strength of the damping. A value of 0.0 disables it, and a value
+    /// of 1.0 removes detail entirely from areas that are completely flat.
+    pub damping_scale: f64,
+
+    seed: u32,
+    sources: Vec<Source>,
+    last_octave_fraction: f64,
+}
+
+impl JordanTurbulence {
+    pub const DEFAULT_SEED: u32 = 0;
+    pub const DEFAULT_OCTAVE_COUNT: usize = 6;
+    pub const DEFAULT_FREQUENCY: f64 = 1.0;
+    pub const DEFAULT_LACUNARITY: f64 = std::f64::consts::PI * 2.0 / 3.0;
+    pub const DEFAULT_PERSISTENCE: f64 = 0.5;
+    pub const DEFAULT_INITIAL_GAIN: f64 = 0.8;
+    pub const DEFAULT_INITIAL_WARP: f64 = 0.4;
+    pub const DEFAULT_WARP: f64 = 0.35;
+    pub const DEFAULT_INITIAL_DAMPING: f64 = 1.0;
+    pub const DEFAULT_DAMPING: f64 = 0.8;
+    pub const DEFAULT_DAMPING_SCALE: f64 = 1.0;
+    pub const MAX_OCTAVES: usize = 32;
+
+    pub fn new() -> Self {
+        Self::with_source(Perlin::new())
+    }
+}
+
+impl<Source> JordanTurbulence<Source>
+where
+    Source: Clone + Seedable,
+{
+    /// Creates Jordan turbulence using a copy of `source` for each octave.
+    ///
+    /// The copies are seeded the same way as the default `Perlin` octaves.
+    pub fn with_source(source: Source) -> Self {
+        Self {
+            initial_gain: JordanTurbulence::DEFAULT_INITIAL_GAIN,
+            initial_warp: JordanTurbulence::DEFAULT_INITIAL_WARP,
+            warp: JordanTurbulence::DEFAULT_WARP,
+            initial_damping: JordanTurbulence::DEFAULT_INITIAL_DAMPING,
+            damping: JordanTurbulence::DEFAULT_DAMPING,
+            damping_scale: JordanTurbulence::DEFAULT_DAMPING_SCALE,
+            seed: JordanTurbulence::DEFAULT_SEED,
+            octaves: JordanTurbulence::DEFAULT_OCTAVE_COUNT,
+            last_octave_fraction: 1.0,
+            frequency: JordanTurbulence::DEFAULT_FREQUENCY,
+            lacunarity: JordanTurbulence::DEFAULT_LACUNARITY,
+            persistence: JordanTurbulence::DEFAULT_PERSISTENCE,
+            sources: super::build_sources(
+                &source,
+                JordanTurbulence::DEFAULT_SEED,
+                JordanTurbulence::DEFAULT_OCTAVE_COUNT,
+            ),
+        }
+    }
+}
+
+impl<Source> JordanTurbulence<Source> {
+    pub fn set_initial_gain(self, initial_gain: f64) -> Self {
+        Self {
+            initial_gain,
+            ..self
+        }
+    }
+
+    pub fn set_initial_warp(self, initial_warp: f64) -> Self {
+        Self {
+            initial_warp,
+            ..self
+        }
+    }
+
+    pub fn set_warp(self, warp: f64) -> Self {
+        Self { warp, ..self }
+    }
+
+    pub fn set_initial_damping(self, initial_damping: f64) -> Self {
+        Self {
+            initial_damping,
+            ..self
+        }
+    }
+
+    pub fn set_damping(self, damping: f64) -> Self {
+        Self { damping, ..self }
+    }
+
+    pub fn set_damping_scale(self, damping_scale: f64) -> Self {
+        Self {
+            damping_scale,
+            ..self
+        }
+    }
+
+    fn get_octaves<const N: usize>(&self, point: [f64; N], filter_width: f64) -> f64
+    where
+        Source: NoiseFnWithGradient<[f64; N]>,
+    {
+        let (octaves, last_octave_fraction) = self.filtered_octaves(filter_width);
+
+        let mut scaled_point = point;
+        for coordinate in scaled_point.iter_mut() {
+            *coordinate *= self.frequency;
+        }
+
+        // The first octave sets the overall shape, and is always added in
+        // full.
+        let (value, gradient) = self.sources[0].get_with_gradient(scaled_point);
+        let mut result = value * value;
+
+        let mut warp_sum = [0.0; N];
+        let mut damping_sum = [0.0; N];
+        for (warp, (damping, derivative)) in warp_sum
+            .iter_mut()
+            .zip(damping_sum.iter_mut().zip(gradient.iter()))
+        {
+            *warp = self.initial_warp * derivative * value;
+            *damping = self.initial_damping * derivative * value;
+        }
+
+        let mut amplitude = self.initial_gain;
+        let mut damped_amplitude = amplitude * self.persistence;
+        let mut frequency = self.frequency * self.lacunarity;
+
+        for x in 1..octaves {
+            // Scale the point to the frequency of this octave, then warp it by
+            // the gradients so far.
+            let mut warped_point = point;
+            for (coordinate, warp) in warped_point.iter_mut().zip(warp_sum.iter()) {
+                *coordinate = *coordinate * frequency + warp;
+            }
+
+            // Get the signal and its gradient, and square it.
+            let (value, gradient) = self.sources[x].get_with_gradient(warped_point);
+            let mut signal = value * value * damped_amplitude;
+
+            // Fade in the last octave by the fractional part of the octave
+            // count.
+            if x == octaves - 1 {
+                signal *= last_octave_fraction;
+            }
+
+            // Add the signal to the result.
+            result += signal;
+
+            // Accumulate the gradient of the squared signal.
+            for (warp, (damping, derivative)) in warp_sum
+                .iter_mut()
+                .zip(damping_sum.iter_mut().zip(gradient.iter()))
+            {
+                *warp += self.warp * derivative * value;
+                *damping += self.damping * derivative * value;
+            }
+
+            // Damp the next octave where the terrain is flat.
+            amplitude *= self.persistence;
+            let slope: f64 = damping_sum.iter().map(|sum| sum * sum).sum();
+            damped_amplitude = amplitude * (1.0 - self.damping_scale / (1.0 + slope));
+
+            // Increase the frequency for the next octave.
+            frequency *= self.lacunarity;
+        }
+
+        // The sum is never negative, so shifting it down by one puts the
+        // lowest possible output at -1.0.
+        result - 1.0
+    }
+
+    fn filtered_octaves(&self, filter_width: f64) -> (usize, f64) {
+        super::filtered_octaves(
+            self.octaves,
+            self.last_octave_fraction,
+            self.frequency,
+            self.lacunarity,
+            filter_width,
+        )
+    }
+}
+
+impl Default for JordanTurbulence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Source> MultiFractal for JordanTurbulence<Source>
+where
+    Source: Clone + Seedable,
+{
+    fn set_octaves(self, octaves: usize) -> Self {
+        self.set_fractional_octaves(octaves as f64)
+    }
+
+    fn set_fractional_octaves(self, octaves: f64) -> Self {
+        let (octaves, last_octave_fraction, sources) = super::fractional_octaves(
+            self.sources,
+            self.seed,
+            octaves,
+            JordanTurbulence::MAX_OCTAVES,
+        );
+
+        Self {
+            octaves,
+            last_octave_fraction,
+            sources,
+            ..self
+        }
+    }
+
+    fn set_frequency(self, frequency: f64) -> Self {
+        Self { frequency, ..self }
+    }
+
+    fn set_lacunarity(self, lacunarity: f64) -> Self {
+        Self { lacunarity, ..self }
+    }
+
+    fn set_persistence(self, persistence: f64) -> Self {
+        Self {
+            persistence,
+            ..self
+        }
+    }
+}
+
+impl<Source> Seedable for JordanTurbulence<Source>
+where
+    Source: Clone + Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        if self.seed == seed {
+            return self;
+        }
+
+        Self {
+            seed,
+            sources: super::build_sources(&self.sources[0], seed, self.octaves),
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+/// 2-dimensional `JordanTurbulence` noise
+impl<Source> NoiseFn<Point2<f64>> for JordanTurbulence<Source>
+where
+    Source: NoiseFnWithGradient<Point2<f64>>,
+{
+    fn get(&self, point: Point2<f64>) -> f64 {
+        self.get_octaves(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point2<f64>, filter_width: f64) -> f64 {
+        self.get_octaves(point, filter_width)
+    }
+}
+
+/// 3-dimensional `JordanTurbulence` noise
+impl<Source> NoiseFn<Point3<f64>> for JordanTurbulence<Source>
+where
+    Source: NoiseFnWithGradient<Point3<f64>>,
+{
+    fn get(&self, point: Point3<f64>) -> f64 {
+        self.get_octaves(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point3<f64>, filter_width: f64) -> f64 {
+        self.get_octaves(point, filter_width)
+    }
+}
+
+/// 4-dimensional `JordanTurbulence` noise
+impl<Source> NoiseFn<Point4<f64>> for JordanTurbulence<Source>
+where
+    Source: NoiseFnWithGradient<Point4<f64>>,
+{
+    fn get(&self, point: Point4<f64>) -> f64 {
+        self.get_octaves(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point4<f64>, filter_width: f64) -> f64 {
+        self.get_octaves(point, filter_width)
+    }
+}
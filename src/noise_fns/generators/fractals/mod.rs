@@ -1,21 +1,33 @@
 pub use self::basicmulti::*;
 pub use self::billow::*;
+pub use self::derivativefbm::*;
 pub use self::fbm::*;
 pub use self::hybridmulti::*;
+pub use self::jordanturbulence::*;
 pub use self::ridgedmulti::*;
+pub use self::swissturbulence::*;
 
 mod basicmulti;
 mod billow;
+mod derivativefbm;
 mod fbm;
 mod hybridmulti;
+mod jordanturbulence;
 mod ridgedmulti;
+mod swissturbulence;
 
-use noise_fns::{Perlin, Seedable};
+use math;
+use noise_fns::Seedable;
 
 /// Trait for `MultiFractal` functions
 pub trait MultiFractal {
     fn set_octaves(self, octaves: usize) -> Self;
 
+    /// Sets a real-valued number of octaves. The fractional part scales the
+    /// contribution of the last octave, so that detail can be faded in
+    /// smoothly rather than popping in one octave at a time.
+    fn set_fractional_octaves(self, octaves: f64) -> Self;
+
     fn set_frequency(self, frequency: f64) -> Self;
 
     fn set_lacunarity(self, lacunarity: f64) -> Self;
@@ -23,10 +35,228 @@ pub trait MultiFractal {
     fn set_persistence(self, persistence: f64) -> Self;
 }
 
-fn build_sources(seed: u32, octaves: usize) -> Vec<Perlin> {
+fn build_sources<Source>(source: &Source, seed: u32, octaves: usize) -> Vec<Source>
+where
+    Source: Clone + Seedable,
+{
     let mut sources = Vec::with_capacity(octaves);
     for x in 0..octaves {
-        sources.push(Perlin::new().set_seed(seed + x as u32));
+        sources.push(source.clone().set_seed(seed.wrapping_add(x as u32)));
     }
     sources
 }
+
+/// Splits a real-valued number of octaves, clamped to [1, `max_octaves`], into
+/// the whole number of octaves and how much of the last one is added, as for
+/// `MultiFractal::set_fractional_octaves`. The sources are only rebuilt when the
+/// whole number of octaves changes.
+fn fractional_octaves<Source>(
+    sources: Vec<Source>,
+    seed: u32,
+    octaves: f64,
+    max_octaves: usize,
+) -> (usize, f64, Vec<Source>)
+where
+    Source: Clone + Seedable,
+{
+    let octaves = math::clamp(octaves, 1.0, max_octaves as f64);
+    let whole_octaves = octaves.ceil() as usize;
+    let last_octave_fraction = octaves - (whole_octaves - 1) as f64;
+
+    let sources = if sources.len() == whole_octaves {
+        sources
+    } else {
+        build_sources(&sources[0], seed, whole_octaves)
+    };
+
+    (whole_octaves, last_octave_fraction, sources)
+}
+
+/// Limits a fractional octave count so that no octave reaches the Nyquist
+/// frequency of samples spaced `filter_width` apart. The last octave below
+/// that frequency fades out smoothly as the filter width grows, and at least
+/// one octave is always kept. Octaves only rise in frequency with a lacunarity
+/// above 1, so nothing is filtered out otherwise.
+fn filtered_octaves(
+    octaves: usize,
+    last_octave_fraction: f64,
+    frequency: f64,
+    lacunarity: f64,
+    filter_width: f64,
+) -> (usize, f64) {
+    if filter_width <= 0.0 || lacunarity <= 1.0 || lacunarity.is_nan() {
+        return (octaves, last_octave_fraction);
+    }
+
+    let nyquist_frequency = 0.5 / filter_width;
+    let band_limit = (nyquist_frequency / frequency).ln() / lacunarity.ln();
+    if band_limit >= (octaves - 1) as f64 + last_octave_fraction {
+        return (octaves, last_octave_fraction);
+    }
+
+    let band_limit = band_limit.max(1.0);
+    let whole_octaves = band_limit.ceil() as usize;
+    (whole_octaves, band_limit - (whole_octaves - 1) as f64)
+}
+
+/// Octave amplitudes precomputed from a spectral exponent `H`.
+///
+/// Octave `i` has an amplitude of `lacunarity^((H - 1) * i)`, which gives the
+/// same output as a persistence of `lacunarity^(H - 1)`. With a lacunarity of
+/// 2.0 that is `H = 1 - log2(1 / persistence)`, so the default persistence of
+/// 0.5 corresponds to `H = 0`, and `H = 1` weights every octave equally.
+#[derive(Clone, Debug)]
+struct SpectralWeights {
+    exponent: f64,
+    weights: Vec<f64>,
+}
+
+impl SpectralWeights {
+    /// Weights each octave by its frequency raised to the power of
+    /// `exponent - 1`.
+    fn new(exponent: f64, lacunarity: f64, octaves: usize) -> Self {
+        let mut frequency = 1.0_f64;
+        let mut weights = Vec::with_capacity(octaves);
+        for _ in 0..octaves {
+            weights.push(frequency.powf(exponent - 1.0));
+            frequency *= lacunarity;
+        }
+
+        Self { exponent, weights }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use math::Point3;
+    use noise_fns::NoiseFn;
+
+    fn assert_outputs_match<A, B>(a: &A, b: &B)
+    where
+        A: NoiseFn<Point3<f64>>,
+        B: NoiseFn<Point3<f64>>,
+    {
+        assert_outputs_within(a, b, 1e-12);
+    }
+
+    fn assert_outputs_within<A, B>(a: &A, b: &B, tolerance: f64)
+    where
+        A: NoiseFn<Point3<f64>>,
+        B: NoiseFn<Point3<f64>>,
+    {
+        for i in 0..128 {
+            let t = f64::from(i) * 0.173;
+            let point = [t, 0.5 - t * 0.3, t * 0.7 + 1.1];
+
+            assert!((a.get(point) - b.get(point)).abs() < tolerance);
+        }
+    }
+
+    fn assert_octaves_fade_in<F>(fractal: F)
+    where
+        F: MultiFractal + NoiseFn<Point3<f64>> + Clone,
+    {
+        for octaves in 1..5 {
+            let whole = fractal.clone().set_octaves(octaves);
+            let fractional = fractal.clone().set_fractional_octaves(octaves as f64);
+            assert_outputs_match(&whole, &fractional);
+
+            // Just past a whole number of octaves, the next octave has barely
+            // faded in, and just before one it has almost fully faded in.
+            let faded_out = fractal
+                .clone()
+                .set_fractional_octaves(octaves as f64 + 1e-9);
+            assert_outputs_within(&whole, &faded_out, 1e-6);
+
+            let faded_in = fractal
+                .clone()
+                .set_fractional_octaves(octaves as f64 + 1.0 - 1e-9);
+            let next_whole = fractal.clone().set_octaves(octaves + 1);
+            assert_outputs_within(&next_whole, &faded_in, 1e-6);
+        }
+    }
+
+    fn assert_filtering_limits_octaves<F>(fractal: F)
+    where
+        F: MultiFractal + NoiseFn<Point3<f64>> + Clone,
+    {
+        let fractal = fractal.set_frequency(1.0).set_lacunarity(2.0);
+
+        // Samples 1/16 apart have a Nyquist frequency of 8, which is three
+        // octaves above the base frequency. Samples 2^-3.5 apart leave two and
+        // a half octaves.
+        let band_limited = fractal.clone().set_octaves(3);
+        let wide_band_limited = fractal.clone().set_fractional_octaves(2.5);
+
+        for i in 0..128 {
+            let t = f64::from(i) * 0.173;
+            let point = [t, 0.5 - t * 0.3, t * 0.7 + 1.1];
+
+            assert_eq!(fractal.get_filtered(point, 0.0), fractal.get(point));
+            assert!(
+                (fractal.get_filtered(point, 1.0 / 16.0) - band_limited.get(point)).abs() < 1e-12
+            );
+            assert!(
+                (fractal.get_filtered(point, 2.0_f64.powf(-3.5)) - wide_band_limited.get(point))
+                    .abs()
+                    < 1e-12
+            );
+
+            for &lacunarity in &[1.0, 0.5] {
+                let fractal = fractal.clone().set_lacunarity(lacunarity);
+                assert_eq!(fractal.get_filtered(point, 1.0 / 16.0), fractal.get(point));
+            }
+        }
+    }
+
+    #[test]
+    fn test_spectral_exponent_matches_persistence() {
+        for &persistence in &[0.25_f64, 0.5, 0.7] {
+            let exponent = 1.0 - (1.0 / persistence).log2();
+
+            let fbm = Fbm::new().set_lacunarity(2.0).set_persistence(persistence);
+            assert_outputs_match(&fbm, &fbm.clone().set_spectral_exponent(exponent));
+
+            let basic_multi = BasicMulti::new()
+                .set_lacunarity(2.0)
+                .set_persistence(persistence);
+            assert_outputs_match(
+                &basic_multi,
+                &basic_multi.clone().set_spectral_exponent(exponent),
+            );
+
+            let hybrid_multi = HybridMulti::new()
+                .set_lacunarity(2.0)
+                .set_persistence(persistence);
+            assert_outputs_match(
+                &hybrid_multi,
+                &hybrid_multi.clone().set_spectral_exponent(exponent),
+            );
+        }
+    }
+
+    #[test]
+    fn test_fractional_octaves_are_continuous() {
+        assert_octaves_fade_in(Fbm::new());
+        assert_octaves_fade_in(Billow::new());
+        assert_octaves_fade_in(BasicMulti::new());
+        assert_octaves_fade_in(HybridMulti::new());
+        assert_octaves_fade_in(RidgedMulti::new());
+        assert_octaves_fade_in(DerivativeFbm::new());
+        assert_octaves_fade_in(SwissTurbulence::new());
+        assert_octaves_fade_in(JordanTurbulence::new());
+    }
+
+    #[test]
+    fn test_filtering_limits_octaves() {
+        assert_filtering_limits_octaves(Fbm::new());
+        assert_filtering_limits_octaves(Billow::new());
+        assert_filtering_limits_octaves(BasicMulti::new());
+        assert_filtering_limits_octaves(HybridMulti::new());
+        assert_filtering_limits_octaves(RidgedMulti::new());
+        assert_filtering_limits_octaves(DerivativeFbm::new());
+        assert_filtering_limits_octaves(SwissTurbulence::new());
+        assert_filtering_limits_octaves(JordanTurbulence::new());
+    }
+}
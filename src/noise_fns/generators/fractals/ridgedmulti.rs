@@ -1,4 +1,4 @@
-use math::{self, scale_shift, Point2, Point3, Point4};
+use math::{self, interpolate, scale_shift, Point2, Point3, Point4};
 use noise_fns::{MultiFractal, NoiseFn, Perlin, Seedable};
 use std;
 
@@ -19,7 +19,7 @@ use std;
 /// Ridged-multifractal noise is often used to generate craggy mountainous
 /// terrain or marble-like textures.
 #[derive(Clone, Debug)]
-pub struct RidgedMulti {
+pub struct RidgedMulti<Source = Perlin> {
     /// Total number of frequency octaves to generate the noise with.
     ///
     /// The number of octaves control the _amount of detail_ in the noise
@@ -55,7 +55,8 @@ pub struct RidgedMulti {
     pub attenuation: f64,
 
     seed: u32,
-    sources: Vec<Perlin>,
+    sources: Vec<Source>,
+    last_octave_fraction: f64,
 }
 
 impl RidgedMulti {
@@ -68,23 +69,52 @@ impl RidgedMulti {
     pub const MAX_OCTAVES: usize = 32;
 
     pub fn new() -> Self {
+        Self::with_source(Perlin::new())
+    }
+}
+
+impl<Source> RidgedMulti<Source>
+where
+    Source: Clone + Seedable,
+{
+    /// Creates ridged-multifractal noise using a copy of `source` for each octave.
+    ///
+    /// The copies are seeded the same way as the default `Perlin` octaves.
+    pub fn with_source(source: Source) -> Self {
         Self {
-            seed: Self::DEFAULT_SEED,
-            octaves: Self::DEFAULT_OCTAVE_COUNT,
-            frequency: Self::DEFAULT_FREQUENCY,
-            lacunarity: Self::DEFAULT_LACUNARITY,
-            persistence: Self::DEFAULT_PERSISTENCE,
-            attenuation: Self::DEFAULT_ATTENUATION,
-            sources: super::build_sources(Self::DEFAULT_SEED, Self::DEFAULT_OCTAVE_COUNT),
+            seed: RidgedMulti::DEFAULT_SEED,
+            octaves: RidgedMulti::DEFAULT_OCTAVE_COUNT,
+            last_octave_fraction: 1.0,
+            frequency: RidgedMulti::DEFAULT_FREQUENCY,
+            lacunarity: RidgedMulti::DEFAULT_LACUNARITY,
+            persistence: RidgedMulti::DEFAULT_PERSISTENCE,
+            attenuation: RidgedMulti::DEFAULT_ATTENUATION,
+            sources: super::build_sources(
+                &source,
+                RidgedMulti::DEFAULT_SEED,
+                RidgedMulti::DEFAULT_OCTAVE_COUNT,
+            ),
         }
     }
+}
 
+impl<Source> RidgedMulti<Source> {
     pub fn set_attenuation(self, attenuation: f64) -> Self {
         Self {
             attenuation,
             ..self
         }
     }
+
+    fn filtered_octaves(&self, filter_width: f64) -> (usize, f64) {
+        super::filtered_octaves(
+            self.octaves,
+            self.last_octave_fraction,
+            self.frequency,
+            self.lacunarity,
+            filter_width,
+        )
+    }
 }
 
 impl Default for RidgedMulti {
@@ -93,16 +123,22 @@ impl Default for RidgedMulti {
     }
 }
 
-impl MultiFractal for RidgedMulti {
-    fn set_octaves(self, mut octaves: usize) -> Self {
-        if self.octaves == octaves {
-            return self;
-        }
+impl<Source> MultiFractal for RidgedMulti<Source>
+where
+    Source: Clone + Seedable,
+{
+    fn set_octaves(self, octaves: usize) -> Self {
+        self.set_fractional_octaves(octaves as f64)
+    }
+
+    fn set_fractional_octaves(self, octaves: f64) -> Self {
+        let (octaves, last_octave_fraction, sources) =
+            super::fractional_octaves(self.sources, self.seed, octaves, RidgedMulti::MAX_OCTAVES);
 
-        octaves = math::clamp(octaves, 1, Self::MAX_OCTAVES);
         Self {
             octaves,
-            sources: super::build_sources(self.seed, octaves),
+            last_octave_fraction,
+            sources,
             ..self
         }
     }
@@ -123,7 +159,10 @@ impl MultiFractal for RidgedMulti {
     }
 }
 
-impl Seedable for RidgedMulti {
+impl<Source> Seedable for RidgedMulti<Source>
+where
+    Source: Clone + Seedable,
+{
     fn set_seed(self, seed: u32) -> Self {
         if self.seed == seed {
             return self;
@@ -131,7 +170,7 @@ impl Seedable for RidgedMulti {
 
         Self {
             seed,
-            sources: super::build_sources(seed, self.octaves),
+            sources: super::build_sources(&self.sources[0], seed, self.octaves),
             ..self
         }
     }
@@ -142,14 +181,23 @@ impl Seedable for RidgedMulti {
 }
 
 /// 2-dimensional `RidgedMulti` noise
-impl NoiseFn<Point2<f64>> for RidgedMulti {
-    fn get(&self, mut point: Point2<f64>) -> f64 {
+impl<Source> NoiseFn<Point2<f64>> for RidgedMulti<Source>
+where
+    Source: NoiseFn<Point2<f64>>,
+{
+    fn get(&self, point: Point2<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, mut point: Point2<f64>, filter_width: f64) -> f64 {
+        let (octaves, last_octave_fraction) = self.filtered_octaves(filter_width);
+
         let mut result = 0.0;
         let mut weight = 1.0;
 
         point = math::mul2(point, self.frequency);
 
-        for x in 0..self.octaves {
+        for x in 0..octaves {
             // Get the value.
             let mut signal = self.sources[x].get(point);
 
@@ -174,6 +222,12 @@ impl NoiseFn<Point2<f64>> for RidgedMulti {
             // Scale the amplitude appropriately for this frequency.
             signal *= self.persistence.powi(x as i32);
 
+            // Fade in the last octave by the fractional part of the octave
+            // count.
+            if x == octaves - 1 {
+                signal *= last_octave_fraction;
+            }
+
             // Add the signal to the result.
             result += signal;
 
@@ -181,21 +235,35 @@ impl NoiseFn<Point2<f64>> for RidgedMulti {
             point = math::mul2(point, self.lacunarity);
         }
 
-        // Scale and shift the result into the [-1,1] range
-        let scale = 2.0 - 0.5_f64.powi(self.octaves as i32 - 1);
+        // Scale and shift the result into the [-1,1] range, blending towards
+        // the scale for one octave fewer while the last octave fades in.
+        let mut scale = 2.0 - 0.5_f64.powi(octaves as i32 - 1);
+        if last_octave_fraction < 1.0 {
+            let previous_scale = 2.0 - 0.5_f64.powi(octaves as i32 - 2);
+            scale = interpolate::linear(previous_scale, scale, last_octave_fraction);
+        }
         scale_shift(result, 2.0 / scale)
     }
 }
 
 /// 3-dimensional `RidgedMulti` noise
-impl NoiseFn<Point3<f64>> for RidgedMulti {
-    fn get(&self, mut point: Point3<f64>) -> f64 {
+impl<Source> NoiseFn<Point3<f64>> for RidgedMulti<Source>
+where
+    Source: NoiseFn<Point3<f64>>,
+{
+    fn get(&self, point: Point3<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, mut point: Point3<f64>, filter_width: f64) -> f64 {
+        let (octaves, last_octave_fraction) = self.filtered_octaves(filter_width);
+
         let mut result = 0.0;
         let mut weight = 1.0;
 
         point = math::mul3(point, self.frequency);
 
-        for x in 0..self.octaves {
+        for x in 0..octaves {
             // Get the value.
             let mut signal = self.sources[x].get(point);
 
@@ -220,6 +288,12 @@ impl NoiseFn<Point3<f64>> for RidgedMulti {
             // Scale the amplitude appropriately for this frequency.
             signal *= self.persistence.powi(x as i32);
 
+            // Fade in the last octave by the fractional part of the octave
+            // count.
+            if x == octaves - 1 {
+                signal *= last_octave_fraction;
+            }
+
             // Add the signal to the result.
             result += signal;
 
@@ -227,21 +301,35 @@ impl NoiseFn<Point3<f64>> for RidgedMulti {
             point = math::mul3(point, self.lacunarity);
         }
 
-        // Scale and shift the result into the [-1,1] range
-        let scale = 2.0 - 0.5_f64.powi(self.octaves as i32 - 1);
+        // Scale and shift the result into the [-1,1] range, blending towards
+        // the scale for one octave fewer while the last octave fades in.
+        let mut scale = 2.0 - 0.5_f64.powi(octaves as i32 - 1);
+        if last_octave_fraction < 1.0 {
+            let previous_scale = 2.0 - 0.5_f64.powi(octaves as i32 - 2);
+            scale = interpolate::linear(previous_scale, scale, last_octave_fraction);
+        }
         scale_shift(result, 2.0 / scale)
     }
 }
 
 /// 4-dimensional `RidgedMulti` noise
-impl NoiseFn<Point4<f64>> for RidgedMulti {
-    fn get(&self, mut point: Point4<f64>) -> f64 {
+impl<Source> NoiseFn<Point4<f64>> for RidgedMulti<Source>
+where
+    Source: NoiseFn<Point4<f64>>,
+{
+    fn get(&self, point: Point4<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, mut point: Point4<f64>, filter_width: f64) -> f64 {
+        let (octaves, last_octave_fraction) = self.filtered_octaves(filter_width);
+
         let mut result = 0.0;
         let mut weight = 1.0;
 
         point = math::mul4(point, self.frequency);
 
-        for x in 0..self.octaves {
+        for x in 0..octaves {
             // Get the value.
             let mut signal = self.sources[x].get(point);
 
@@ -266,6 +354,12 @@ impl NoiseFn<Point4<f64>> for RidgedMulti {
             // Scale the amplitude appropriately for this frequency.
             signal *= self.persistence.powi(x as i32);
 
+            // Fade in the last octave by the fractional part of the octave
+            // count.
+            if x == octaves - 1 {
+                signal *= last_octave_fraction;
+            }
+
             // Add the signal to the result.
             result += signal;
 
@@ -273,8 +367,13 @@ impl NoiseFn<Point4<f64>> for RidgedMulti {
             point = math::mul4(point, self.lacunarity);
         }
 
-        // Scale and shift the result into the [-1,1] range
-        let scale = 2.0 - 0.5_f64.powi(self.octaves as i32 - 1);
+        // Scale and shift the result into the [-1,1] range, blending towards
+        // the scale for one octave fewer while the last octave fades in.
+        let mut scale = 2.0 - 0.5_f64.powi(octaves as i32 - 1);
+        if last_octave_fraction < 1.0 {
+            let previous_scale = 2.0 - 0.5_f64.powi(octaves as i32 - 2);
+            scale = interpolate::linear(previous_scale, scale, last_octave_fraction);
+        }
         scale_shift(result, 2.0 / scale)
     }
 }
@@ -0,0 +1,272 @@
+use math::{self, Point2, Point3, Point4};
+use noise_fns::{MultiFractal, NoiseFn, NoiseFnWithGradient, Perlin, Seedable};
+use std;
+
+/// Noise function that outputs Swiss turbulence, as described by Giliam de
+/// Carpentier.
+///
+/// Each octave is made into ridges like `RidgedMulti`, but the octaves are
+/// also offset by the sum of the previous octaves' gradients, and their
+/// amplitudes are damped where the sum so far is low. This produces sharp,
+/// eroded-looking ridges with smooth low areas.
+///
+/// The source must be able to calculate its gradient, so it must implement
+/// `NoiseFnWithGradient`.
+///
+/// The values output from this function never go below -1.0, and will reach
+/// up to about 0.95 with default values for the parameters. If the parameters
+/// are modified from their defaults, then the output may need to be scaled to
+/// keep the peaks in range.
+#[derive(Clone, Debug)]
+pub struct SwissTurbulence<Source = Perlin> {
+    /// Total number of frequency octaves to generate the noise with.
+    ///
+    /// The number of octaves control the _amount of detail_ in the noise
+    /// function. Adding more octaves increases the detail, with the drawback
+    /// of increasing the calculation time.
+    pub octaves: usize,
+
+    /// The number of cycles per unit length that the noise function outputs.
+    pub frequency: f64,
+
+    /// A multiplier that determines how quickly the frequency increases for
+    /// each successive octave in the noise function.
+    ///
+    /// The frequency of each successive octave is equal to the product of the
+    /// previous octave's frequency and the lacunarity value.
+    ///
+    /// A lacunarity of 2.0 results in the frequency doubling every octave. For
+    /// almost all cases, 2.0 is a good value to use.
+    pub lacunarity: f64,
+
+    /// A multiplier that determines how quickly the amplitudes diminish for
+    /// each successive octave in the noise function.
+    ///
+    /// The amplitude of each successive octave is equal to the product of the
+    /// previous octave's amplitude and the persistence value. Increasing the
+    /// persistence produces "rougher" noise.
+    pub persistence: f64,
+
+    /// How far each octave is offset by the sum of the previous octaves'
+    /// gradients. Larger values bend the ridges more.
+    pub warp: f64,
+
+    seed: u32,
+    sources: Vec<Source>,
+    last_octave_fraction: f64,
+}
+
+impl SwissTurbulence {
+    pub const DEFAULT_SEED: u32 = 0;
+    pub const DEFAULT_OCTAVE_COUNT: usize = 6;
+    pub const DEFAULT_FREQUENCY: f64 = 1.0;
+    pub const DEFAULT_LACUNARITY: f64 = std::f64::consts::PI * 2.0 / 3.0;
+    pub const DEFAULT_PERSISTENCE: f64 = 0.5;
+    pub const DEFAULT_WARP: f64 = 0.15;
+    pub const MAX_OCTAVES: usize = 32;
+
+    pub fn new() -> Self {
+        Self::with_source(Perlin::new())
+    }
+}
+
+impl<Source> SwissTurbulence<Source>
+where
+    Source: Clone + Seedable,
+{
+    /// Creates Swiss turbulence using a copy of `source` for each octave.
+    ///
+    /// The copies are seeded the same way as the default `Perlin` octaves.
+    pub fn with_source(source: Source) -> Self {
+        Self {
+            warp: SwissTurbulence::DEFAULT_WARP,
+            seed: SwissTurbulence::DEFAULT_SEED,
+            octaves: SwissTurbulence::DEFAULT_OCTAVE_COUNT,
+            last_octave_fraction: 1.0,
+            frequency: SwissTurbulence::DEFAULT_FREQUENCY,
+            lacunarity: SwissTurbulence::DEFAULT_LACUNARITY,
+            persistence: SwissTurbulence::DEFAULT_PERSISTENCE,
+            sources: super::build_sources(
+                &source,
+                SwissTurbulence::DEFAULT_SEED,
+                SwissTurbulence::DEFAULT_OCTAVE_COUNT,
+            ),
+        }
+    }
+}
+
+impl<Source> SwissTurbulence<Source> {
+    pub fn set_warp(self, warp: f64) -> Self {
+        Self { warp, ..self }
+    }
+
+    fn get_octaves<const N: usize>(&self, point: [f64; N], filter_width: f64) -> f64
+    where
+        Source: NoiseFnWithGradient<[f64; N]>,
+    {
+        let (octaves, last_octave_fraction) = self.filtered_octaves(filter_width);
+
+        let mut result = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = self.frequency;
+        let mut gradient_sum = [0.0; N];
+
+        for x in 0..octaves {
+            // Warp the point by the gradients so far, then scale it to the
+            // frequency of this octave.
+            let mut warped_point = point;
+            for (coordinate, sum) in warped_point.iter_mut().zip(gradient_sum.iter()) {
+                *coordinate = (*coordinate + self.warp * sum) * frequency;
+            }
+
+            // Get the signal and its gradient.
+            let (value, gradient) = self.sources[x].get_with_gradient(warped_point);
+
+            // Make the ridges, and scale the amplitude for this octave.
+            let mut signal = (1.0 - value.abs()) * amplitude;
+
+            // Fade in the last octave by the fractional part of the octave
+            // count.
+            if x == octaves - 1 {
+                signal *= last_octave_fraction;
+            }
+
+            // Add the signal to the result.
+            result += signal;
+
+            // Accumulate the gradient of the ridges.
+            for (sum, derivative) in gradient_sum.iter_mut().zip(gradient.iter()) {
+                *sum -= amplitude * derivative * value;
+            }
+
+            // Damp the next octave where the result so far is low.
+            amplitude *= self.persistence * math::clamp(result, 0.0, 1.0);
+
+            // Increase the frequency for the next octave.
+            frequency *= self.lacunarity;
+        }
+
+        // The sum is never negative, so shifting it down by one puts the
+        // lowest possible output at -1.0.
+        result - 1.0
+    }
+
+    fn filtered_octaves(&self, filter_width: f64) -> (usize, f64) {
+        super::filtered_octaves(
+            self.octaves,
+            self.last_octave_fraction,
+            self.frequency,
+            self.lacunarity,
+            filter_width,
+        )
+    }
+}
+
+impl Default for SwissTurbulence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Source> MultiFractal for SwissTurbulence<Source>
+where
+    Source: Clone + Seedable,
+{
+    fn set_octaves(self, octaves: usize) -> Self {
+        self.set_fractional_octaves(octaves as f64)
+    }
+
+    fn set_fractional_octaves(self, octaves: f64) -> Self {
+        let (octaves, last_octave_fraction, sources) = super::fractional_octaves(
+            self.sources,
+            self.seed,
+            octaves,
+            SwissTurbulence::MAX_OCTAVES,
+        );
+
+        Self {
+            octaves,
+            last_octave_fraction,
+            sources,
+            ..self
+        }
+    }
+
+    fn set_frequency(self, frequency: f64) -> Self {
+        Self { frequency, ..self }
+    }
+
+    fn set_lacunarity(self, lacunarity: f64) -> Self {
+        Self { lacunarity, ..self }
+    }
+
+    fn set_persistence(self, persistence: f64) -> Self {
+        Self {
+            persistence,
+            ..self
+        }
+    }
+}
+
+impl<Source> Seedable for SwissTurbulence<Source>
+where
+    Source: Clone + Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        if self.seed == seed {
+            return self;
+        }
+
+        Self {
+            seed,
+            sources: super::build_sources(&self.sources[0], seed, self.octaves),
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+/// 2-dimensional `SwissTurbulence` noise
+impl<Source> NoiseFn<Point2<f64>> for SwissTurbulence<Source>
+where
+    Source: NoiseFnWithGradient<Point2<f64>>,
+{
+    fn get(&self, point: Point2<f64>) -> f64 {
+        self.get_octaves(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point2<f64>, filter_width: f64) -> f64 {
+        self.get_octaves(point, filter_width)
+    }
+}
+
+/// 3-dimensional `SwissTurbulence` noise
+impl<Source> NoiseFn<Point3<f64>> for SwissTurbulence<Source>
+where
+    Source: NoiseFnWithGradient<Point3<f64>>,
+{
+    fn get(&self, point: Point3<f64>) -> f64 {
+        self.get_octaves(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point3<f64>, filter_width: f64) -> f64 {
+        self.get_octaves(point, filter_width)
+    }
+}
+
+/// 4-dimensional `SwissTurbulence` noise
+impl<Source> NoiseFn<Point4<f64>> for SwissTurbulence<Source>
+where
+    Source: NoiseFnWithGradient<Point4<f64>>,
+{
+    fn get(&self, point: Point4<f64>) -> f64 {
+        self.get_octaves(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point4<f64>, filter_width: f64) -> f64 {
+        self.get_octaves(point, filter_width)
+    }
+}
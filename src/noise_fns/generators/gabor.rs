@@ -0,0 +1,382 @@
+//! Gabor noise, as detailed in "Procedural Noise using Sparse Gabor
+//! Convolution" by Lagae, Lefebvre, Drettakis and Dutré:
+//! <https://graphics.cs.kuleuven.be/publications/LLDD09PNSGC/>
+
+use math::{Point2, Point3};
+use noise_fns::{NoiseFn, Seedable};
+use permutationtable::{LatticeHash, LatticeHashable, PermutationTable};
+use std::f64::consts::PI;
+
+/// Noise function that outputs 2/3-dimensional Gabor noise.
+///
+/// Gabor noise sums randomly placed and weighted Gabor kernels, which are
+/// cosine waves under a Gaussian envelope. This gives band-limited noise whose
+/// frequency and orientation can be controlled directly, such as the streaks
+/// of brushed metal or the weave of fabric.
+///
+/// The output is scaled by three standard deviations of the noise, so it lies
+/// within [-1, 1] for all but a small fraction of points.
+#[derive(Clone, Copy, Debug)]
+pub struct Gabor {
+    /// Frequency of the waves in each kernel.
+    pub frequency: f64,
+
+    /// Direction the waves in each kernel travel in.
+    pub orientation: Orientation,
+
+    /// Width of the frequency band covered by the noise. Higher values shrink
+    /// the Gaussian envelope of each kernel, which gives less regular waves.
+    pub bandwidth: f64,
+
+    /// Average number of kernels overlapping each point.
+    pub impulse_density: f64,
+
+    seed: u32,
+    perm_table: PermutationTable,
+}
+
+/// Orientation of the kernels of the Gabor noise function.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Orientation {
+    /// Every kernel points the same way, which gives anisotropic noise. The
+    /// azimuth is the angle from the x axis in the x-y plane, and the
+    /// elevation the angle out of that plane, both in radians. 2D noise
+    /// ignores the elevation.
+    Fixed { azimuth: f64, elevation: f64 },
+
+    /// Every kernel points in a random direction, which gives isotropic noise.
+    Random,
+}
+
+impl Gabor {
+    pub const DEFAULT_SEED: u32 = 0;
+    pub const DEFAULT_FREQUENCY: f64 = 4.0;
+    pub const DEFAULT_ORIENTATION: Orientation = Orientation::Fixed {
+        azimuth: PI / 4.0,
+        elevation: 0.0,
+    };
+    pub const DEFAULT_BANDWIDTH: f64 = 2.0;
+    pub const DEFAULT_IMPULSE_DENSITY: f64 = 32.0;
+
+    pub fn new() -> Self {
+        Self {
+            seed: Self::DEFAULT_SEED,
+            perm_table: PermutationTable::new(Self::DEFAULT_SEED),
+            frequency: Self::DEFAULT_FREQUENCY,
+            orientation: Self::DEFAULT_ORIENTATION,
+            bandwidth: Self::DEFAULT_BANDWIDTH,
+            impulse_density: Self::DEFAULT_IMPULSE_DENSITY,
+        }
+    }
+
+    /// Sets the frequency of the waves in each kernel.
+    pub fn set_frequency(self, frequency: f64) -> Self {
+        Self { frequency, ..self }
+    }
+
+    /// Sets the direction the waves in each kernel travel in.
+    pub fn set_orientation(self, orientation: Orientation) -> Self {
+        Self {
+            orientation,
+            ..self
+        }
+    }
+
+    /// Sets the width of the frequency band covered by the noise.
+    pub fn set_bandwidth(self, bandwidth: f64) -> Self {
+        Self { bandwidth, ..self }
+    }
+
+    /// Sets the average number of kernels overlapping each point.
+    pub fn set_impulse_density(self, impulse_density: f64) -> Self {
+        Self {
+            impulse_density,
+            ..self
+        }
+    }
+
+    /// Radius beyond which the Gaussian envelope of a kernel drops below 5% and
+    /// is cut off. This is also the size of the cells the impulses are
+    /// scattered in.
+    fn kernel_radius(&self) -> f64 {
+        (-(0.05f64.ln()) / PI).sqrt() / self.bandwidth
+    }
+
+    /// Integral of the square of a kernel over `dimensions`-dimensional space.
+    fn kernel_energy(&self, dimensions: i32) -> f64 {
+        let a2 = self.bandwidth * self.bandwidth;
+
+        0.5 * (2.0 * a2).powf(-f64::from(dimensions) / 2.0)
+            * (1.0 + (-2.0 * PI * self.frequency * self.frequency / a2).exp())
+    }
+
+    /// Factor that scales the sum of the kernels by three standard deviations,
+    /// given the volume of a sphere with the kernel radius.
+    fn scale(&self, dimensions: i32, kernel_volume: f64) -> f64 {
+        // The impulse weights are uniform in [-1, 1], whose mean square is 1/3.
+        let variance = self.impulse_density / kernel_volume / 3.0 * self.kernel_energy(dimensions);
+
+        1.0 / (3.0 * variance.sqrt())
+    }
+}
+
+impl Default for Gabor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Seedable for Gabor {
+    /// Sets the seed value for Gabor noise
+    fn set_seed(self, seed: u32) -> Self {
+        // If the new seed is the same as the current seed, just return self.
+        if self.seed == seed {
+            return self;
+        }
+
+        // Otherwise, regenerate the permutation table based on the new seed.
+        Self {
+            seed,
+            perm_table: PermutationTable::with_hash(seed, self.perm_table.hash()),
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+impl LatticeHashable for Gabor {
+    fn set_lattice_hash(self, hash: LatticeHash) -> Self {
+        Self {
+            perm_table: PermutationTable::with_hash(self.seed, hash),
+            ..self
+        }
+    }
+
+    fn lattice_hash(&self) -> LatticeHash {
+        self.perm_table.hash()
+    }
+}
+
+/// 2-dimensional Gabor noise
+impl NoiseFn<Point2<f64>> for Gabor {
+    fn get(&self, point: Point2<f64>) -> f64 {
+        let radius = self.kernel_radius();
+        let a2 = self.bandwidth * self.bandwidth;
+
+        let (fixed_cos, fixed_sin) = match self.orientation {
+            Orientation::Fixed { azimuth, .. } => (azimuth.cos(), azimuth.sin()),
+            Orientation::Random => (0.0, 0.0),
+        };
+
+        // Each cell is a square the size of the kernel radius, so only the
+        // neighbouring cells hold kernels that can reach the point.
+        let cell_point = [point[0] / radius, point[1] / radius];
+        let cell = [cell_point[0].floor(), cell_point[1].floor()];
+        let frac = [cell_point[0] - cell[0], cell_point[1] - cell[1]];
+        let cell = [cell[0] as isize, cell[1] as isize];
+
+        let mean_impulses = self.impulse_density / PI;
+
+        let mut value = 0.0;
+
+        for x_offset in -1..=1 {
+            for y_offset in -1..=1 {
+                let cur_cell = [cell[0] + x_offset, cell[1] + y_offset];
+
+                let mut random = Lcg::new(|i| self.perm_table.get3([i, cur_cell[0], cur_cell[1]]));
+
+                for _ in 0..random.poisson(mean_impulses) {
+                    let impulse = [random.next_f64(), random.next_f64()];
+                    let weight = random.next_f64() * 2.0 - 1.0;
+                    let (cos, sin) = match self.orientation {
+                        Orientation::Fixed { .. } => (fixed_cos, fixed_sin),
+                        Orientation::Random => {
+                            let angle = random.next_f64() * 2.0 * PI;
+                            (angle.cos(), angle.sin())
+                        }
+                    };
+
+                    let distance = [
+                        (frac[0] - impulse[0] - x_offset as f64) * radius,
+                        (frac[1] - impulse[1] - y_offset as f64) * radius,
+                    ];
+                    let distance_squared = distance[0] * distance[0] + distance[1] * distance[1];
+
+                    if distance_squared < radius * radius {
+                        let envelope = (-PI * a2 * distance_squared).exp();
+                        let phase =
+                            2.0 * PI * self.frequency * (distance[0] * cos + distance[1] * sin);
+                        value += weight * envelope * phase.cos();
+                    }
+                }
+            }
+        }
+
+        value * self.scale(2, PI * radius * radius)
+    }
+}
+
+/// 3-dimensional Gabor noise
+impl NoiseFn<Point3<f64>> for Gabor {
+    fn get(&self, point: Point3<f64>) -> f64 {
+        let radius = self.kernel_radius();
+        let a2 = self.bandwidth * self.bandwidth;
+
+        let fixed_direction = match self.orientation {
+            Orientation::Fixed { azimuth, elevation } => direction(azimuth, elevation),
+            Orientation::Random => [0.0; 3],
+        };
+
+        // Each cell is a cube the size of the kernel radius, so only the
+        // neighbouring cells hold kernels that can reach the point.
+        let cell_point = [point[0] / radius, point[1] / radius, point[2] / radius];
+        let cell = [
+            cell_point[0].floor(),
+            cell_point[1].floor(),
+            cell_point[2].floor(),
+        ];
+        let frac = [
+            cell_point[0] - cell[0],
+            cell_point[1] - cell[1],
+            cell_point[2] - cell[2],
+        ];
+        let cell = [cell[0] as isize, cell[1] as isize, cell[2] as isize];
+
+        let mean_impulses = self.impulse_density / (4.0 / 3.0 * PI);
+
+        let mut value = 0.0;
+
+        for x_offset in -1..=1 {
+            for y_offset in -1..=1 {
+                for z_offset in -1..=1 {
+                    let cur_cell = [cell[0] + x_offset, cell[1] + y_offset, cell[2] + z_offset];
+
+                    let mut random = Lcg::new(|i| {
+                        self.perm_table
+                            .get4([i, cur_cell[0], cur_cell[1], cur_cell[2]])
+                    });
+
+                    for _ in 0..random.poisson(mean_impulses) {
+                        let impulse = [random.next_f64(), random.next_f64(), random.next_f64()];
+                        let weight = random.next_f64() * 2.0 - 1.0;
+                        let direction = match self.orientation {
+                            Orientation::Fixed { .. } => fixed_direction,
+                            Orientation::Random => {
+                                // Uniformly distributed over the sphere.
+                                let azimuth = random.next_f64() * 2.0 * PI;
+                                let elevation = (random.next_f64() * 2.0 - 1.0).asin();
+                                direction(azimuth, elevation)
+                            }
+                        };
+
+                        let distance = [
+                            (frac[0] - impulse[0] - x_offset as f64) * radius,
+                            (frac[1] - impulse[1] - y_offset as f64) * radius,
+                            (frac[2] - impulse[2] - z_offset as f64) * radius,
+                        ];
+                        let distance_squared = distance[0] * distance[0]
+                            + distance[1] * distance[1]
+                            + distance[2] * distance[2];
+
+                        if distance_squared < radius * radius {
+                            let envelope = (-PI * a2 * distance_squared).exp();
+                            let phase = 2.0
+                                * PI
+                                * self.frequency
+                                * (distance[0] * direction[0]
+                                    + distance[1] * direction[1]
+                                    + distance[2] * direction[2]);
+                            value += weight * envelope * phase.cos();
+                        }
+                    }
+                }
+            }
+        }
+
+        value * self.scale(3, 4.0 / 3.0 * PI * radius * radius * radius)
+    }
+}
+
+/// Unit vector pointing in the direction given by its spherical angles.
+fn direction(azimuth: f64, elevation: f64) -> Point3<f64> {
+    [
+        azimuth.cos() * elevation.cos(),
+        azimuth.sin() * elevation.cos(),
+        elevation.sin(),
+    ]
+}
+
+/// Linear congruential generator that scatters the impulses in a cell, as in
+/// the reference implementation.
+struct Lcg {
+    state: u32,
+}
+
+impl Lcg {
+    /// Seeds the generator from four bytes of the permutation table, looked up
+    /// with the index of each byte.
+    fn new<F>(perm: F) -> Self
+    where
+        F: Fn(isize) -> usize,
+    {
+        let state = (0..4).fold(0, |state, i| (state << 8) | perm(i) as u32);
+
+        // The state has to be odd for the generator to have a long period.
+        Self { state: state | 1 }
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        self.state = self.state.wrapping_mul(3_039_177_861);
+        self.state
+    }
+
+    /// Uniformly distributed value in [0, 1].
+    fn next_f64(&mut self) -> f64 {
+        f64::from(self.next_u32()) / f64::from(u32::MAX)
+    }
+
+    /// Poisson distributed value with the given mean, using Knuth's method.
+    fn poisson(&mut self, mean: f64) -> usize {
+        let limit = (-mean).exp();
+        let mut product = self.next_f64();
+        let mut count = 0;
+
+        while product > limit {
+            product *= self.next_f64();
+            count += 1;
+        }
+
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_scale() {
+        for &orientation in &[Gabor::DEFAULT_ORIENTATION, Orientation::Random] {
+            let gabor = Gabor::new().set_orientation(orientation);
+            let (mut sum_squares_2d, mut sum_squares_3d) = (0.0, 0.0);
+            let count = 64 * 64;
+
+            for x in 0..64 {
+                for y in 0..64 {
+                    let point = [x as f64 * 0.173, y as f64 * 0.191];
+                    sum_squares_2d += gabor.get(point).powi(2);
+                    sum_squares_3d += gabor.get([point[0], point[1], 0.5]).powi(2);
+                }
+            }
+
+            // The output is scaled by three standard deviations.
+            for &sum_squares in &[sum_squares_2d, sum_squares_3d] {
+                let deviation = (sum_squares / f64::from(count)).sqrt();
+                assert!((0.25..0.45).contains(&deviation), "{}", deviation);
+            }
+        }
+    }
+}
@@ -1,19 +1,29 @@
 pub use self::checkerboard::*;
+pub use self::closure::*;
 pub use self::constant::*;
 pub use self::cylinders::*;
+pub use self::domain_warp::*;
 pub use self::fractals::*;
+pub use self::gabor::*;
 pub use self::open_simplex::*;
 pub use self::perlin::*;
+pub use self::simplex::*;
+pub use self::spheres::*;
 pub use self::super_simplex::*;
 pub use self::value::*;
 pub use self::worley::*;
 
 mod checkerboard;
+mod closure;
 mod constant;
 mod cylinders;
+mod domain_warp;
 mod fractals;
+mod gabor;
 mod open_simplex;
 mod perlin;
+mod simplex;
+mod spheres;
 mod super_simplex;
 mod value;
 mod worley;
@@ -1,10 +1,11 @@
-//! Note that this is NOT Ken Perlin's simplex noise, as that is patent encumbered.
-//! Instead, these functions use the `OpenSimplex` algorithm, as detailed here:
+//! Note that this is NOT Ken Perlin's simplex noise, which is available as
+//! `Simplex`. These functions use the `OpenSimplex` algorithm, which was
+//! created while simplex noise was still patent encumbered, as detailed here:
 //! <http://uniblock.tumblr.com/post/97868843242/noise>
 
-use math::{Point2, Point3, Point4};
-use noise_fns::{NoiseFn, Seedable};
-use permutationtable::PermutationTable;
+use math::{Point2, Point3, Point4, Vector2, Vector3, Vector4};
+use noise_fns::{NoiseFn, NoiseFnWithGradient, Seedable};
+use permutationtable::{LatticeHash, LatticeHashable, PermutationTable};
 use std::ops::Add;
 use {gradient, math};
 
@@ -24,6 +25,7 @@ const NORM_CONSTANT_4D: f64 = 1.0 / 6.869_909_007_095_662_5;
 pub struct OpenSimplex {
     seed: u32,
     perm_table: PermutationTable,
+    period: [usize; 4],
 }
 
 impl OpenSimplex {
@@ -33,8 +35,26 @@ impl OpenSimplex {
         Self {
             seed: Self::DEFAULT_SEED,
             perm_table: PermutationTable::new(Self::DEFAULT_SEED),
+            period: [0; 4],
         }
     }
+
+    /// Sets the number of lattice cells after which the noise repeats along
+    /// each axis of the simplex lattice. A period of zero doesn't repeat.
+    ///
+    /// The lattice is skewed relative to the input space, so the noise repeats
+    /// along the lattice axes rather than the input axes, and its tiles are
+    /// parallelograms instead of rectangles. Use `Perlin` or `Value` noise for
+    /// textures that need to tile along the input axes.
+    ///
+    /// 2- and 3-dimensional noise use the first two and three periods.
+    pub fn set_period(self, period: [usize; 4]) -> Self {
+        Self { period, ..self }
+    }
+
+    pub fn period(&self) -> [usize; 4] {
+        self.period
+    }
 }
 
 impl Default for OpenSimplex {
@@ -54,7 +74,8 @@ impl Seedable for OpenSimplex {
         // Otherwise, regenerate the permutation table based on the new seed.
         Self {
             seed,
-            perm_table: PermutationTable::new(seed),
+            perm_table: PermutationTable::with_hash(seed, self.perm_table.hash()),
+            ..self
         }
     }
 
@@ -63,15 +84,33 @@ impl Seedable for OpenSimplex {
     }
 }
 
+impl LatticeHashable for OpenSimplex {
+    fn set_lattice_hash(self, hash: LatticeHash) -> Self {
+        Self {
+            perm_table: PermutationTable::with_hash(self.seed, hash),
+            ..self
+        }
+    }
+
+    fn lattice_hash(&self) -> LatticeHash {
+        self.perm_table.hash()
+    }
+}
+
 /// 2-dimensional [`OpenSimplex` Noise](http://uniblock.tumblr.com/post/97868843242/noise)
 ///
 /// This is a slower but higher quality form of gradient noise than `Perlin` 2D.
 impl NoiseFn<Point2<f64>> for OpenSimplex {
     fn get(&self, point: Point2<f64>) -> f64 {
-        fn gradient(perm_table: &PermutationTable, vertex: Point2<f64>, pos: Point2<f64>) -> f64 {
+        fn gradient(
+            perm_table: &PermutationTable,
+            vertex: Point2<f64>,
+            period: Vector2<usize>,
+            pos: Point2<f64>,
+        ) -> f64 {
             let attn = 2.0 - math::dot2(pos, pos);
             if attn > 0.0 {
-                let index = perm_table.get2(math::to_isize2(vertex));
+                let index = perm_table.get2(math::wrap2(math::to_isize2(vertex), period));
                 let vec = gradient::get2(index);
                 attn.powi(4) * math::dot2(pos, vec)
             } else {
@@ -79,66 +118,11 @@ impl NoiseFn<Point2<f64>> for OpenSimplex {
             }
         }
 
-        // Place input coordinates onto grid.
-        let stretch_offset = math::fold2(point, Add::add) * STRETCH_CONSTANT_2D;
-        let stretched = math::map2(point, |v| v + stretch_offset);
-
-        // Floor to get grid coordinates of rhombus (stretched square) cell origin.
-        let stretched_floor = math::map2(stretched, f64::floor);
-
-        // Skew out to get actual coordinates of rhombus origin. We'll need these later.
-        let squish_offset = math::fold2(stretched_floor, Add::add) * SQUISH_CONSTANT_2D;
-        let skewed_floor = math::map2(stretched_floor, |v| v + squish_offset);
-
-        // Compute grid coordinates relative to rhombus origin.
-        let rel_coords = math::sub2(stretched, stretched_floor);
-
-        // Sum those together to get a value that determines which region we're in.
-        let region_sum = math::fold2(rel_coords, Add::add);
-
-        // Positions relative to origin point (0, 0).
-        let pos0 = math::sub2(point, skewed_floor);
-
         let mut value = 0.0;
-
-        let mut vertex;
-        let mut dpos;
-
-        // (0, 0) --- (1, 0)
-        // |   A     /     |
-        // |       /       |
-        // |     /     B   |
-        // (0, 1) --- (1, 1)
-
-        let t0 = SQUISH_CONSTANT_2D;
-        let t1 = SQUISH_CONSTANT_2D + 1.0;
-        let t2 = SQUISH_CONSTANT_2D + t1;
-
-        // Contribution (1, 0)
-        vertex = math::add2(stretched_floor, [1.0, 0.0]);
-        dpos = math::sub2(pos0, [t1, t0]);
-        value += gradient(&self.perm_table, vertex, dpos);
-
-        // Contribution (0, 1)
-        vertex = math::add2(stretched_floor, [0.0, 1.0]);
-        dpos = math::sub2(pos0, [t0, t1]);
-        value += gradient(&self.perm_table, vertex, dpos);
-
-        // See the graph for an intuitive explanation; the sum of `x` and `y` is
-        // only greater than `1` if we're on Region B.
-        if region_sum > 1.0 {
-            // Contribution (1, 1)
-            vertex = math::add2(stretched_floor, [1.0, 1.0]);
-            // We are moving across the diagonal `/`, so we'll need to add by the
-            // squish constant
-            dpos = math::sub2(pos0, [t2, t2]);
-        } else {
-            vertex = math::add2(stretched_floor, [0.0, 0.0]);
-            dpos = math::sub2(pos0, [0.0, 0.0]);
-        }
-
-        // Point (0, 0) or (1, 1)
-        value += gradient(&self.perm_table, vertex, dpos);
+        let period = [self.period[0], self.period[1]];
+        for_each_vertex2(point, |vertex, dpos| {
+            value += gradient(&self.perm_table, vertex, period, dpos);
+        });
 
         value * NORM_CONSTANT_2D
     }
@@ -149,10 +133,15 @@ impl NoiseFn<Point2<f64>> for OpenSimplex {
 /// This is a slower but higher quality form of gradient noise than `Perlin` 3D.
 impl NoiseFn<Point3<f64>> for OpenSimplex {
     fn get(&self, point: Point3<f64>) -> f64 {
-        fn gradient(perm_table: &PermutationTable, vertex: Point3<f64>, pos: Point3<f64>) -> f64 {
+        fn gradient(
+            perm_table: &PermutationTable,
+            vertex: Point3<f64>,
+            period: Vector3<usize>,
+            pos: Point3<f64>,
+        ) -> f64 {
             let attn = 2.0 - math::dot3(pos, pos);
             if attn > 0.0 {
-                let index = perm_table.get3(math::to_isize3(vertex));
+                let index = perm_table.get3(math::wrap3(math::to_isize3(vertex), period));
                 let vec = gradient::get3(index);
                 attn.powi(4) * math::dot3(pos, vec)
             } else {
@@ -160,119 +149,11 @@ impl NoiseFn<Point3<f64>> for OpenSimplex {
             }
         }
 
-        // Place input coordinates on simplectic h1.0ycomb.
-        let stretch_offset = math::fold3(point, Add::add) * STRETCH_CONSTANT_3D;
-        let stretched = math::map3(point, |v| v + stretch_offset);
-
-        // Floor to get simplectic h1.0ycomb coordinates of rhombohedron
-        // (stretched cube) super-cell origin.
-        let stretched_floor = math::map3(stretched, f64::floor);
-
-        // Skew out to get actual coordinates of rhombohedron origin. We'll need
-        // these later.
-        let squish_offset = math::fold3(stretched_floor, Add::add) * SQUISH_CONSTANT_3D;
-        let skewed_floor = math::map3(stretched_floor, |v| v + squish_offset);
-
-        // Compute simplectic h1.0ycomb coordinates relative to rhombohedral origin.
-        let rel_coords = math::sub3(stretched, stretched_floor);
-
-        // Sum those together to get a value that determines which region we're in.
-        let region_sum = math::fold3(rel_coords, Add::add);
-
-        // Positions relative to origin point.
-        let pos0 = math::sub3(point, skewed_floor);
-
         let mut value = 0.0;
-
-        let mut vertex;
-        let mut dpos;
-
-        if region_sum <= 1.0 {
-            // We're inside the tetrahedron (3-Simplex) at (0, 0, 0)
-            let t0 = SQUISH_CONSTANT_3D;
-            let t1 = SQUISH_CONSTANT_2D + 1.0;
-
-            // Contribution at (0, 0, 0)
-            vertex = math::add3(stretched_floor, [0.0, 0.0, 0.0]);
-            dpos = math::sub3(pos0, [0.0, 0.0, 0.0]);
-            value += gradient(&self.perm_table, vertex, dpos);
-
-            // Contribution at (1, 0, 0)
-            vertex = math::add3(stretched_floor, [1.0, 0.0, 0.0]);
-            dpos = math::sub3(pos0, [t1, t0, t0]);
-            value += gradient(&self.perm_table, vertex, dpos);
-
-            // Contribution at (0, 1, 0)
-            vertex = math::add3(stretched_floor, [0.0, 1.0, 0.0]);
-            dpos = math::sub3(pos0, [t0, t1, t0]);
-            value += gradient(&self.perm_table, vertex, dpos);
-
-            // Contribution at (0, 0, 1)
-            vertex = math::add3(stretched_floor, [0.0, 0.0, 1.0]);
-            dpos = math::sub3(pos0, [t0, t0, t1]);
-            value += gradient(&self.perm_table, vertex, dpos);
-        } else if region_sum >= 2.0 {
-            // We're inside the tetrahedron (3-Simplex) at (1, 1, 1)
-            let t0 = 2.0 * SQUISH_CONSTANT_3D;
-            let t1 = 1.0 + 2.0 * SQUISH_CONSTANT_3D;
-            let t2 = t1 + SQUISH_CONSTANT_3D;
-
-            // Contribution at (1, 1, 0)
-            vertex = math::add3(stretched_floor, [1.0, 1.0, 0.0]);
-            dpos = math::sub3(pos0, [t1, t1, t0]);
-            value += gradient(&self.perm_table, vertex, dpos);
-
-            // Contribution at (1, 0, 1)
-            vertex = math::add3(stretched_floor, [1.0, 0.0, 1.0]);
-            dpos = math::sub3(pos0, [t1, t0, t1]);
-            value += gradient(&self.perm_table, vertex, dpos);
-
-            // Contribution at (0, 1, 1)
-            vertex = math::add3(stretched_floor, [0.0, 1.0, 1.0]);
-            dpos = math::sub3(pos0, [t0, t1, t1]);
-            value += gradient(&self.perm_table, vertex, dpos);
-
-            // Contribution at (1, 1, 1)
-            vertex = math::add3(stretched_floor, [1.0, 1.0, 1.0]);
-            dpos = math::sub3(pos0, [t2, t2, t2]);
-            value += gradient(&self.perm_table, vertex, dpos);
-        } else {
-            // We're inside the octahedron (Rectified 3-Simplex) inbetween.
-            let t0 = SQUISH_CONSTANT_3D;
-            let t1 = 1.0 + SQUISH_CONSTANT_3D;
-            let t2 = 2.0 * SQUISH_CONSTANT_3D;
-            let t3 = 1.0 + 2.0 * SQUISH_CONSTANT_3D;
-
-            // Contribution at (1, 0, 0)
-            vertex = math::add3(stretched_floor, [1.0, 0.0, 0.0]);
-            dpos = math::sub3(pos0, [t1, t0, t0]);
-            value += gradient(&self.perm_table, vertex, dpos);
-
-            // Contribution at (0, 1, 0)
-            vertex = math::add3(stretched_floor, [0.0, 1.0, 0.0]);
-            dpos = math::sub3(pos0, [t0, t1, t0]);
-            value += gradient(&self.perm_table, vertex, dpos);
-
-            // Contribution at (0, 0, 1)
-            vertex = math::add3(stretched_floor, [0.0, 0.0, 1.0]);
-            dpos = math::sub3(pos0, [t0, t0, t1]);
-            value += gradient(&self.perm_table, vertex, dpos);
-
-            // Contribution at (1, 1, 0)
-            vertex = math::add3(stretched_floor, [1.0, 1.0, 0.0]);
-            dpos = math::sub3(pos0, [t3, t3, t2]);
-            value += gradient(&self.perm_table, vertex, dpos);
-
-            // Contribution at (1, 0, 1)
-            vertex = math::add3(stretched_floor, [1.0, 0.0, 1.0]);
-            dpos = math::sub3(pos0, [t3, t2, t3]);
-            value += gradient(&self.perm_table, vertex, dpos);
-
-            // Contribution at (0, 1, 1)
-            vertex = math::add3(stretched_floor, [0.0, 1.0, 1.0]);
-            dpos = math::sub3(pos0, [t2, t3, t3]);
-            value += gradient(&self.perm_table, vertex, dpos);
-        }
+        let period = [self.period[0], self.period[1], self.period[2]];
+        for_each_vertex3(point, |vertex, dpos| {
+            value += gradient(&self.perm_table, vertex, period, dpos);
+        });
 
         value * NORM_CONSTANT_3D
     }
@@ -284,10 +165,15 @@ impl NoiseFn<Point3<f64>> for OpenSimplex {
 impl NoiseFn<Point4<f64>> for OpenSimplex {
     fn get(&self, point: Point4<f64>) -> f64 {
         #[inline(always)]
-        fn gradient(perm_table: &PermutationTable, vertex: Point4<f64>, pos: Point4<f64>) -> f64 {
+        fn gradient(
+            perm_table: &PermutationTable,
+            vertex: Point4<f64>,
+            period: Vector4<usize>,
+            pos: Point4<f64>,
+        ) -> f64 {
             let attn = 2.0 - math::dot4(pos, pos);
             if attn > 0.0 {
-                let index = perm_table.get4(math::to_isize4(vertex));
+                let index = perm_table.get4(math::wrap4(math::to_isize4(vertex), period));
                 let vec = gradient::get4(index);
                 attn.powi(4) * math::dot4(pos, vec)
             } else {
@@ -295,322 +181,700 @@ impl NoiseFn<Point4<f64>> for OpenSimplex {
             }
         }
 
-        // Place input coordinates on simplectic h1.0ycomb.
-        let stretch_offset = math::fold4(point, Add::add) * STRETCH_CONSTANT_4D;
-        let stretched = math::map4(point, |v| v + stretch_offset);
-
-        // Floor to get simplectic h1.0ycomb coordinates of rhombo-hypercube
-        // super-cell origin.
-        let stretched_floor = math::map4(stretched, f64::floor);
-
-        // Skew out to get actual coordinates of stretched rhombo-hypercube origin.
-        // We'll need these later.
-        let squish_offset = math::fold4(stretched_floor, Add::add) * SQUISH_CONSTANT_4D;
-        let skewed_floor = math::map4(stretched_floor, |v| v + squish_offset);
+        struct Sum<'a> {
+            perm_table: &'a PermutationTable,
+            period: Vector4<usize>,
+            value: f64,
+        }
 
-        // Compute simplectic h1.0ycomb coordinates relative to rhombo-hypercube
-        // origin.
-        let rel_coords = math::sub4(stretched, stretched_floor);
+        impl<'a> VertexVisitor4 for Sum<'a> {
+            #[inline(always)]
+            fn visit(&mut self, vertex: Point4<f64>, pos: Vector4<f64>) {
+                self.value += gradient(self.perm_table, vertex, self.period, pos);
+            }
+        }
 
-        // Sum those together to get a value that determines which region
-        // we're in.
-        let region_sum = math::fold4(rel_coords, Add::add);
+        let mut sum = Sum {
+            perm_table: &self.perm_table,
+            period: self.period,
+            value: 0.0,
+        };
+        for_each_vertex4(point, &mut sum);
 
-        // Position relative to origin point.
-        let mut pos0 = math::sub4(point, skewed_floor);
+        sum.value * NORM_CONSTANT_4D
+    }
+}
 
-        let mut value = 0.0;
-        if region_sum <= 1.0 {
-            // We're inside the pentachoron (4-Simplex) at (0, 0, 0, 0)
-
-            // Contribution at (0, 0, 0, 0)
-            value += gradient(&self.perm_table, stretched_floor, pos0);
-
-            // Contribution at (1, 0, 0, 0)
-            let pos1;
-            {
-                let vertex = math::add4(stretched_floor, [1.0, 0.0, 0.0, 0.0]);
-                pos1 = math::sub4(
-                    pos0,
-                    [
-                        1.0 + SQUISH_CONSTANT_4D,
-                        SQUISH_CONSTANT_4D,
-                        SQUISH_CONSTANT_4D,
-                        SQUISH_CONSTANT_4D,
-                    ],
+/// 2-dimensional [`OpenSimplex` Noise](http://uniblock.tumblr.com/post/97868843242/noise), along
+/// with its gradient
+impl NoiseFnWithGradient<Point2<f64>> for OpenSimplex {
+    fn get_with_gradient(&self, point: Point2<f64>) -> (f64, Vector2<f64>) {
+        #[inline(always)]
+        fn gradient(
+            perm_table: &PermutationTable,
+            vertex: Point2<f64>,
+            period: Vector2<usize>,
+            pos: Vector2<f64>,
+        ) -> (f64, Vector2<f64>) {
+            let attn = 2.0 - math::dot2(pos, pos);
+            if attn > 0.0 {
+                let index = perm_table.get2(math::wrap2(math::to_isize2(vertex), period));
+                let vec = gradient::get2(index);
+                let dot = math::dot2(pos, vec);
+                let derivative = math::add2(
+                    math::mul2(vec, attn.powi(4)),
+                    math::mul2(pos, -8.0 * attn.powi(3) * dot),
                 );
-                value += gradient(&self.perm_table, vertex, pos1);
+                (attn.powi(4) * dot, derivative)
+            } else {
+                (0.0, [0.0; 2])
             }
+        }
 
-            // Contribution at (0, 1, 0, 0)
-            let pos2;
-            {
-                let vertex = math::add4(stretched_floor, [0.0, 1.0, 0.0, 0.0]);
-                pos2 = [pos1[0] + 1.0, pos1[1] - 1.0, pos1[2], pos1[3]];
-                value += gradient(&self.perm_table, vertex, pos2);
-            }
+        let mut value = 0.0;
+        let mut derivative = [0.0; 2];
+        let period = [self.period[0], self.period[1]];
+        for_each_vertex2(point, |vertex, dpos| {
+            let (vertex_value, vertex_derivative) =
+                gradient(&self.perm_table, vertex, period, dpos);
+            value += vertex_value;
+            derivative = math::add2(derivative, vertex_derivative);
+        });
+
+        (
+            value * NORM_CONSTANT_2D,
+            math::mul2(derivative, NORM_CONSTANT_2D),
+        )
+    }
+}
 
-            // Contribution at (0, 0, 1, 0)
-            let pos3;
-            {
-                let vertex = math::add4(stretched_floor, [0.0, 0.0, 1.0, 0.0]);
-                pos3 = [pos2[0], pos1[1], pos1[2] - 1.0, pos1[3]];
-                value += gradient(&self.perm_table, vertex, pos3);
+/// 3-dimensional [`OpenSimplex` Noise](http://uniblock.tumblr.com/post/97868843242/noise), along
+/// with its gradient
+impl NoiseFnWithGradient<Point3<f64>> for OpenSimplex {
+    fn get_with_gradient(&self, point: Point3<f64>) -> (f64, Vector3<f64>) {
+        #[inline(always)]
+        fn gradient(
+            perm_table: &PermutationTable,
+            vertex: Point3<f64>,
+            period: Vector3<usize>,
+            pos: Vector3<f64>,
+        ) -> (f64, Vector3<f64>) {
+            let attn = 2.0 - math::dot3(pos, pos);
+            if attn > 0.0 {
+                let index = perm_table.get3(math::wrap3(math::to_isize3(vertex), period));
+                let vec = gradient::get3(index);
+                let dot = math::dot3(pos, vec);
+                let derivative = math::add3(
+                    math::mul3(vec, attn.powi(4)),
+                    math::mul3(pos, -8.0 * attn.powi(3) * dot),
+                );
+                (attn.powi(4) * dot, derivative)
+            } else {
+                (0.0, [0.0; 3])
             }
+        }
 
-            // Contribution at (0, 0, 0, 1)
-            let pos4;
-            {
-                let vertex = math::add4(stretched_floor, [0.0, 0.0, 0.0, 1.0]);
-                pos4 = [pos2[0], pos1[1], pos1[2], pos1[3] - 1.0];
-                value += gradient(&self.perm_table, vertex, pos4);
-            }
-        } else if region_sum >= 3.0 {
-            // We're inside the pentachoron (4-Simplex) at (1, 1, 1, 1)
-            let squish_constant_3 = 3.0 * SQUISH_CONSTANT_4D;
-
-            // Contribution at (1, 1, 1, 0)
-            let pos4;
-            {
-                let vertex = math::add4(stretched_floor, [1.0, 1.0, 1.0, 0.0]);
-                pos4 = math::sub4(
-                    pos0,
-                    [
-                        1.0 + squish_constant_3,
-                        1.0 + squish_constant_3,
-                        1.0 + squish_constant_3,
-                        squish_constant_3,
-                    ],
+        let mut value = 0.0;
+        let mut derivative = [0.0; 3];
+        let period = [self.period[0], self.period[1], self.period[2]];
+        for_each_vertex3(point, |vertex, dpos| {
+            let (vertex_value, vertex_derivative) =
+                gradient(&self.perm_table, vertex, period, dpos);
+            value += vertex_value;
+            derivative = math::add3(derivative, vertex_derivative);
+        });
+
+        (
+            value * NORM_CONSTANT_3D,
+            math::mul3(derivative, NORM_CONSTANT_3D),
+        )
+    }
+}
+
+/// 4-dimensional [`OpenSimplex` Noise](http://uniblock.tumblr.com/post/97868843242/noise), along
+/// with its gradient
+impl NoiseFnWithGradient<Point4<f64>> for OpenSimplex {
+    fn get_with_gradient(&self, point: Point4<f64>) -> (f64, Vector4<f64>) {
+        #[inline(always)]
+        fn gradient(
+            perm_table: &PermutationTable,
+            vertex: Point4<f64>,
+            period: Vector4<usize>,
+            pos: Vector4<f64>,
+        ) -> (f64, Vector4<f64>) {
+            let attn = 2.0 - math::dot4(pos, pos);
+            if attn > 0.0 {
+                let index = perm_table.get4(math::wrap4(math::to_isize4(vertex), period));
+                let vec = gradient::get4(index);
+                let dot = math::dot4(pos, vec);
+                let derivative = math::add4(
+                    math::mul4(vec, attn.powi(4)),
+                    math::mul4(pos, -8.0 * attn.powi(3) * dot),
                 );
-                value += gradient(&self.perm_table, vertex, pos4);
+                (attn.powi(4) * dot, derivative)
+            } else {
+                (0.0, [0.0; 4])
             }
+        }
 
-            // Contribution at (1, 1, 0, 1)
-            let pos3;
-            {
-                let vertex = math::add4(stretched_floor, [1.0, 1.0, 0.0, 1.0]);
-                pos3 = [pos4[0], pos4[1], pos4[2] + 1.0, pos4[3] - 1.0];
-                value += gradient(&self.perm_table, vertex, pos3);
-            }
+        struct Sum<'a> {
+            perm_table: &'a PermutationTable,
+            period: Vector4<usize>,
+            value: f64,
+            derivative: Vector4<f64>,
+        }
 
-            // Contribution at (1, 0, 1, 1)
-            let pos2;
-            {
-                let vertex = math::add4(stretched_floor, [1.0, 0.0, 1.0, 1.0]);
-                pos2 = [pos4[0], pos4[1] + 1.0, pos4[2], pos3[3]];
-                value += gradient(&self.perm_table, vertex, pos2);
+        impl<'a> VertexVisitor4 for Sum<'a> {
+            #[inline(always)]
+            fn visit(&mut self, vertex: Point4<f64>, pos: Vector4<f64>) {
+                let (value, derivative) = gradient(self.perm_table, vertex, self.period, pos);
+                self.value += value;
+                self.derivative = math::add4(self.derivative, derivative);
             }
+        }
 
-            // Contribution at (0, 1, 1, 1)
-            let pos1;
-            {
-                let vertex = math::add4(stretched_floor, [0.0, 1.0, 1.0, 1.0]);
-                pos1 = [pos0[0] - squish_constant_3, pos4[1], pos4[2], pos3[3]];
-                value += gradient(&self.perm_table, vertex, pos1);
-            }
+        let mut sum = Sum {
+            perm_table: &self.perm_table,
+            period: self.period,
+            value: 0.0,
+            derivative: [0.0; 4],
+        };
+        for_each_vertex4(point, &mut sum);
+
+        (
+            sum.value * NORM_CONSTANT_4D,
+            math::mul4(sum.derivative, NORM_CONSTANT_4D),
+        )
+    }
+}
 
-            // Contribution at (1, 1, 1, 1)
-            {
-                let vertex = math::add4(stretched_floor, [1.0, 1.0, 1.0, 1.0]);
-                pos0[0] = pos4[0] - SQUISH_CONSTANT_4D;
-                pos0[1] = pos4[1] - SQUISH_CONSTANT_4D;
-                pos0[2] = pos4[2] - SQUISH_CONSTANT_4D;
-                pos0[3] = pos3[3] - SQUISH_CONSTANT_4D;
-                value += gradient(&self.perm_table, vertex, pos0);
-            }
-        } else if region_sum <= 2.0 {
-            // We're inside the first dispentachoron (Rectified 4-Simplex)
-
-            // Contribution at (1, 0, 0, 0)
-            let pos1;
-            {
-                let vertex = math::add4(stretched_floor, [1.0, 0.0, 0.0, 0.0]);
-                pos1 = math::sub4(
-                    pos0,
-                    [
-                        1.0 + SQUISH_CONSTANT_4D,
-                        SQUISH_CONSTANT_4D,
-                        SQUISH_CONSTANT_4D,
-                        SQUISH_CONSTANT_4D,
-                    ],
-                );
-                value += gradient(&self.perm_table, vertex, pos1);
-            }
+/// Calls `f` with each vertex of the 2-dimensional simplectic honeycomb that contributes to the
+/// point, along with the position of the point relative to the vertex.
+#[inline(always)]
+fn for_each_vertex2<F>(point: Point2<f64>, mut f: F)
+where
+    F: FnMut(Point2<f64>, Vector2<f64>),
+{
+    // Place input coordinates onto grid.
+    let stretch_offset = math::fold2(point, Add::add) * STRETCH_CONSTANT_2D;
+    let stretched = math::map2(point, |v| v + stretch_offset);
+
+    // Floor to get grid coordinates of rhombus (stretched square) cell origin.
+    let stretched_floor = math::map2(stretched, f64::floor);
+
+    // Skew out to get actual coordinates of rhombus origin. We'll need these later.
+    let squish_offset = math::fold2(stretched_floor, Add::add) * SQUISH_CONSTANT_2D;
+    let skewed_floor = math::map2(stretched_floor, |v| v + squish_offset);
+
+    // Compute grid coordinates relative to rhombus origin.
+    let rel_coords = math::sub2(stretched, stretched_floor);
+
+    // Sum those together to get a value that determines which region we're in.
+    let region_sum = math::fold2(rel_coords, Add::add);
+
+    // Positions relative to origin point (0, 0).
+    let pos0 = math::sub2(point, skewed_floor);
+
+    let mut vertex;
+    let mut dpos;
+
+    // (0, 0) --- (1, 0)
+    // |   A     /     |
+    // |       /       |
+    // |     /     B   |
+    // (0, 1) --- (1, 1)
+
+    let t0 = SQUISH_CONSTANT_2D;
+    let t1 = SQUISH_CONSTANT_2D + 1.0;
+    let t2 = SQUISH_CONSTANT_2D + t1;
+
+    // Contribution (1, 0)
+    vertex = math::add2(stretched_floor, [1.0, 0.0]);
+    dpos = math::sub2(pos0, [t1, t0]);
+    f(vertex, dpos);
+
+    // Contribution (0, 1)
+    vertex = math::add2(stretched_floor, [0.0, 1.0]);
+    dpos = math::sub2(pos0, [t0, t1]);
+    f(vertex, dpos);
+
+    // See the graph for an intuitive explanation; the sum of `x` and `y` is
+    // only greater than `1` if we're on Region B.
+    if region_sum > 1.0 {
+        // Contribution (1, 1)
+        vertex = math::add2(stretched_floor, [1.0, 1.0]);
+        // We are moving across the diagonal `/`, so we'll need to add by the
+        // squish constant
+        dpos = math::sub2(pos0, [t2, t2]);
+    } else {
+        vertex = math::add2(stretched_floor, [0.0, 0.0]);
+        dpos = math::sub2(pos0, [0.0, 0.0]);
+    }
 
-            // Contribution at (0, 1, 0, 0)
-            let pos2;
-            {
-                let vertex = math::add4(stretched_floor, [0.0, 1.0, 0.0, 0.0]);
-                pos2 = [pos1[0] + 1.0, pos1[1] - 1.0, pos1[2], pos1[3]];
-                value += gradient(&self.perm_table, vertex, pos2);
-            }
+    // Point (0, 0) or (1, 1)
+    f(vertex, dpos);
+}
 
-            // Contribution at (0, 0, 1, 0)
-            let pos3;
-            {
-                let vertex = math::add4(stretched_floor, [0.0, 0.0, 1.0, 0.0]);
-                pos3 = [pos2[0], pos1[1], pos1[2] - 1.0, pos1[3]];
-                value += gradient(&self.perm_table, vertex, pos3);
-            }
+/// Calls `f` with each vertex of the 3-dimensional simplectic honeycomb that contributes to the
+/// point, along with the position of the point relative to the vertex.
+#[inline(always)]
+fn for_each_vertex3<F>(point: Point3<f64>, mut f: F)
+where
+    F: FnMut(Point3<f64>, Vector3<f64>),
+{
+    // Place input coordinates on simplectic h1.0ycomb.
+    let stretch_offset = math::fold3(point, Add::add) * STRETCH_CONSTANT_3D;
+    let stretched = math::map3(point, |v| v + stretch_offset);
+
+    // Floor to get simplectic h1.0ycomb coordinates of rhombohedron
+    // (stretched cube) super-cell origin.
+    let stretched_floor = math::map3(stretched, f64::floor);
+
+    // Skew out to get actual coordinates of rhombohedron origin. We'll need
+    // these later.
+    let squish_offset = math::fold3(stretched_floor, Add::add) * SQUISH_CONSTANT_3D;
+    let skewed_floor = math::map3(stretched_floor, |v| v + squish_offset);
+
+    // Compute simplectic h1.0ycomb coordinates relative to rhombohedral origin.
+    let rel_coords = math::sub3(stretched, stretched_floor);
+
+    // Sum those together to get a value that determines which region we're in.
+    let region_sum = math::fold3(rel_coords, Add::add);
+
+    // Positions relative to origin point.
+    let pos0 = math::sub3(point, skewed_floor);
+
+    let mut vertex;
+    let mut dpos;
+
+    if region_sum <= 1.0 {
+        // We're inside the tetrahedron (3-Simplex) at (0, 0, 0)
+        let t0 = SQUISH_CONSTANT_3D;
+        let t1 = SQUISH_CONSTANT_2D + 1.0;
 
-            // Contribution at (0, 0, 0, 1)
-            let pos4;
-            {
-                let vertex = math::add4(stretched_floor, [0.0, 0.0, 0.0, 1.0]);
-                pos4 = [pos2[0], pos1[1], pos1[2], pos1[3] - 1.0];
-                value += gradient(&self.perm_table, vertex, pos4);
-            }
+        // Contribution at (0, 0, 0)
+        vertex = math::add3(stretched_floor, [0.0, 0.0, 0.0]);
+        dpos = math::sub3(pos0, [0.0, 0.0, 0.0]);
+        f(vertex, dpos);
+
+        // Contribution at (1, 0, 0)
+        vertex = math::add3(stretched_floor, [1.0, 0.0, 0.0]);
+        dpos = math::sub3(pos0, [t1, t0, t0]);
+        f(vertex, dpos);
+
+        // Contribution at (0, 1, 0)
+        vertex = math::add3(stretched_floor, [0.0, 1.0, 0.0]);
+        dpos = math::sub3(pos0, [t0, t1, t0]);
+        f(vertex, dpos);
+
+        // Contribution at (0, 0, 1)
+        vertex = math::add3(stretched_floor, [0.0, 0.0, 1.0]);
+        dpos = math::sub3(pos0, [t0, t0, t1]);
+        f(vertex, dpos);
+    } else if region_sum >= 2.0 {
+        // We're inside the tetrahedron (3-Simplex) at (1, 1, 1)
+        let t0 = 2.0 * SQUISH_CONSTANT_3D;
+        let t1 = 1.0 + 2.0 * SQUISH_CONSTANT_3D;
+        let t2 = t1 + SQUISH_CONSTANT_3D;
+
+        // Contribution at (1, 1, 0)
+        vertex = math::add3(stretched_floor, [1.0, 1.0, 0.0]);
+        dpos = math::sub3(pos0, [t1, t1, t0]);
+        f(vertex, dpos);
+
+        // Contribution at (1, 0, 1)
+        vertex = math::add3(stretched_floor, [1.0, 0.0, 1.0]);
+        dpos = math::sub3(pos0, [t1, t0, t1]);
+        f(vertex, dpos);
+
+        // Contribution at (0, 1, 1)
+        vertex = math::add3(stretched_floor, [0.0, 1.0, 1.0]);
+        dpos = math::sub3(pos0, [t0, t1, t1]);
+        f(vertex, dpos);
+
+        // Contribution at (1, 1, 1)
+        vertex = math::add3(stretched_floor, [1.0, 1.0, 1.0]);
+        dpos = math::sub3(pos0, [t2, t2, t2]);
+        f(vertex, dpos);
+    } else {
+        // We're inside the octahedron (Rectified 3-Simplex) inbetween.
+        let t0 = SQUISH_CONSTANT_3D;
+        let t1 = 1.0 + SQUISH_CONSTANT_3D;
+        let t2 = 2.0 * SQUISH_CONSTANT_3D;
+        let t3 = 1.0 + 2.0 * SQUISH_CONSTANT_3D;
+
+        // Contribution at (1, 0, 0)
+        vertex = math::add3(stretched_floor, [1.0, 0.0, 0.0]);
+        dpos = math::sub3(pos0, [t1, t0, t0]);
+        f(vertex, dpos);
+
+        // Contribution at (0, 1, 0)
+        vertex = math::add3(stretched_floor, [0.0, 1.0, 0.0]);
+        dpos = math::sub3(pos0, [t0, t1, t0]);
+        f(vertex, dpos);
+
+        // Contribution at (0, 0, 1)
+        vertex = math::add3(stretched_floor, [0.0, 0.0, 1.0]);
+        dpos = math::sub3(pos0, [t0, t0, t1]);
+        f(vertex, dpos);
+
+        // Contribution at (1, 1, 0)
+        vertex = math::add3(stretched_floor, [1.0, 1.0, 0.0]);
+        dpos = math::sub3(pos0, [t3, t3, t2]);
+        f(vertex, dpos);
+
+        // Contribution at (1, 0, 1)
+        vertex = math::add3(stretched_floor, [1.0, 0.0, 1.0]);
+        dpos = math::sub3(pos0, [t3, t2, t3]);
+        f(vertex, dpos);
+
+        // Contribution at (0, 1, 1)
+        vertex = math::add3(stretched_floor, [0.0, 1.0, 1.0]);
+        dpos = math::sub3(pos0, [t2, t3, t3]);
+        f(vertex, dpos);
+    }
+}
 
-            // Contribution at (1, 1, 0, 0)
-            let pos5;
-            {
-                let vertex = math::add4(stretched_floor, [1.0, 1.0, 0.0, 0.0]);
-                pos5 = [
-                    pos1[0] - SQUISH_CONSTANT_4D,
-                    pos2[1] - SQUISH_CONSTANT_4D,
-                    pos1[2] - SQUISH_CONSTANT_4D,
-                    pos1[3] - SQUISH_CONSTANT_4D,
-                ];
-                value += gradient(&self.perm_table, vertex, pos5);
-            }
+/// Receives the vertices visited by `for_each_vertex4`.
+///
+/// The 4D lattice walk has too many call sites for a closure to be inlined
+/// into, which makes it much slower. Unlike a closure, `visit` can be forced
+/// inline.
+trait VertexVisitor4 {
+    fn visit(&mut self, vertex: Point4<f64>, pos: Vector4<f64>);
+}
 
-            // Contribution at (1, 0, 1, 0)
-            let pos6;
-            {
-                let vertex = math::add4(stretched_floor, [1.0, 0.0, 1.0, 0.0]);
-                pos6 = [pos5[0], pos5[1] + 1.0, pos5[2] - 1.0, pos5[3]];
-                value += gradient(&self.perm_table, vertex, pos6);
-            }
+/// Calls `visitor` with each vertex of the 4-dimensional simplectic honeycomb that contributes to
+/// the point, along with the position of the point relative to the vertex.
+#[inline(always)]
+fn for_each_vertex4<V>(point: Point4<f64>, visitor: &mut V)
+where
+    V: VertexVisitor4,
+{
+    // Place input coordinates on simplectic h1.0ycomb.
+    let stretch_offset = math::fold4(point, Add::add) * STRETCH_CONSTANT_4D;
+    let stretched = math::map4(point, |v| v + stretch_offset);
+
+    // Floor to get simplectic h1.0ycomb coordinates of rhombo-hypercube
+    // super-cell origin.
+    let stretched_floor = math::map4(stretched, f64::floor);
+
+    // Skew out to get actual coordinates of stretched rhombo-hypercube origin.
+    // We'll need these later.
+    let squish_offset = math::fold4(stretched_floor, Add::add) * SQUISH_CONSTANT_4D;
+    let skewed_floor = math::map4(stretched_floor, |v| v + squish_offset);
+
+    // Compute simplectic h1.0ycomb coordinates relative to rhombo-hypercube
+    // origin.
+    let rel_coords = math::sub4(stretched, stretched_floor);
+
+    // Sum those together to get a value that determines which region
+    // we're in.
+    let region_sum = math::fold4(rel_coords, Add::add);
+
+    // Position relative to origin point.
+    let mut pos0 = math::sub4(point, skewed_floor);
+
+    if region_sum <= 1.0 {
+        // We're inside the pentachoron (4-Simplex) at (0, 0, 0, 0)
+
+        // Contribution at (0, 0, 0, 0)
+        visitor.visit(stretched_floor, pos0);
+
+        // Contribution at (1, 0, 0, 0)
+        let pos1;
+        {
+            let vertex = math::add4(stretched_floor, [1.0, 0.0, 0.0, 0.0]);
+            pos1 = math::sub4(
+                pos0,
+                [
+                    1.0 + SQUISH_CONSTANT_4D,
+                    SQUISH_CONSTANT_4D,
+                    SQUISH_CONSTANT_4D,
+                    SQUISH_CONSTANT_4D,
+                ],
+            );
+            visitor.visit(vertex, pos1);
+        }
 
-            // Contribution at (1, 0, 0, 1)
-            let pos7;
-            {
-                let vertex = math::add4(stretched_floor, [1.0, 0.0, 0.0, 1.0]);
-                pos7 = [pos5[0], pos6[1], pos5[2], pos5[3] - 1.0];
-                value += gradient(&self.perm_table, vertex, pos7);
-            }
+        // Contribution at (0, 1, 0, 0)
+        let pos2;
+        {
+            let vertex = math::add4(stretched_floor, [0.0, 1.0, 0.0, 0.0]);
+            pos2 = [pos1[0] + 1.0, pos1[1] - 1.0, pos1[2], pos1[3]];
+            visitor.visit(vertex, pos2);
+        }
 
-            // Contribution at (0, 1, 1, 0)
-            let pos8;
-            {
-                let vertex = math::add4(stretched_floor, [0.0, 1.0, 1.0, 0.0]);
-                pos8 = [pos5[0] + 1.0, pos5[1], pos6[2], pos5[3]];
-                value += gradient(&self.perm_table, vertex, pos8);
-            }
+        // Contribution at (0, 0, 1, 0)
+        let pos3;
+        {
+            let vertex = math::add4(stretched_floor, [0.0, 0.0, 1.0, 0.0]);
+            pos3 = [pos2[0], pos1[1], pos1[2] - 1.0, pos1[3]];
+            visitor.visit(vertex, pos3);
+        }
 
-            // Contribution at (0, 1, 0, 1)
-            let pos9;
-            {
-                let vertex = math::add4(stretched_floor, [0.0, 1.0, 0.0, 1.0]);
-                pos9 = [pos8[0], pos5[1], pos5[2], pos7[3]];
-                value += gradient(&self.perm_table, vertex, pos9);
-            }
+        // Contribution at (0, 0, 0, 1)
+        let pos4;
+        {
+            let vertex = math::add4(stretched_floor, [0.0, 0.0, 0.0, 1.0]);
+            pos4 = [pos2[0], pos1[1], pos1[2], pos1[3] - 1.0];
+            visitor.visit(vertex, pos4);
+        }
+    } else if region_sum >= 3.0 {
+        // We're inside the pentachoron (4-Simplex) at (1, 1, 1, 1)
+        let squish_constant_3 = 3.0 * SQUISH_CONSTANT_4D;
+
+        // Contribution at (1, 1, 1, 0)
+        let pos4;
+        {
+            let vertex = math::add4(stretched_floor, [1.0, 1.0, 1.0, 0.0]);
+            pos4 = math::sub4(
+                pos0,
+                [
+                    1.0 + squish_constant_3,
+                    1.0 + squish_constant_3,
+                    1.0 + squish_constant_3,
+                    squish_constant_3,
+                ],
+            );
+            visitor.visit(vertex, pos4);
+        }
 
-            // Contribution at (0, 0, 1, 1)
-            let pos10;
-            {
-                let vertex = math::add4(stretched_floor, [0.0, 0.0, 1.0, 1.0]);
-                pos10 = [pos8[0], pos6[1], pos6[2], pos7[3]];
-                value += gradient(&self.perm_table, vertex, pos10);
-            }
-        } else {
-            // We're inside the second dispentachoron (Rectified 4-Simplex)
-            let squish_constant_3 = 3.0 * SQUISH_CONSTANT_4D;
-
-            // Contribution at (1, 1, 1, 0)
-            let pos4;
-            {
-                let vertex = math::add4(stretched_floor, [1.0, 1.0, 1.0, 0.0]);
-                pos4 = math::sub4(
-                    pos0,
-                    [
-                        1.0 + squish_constant_3,
-                        1.0 + squish_constant_3,
-                        1.0 + squish_constant_3,
-                        squish_constant_3,
-                    ],
-                );
-                value += gradient(&self.perm_table, vertex, pos4);
-            }
+        // Contribution at (1, 1, 0, 1)
+        let pos3;
+        {
+            let vertex = math::add4(stretched_floor, [1.0, 1.0, 0.0, 1.0]);
+            pos3 = [pos4[0], pos4[1], pos4[2] + 1.0, pos4[3] - 1.0];
+            visitor.visit(vertex, pos3);
+        }
 
-            // Contribution at (1, 1, 0, 1)
-            let pos3;
-            {
-                let vertex = math::add4(stretched_floor, [1.0, 1.0, 0.0, 1.0]);
-                pos3 = [pos4[0], pos4[1], pos4[2] + 1.0, pos4[3] - 1.0];
-                value += gradient(&self.perm_table, vertex, pos3);
-            }
+        // Contribution at (1, 0, 1, 1)
+        let pos2;
+        {
+            let vertex = math::add4(stretched_floor, [1.0, 0.0, 1.0, 1.0]);
+            pos2 = [pos4[0], pos4[1] + 1.0, pos4[2], pos3[3]];
+            visitor.visit(vertex, pos2);
+        }
 
-            // Contribution at (1, 0, 1, 1)
-            let pos2;
-            {
-                let vertex = math::add4(stretched_floor, [1.0, 0.0, 1.0, 1.0]);
-                pos2 = [pos4[0], pos4[1] + 1.0, pos4[2], pos3[3]];
-                value += gradient(&self.perm_table, vertex, pos2);
-            }
+        // Contribution at (0, 1, 1, 1)
+        let pos1;
+        {
+            let vertex = math::add4(stretched_floor, [0.0, 1.0, 1.0, 1.0]);
+            pos1 = [pos0[0] - squish_constant_3, pos4[1], pos4[2], pos3[3]];
+            visitor.visit(vertex, pos1);
+        }
 
-            // Contribution at (0, 1, 1, 1)
-            let pos1;
-            {
-                let vertex = math::add4(stretched_floor, [0.0, 1.0, 1.0, 1.0]);
-                pos1 = [pos4[0] + 1.0, pos4[1], pos4[2], pos3[3]];
-                value += gradient(&self.perm_table, vertex, pos1);
-            }
+        // Contribution at (1, 1, 1, 1)
+        {
+            let vertex = math::add4(stretched_floor, [1.0, 1.0, 1.0, 1.0]);
+            pos0[0] = pos4[0] - SQUISH_CONSTANT_4D;
+            pos0[1] = pos4[1] - SQUISH_CONSTANT_4D;
+            pos0[2] = pos4[2] - SQUISH_CONSTANT_4D;
+            pos0[3] = pos3[3] - SQUISH_CONSTANT_4D;
+            visitor.visit(vertex, pos0);
+        }
+    } else if region_sum <= 2.0 {
+        // We're inside the first dispentachoron (Rectified 4-Simplex)
+
+        // Contribution at (1, 0, 0, 0)
+        let pos1;
+        {
+            let vertex = math::add4(stretched_floor, [1.0, 0.0, 0.0, 0.0]);
+            pos1 = math::sub4(
+                pos0,
+                [
+                    1.0 + SQUISH_CONSTANT_4D,
+                    SQUISH_CONSTANT_4D,
+                    SQUISH_CONSTANT_4D,
+                    SQUISH_CONSTANT_4D,
+                ],
+            );
+            visitor.visit(vertex, pos1);
+        }
 
-            // Contribution at (1, 1, 0, 0)
-            let pos5;
-            {
-                let vertex = math::add4(stretched_floor, [1.0, 1.0, 0.0, 0.0]);
-                pos5 = [
-                    pos4[0] + SQUISH_CONSTANT_4D,
-                    pos4[1] + SQUISH_CONSTANT_4D,
-                    pos3[2] + SQUISH_CONSTANT_4D,
-                    pos4[3] + SQUISH_CONSTANT_4D,
-                ];
-                value += gradient(&self.perm_table, vertex, pos5);
-            }
+        // Contribution at (0, 1, 0, 0)
+        let pos2;
+        {
+            let vertex = math::add4(stretched_floor, [0.0, 1.0, 0.0, 0.0]);
+            pos2 = [pos1[0] + 1.0, pos1[1] - 1.0, pos1[2], pos1[3]];
+            visitor.visit(vertex, pos2);
+        }
 
-            // Contribution at (1, 0, 1, 0)
-            let pos6;
-            {
-                let vertex = math::add4(stretched_floor, [1.0, 0.0, 1.0, 0.0]);
-                pos6 = [pos5[0], pos5[1] + 1.0, pos5[2] - 1.0, pos5[3]];
-                value += gradient(&self.perm_table, vertex, pos6);
-            }
+        // Contribution at (0, 0, 1, 0)
+        let pos3;
+        {
+            let vertex = math::add4(stretched_floor, [0.0, 0.0, 1.0, 0.0]);
+            pos3 = [pos2[0], pos1[1], pos1[2] - 1.0, pos1[3]];
+            visitor.visit(vertex, pos3);
+        }
 
-            // Contribution at (1, 0, 0, 1)
-            let pos7;
-            {
-                let vertex = math::add4(stretched_floor, [1.0, 0.0, 0.0, 1.0]);
-                pos7 = [pos5[0], pos6[1], pos5[2], pos5[3] - 1.0];
-                value += gradient(&self.perm_table, vertex, pos7);
-            }
+        // Contribution at (0, 0, 0, 1)
+        let pos4;
+        {
+            let vertex = math::add4(stretched_floor, [0.0, 0.0, 0.0, 1.0]);
+            pos4 = [pos2[0], pos1[1], pos1[2], pos1[3] - 1.0];
+            visitor.visit(vertex, pos4);
+        }
 
-            // Contribution at (0, 1, 1, 0)
-            let pos8;
-            {
-                let vertex = math::add4(stretched_floor, [0.0, 1.0, 1.0, 0.0]);
-                pos8 = [pos5[0] + 1.0, pos5[1], pos6[2], pos5[3]];
-                value += gradient(&self.perm_table, vertex, pos8);
-            }
+        // Contribution at (1, 1, 0, 0)
+        let pos5;
+        {
+            let vertex = math::add4(stretched_floor, [1.0, 1.0, 0.0, 0.0]);
+            pos5 = [
+                pos1[0] - SQUISH_CONSTANT_4D,
+                pos2[1] - SQUISH_CONSTANT_4D,
+                pos1[2] - SQUISH_CONSTANT_4D,
+                pos1[3] - SQUISH_CONSTANT_4D,
+            ];
+            visitor.visit(vertex, pos5);
+        }
 
-            // Contribution at (0, 1, 0, 1)
-            let pos9;
-            {
-                let vertex = math::add4(stretched_floor, [0.0, 1.0, 0.0, 1.0]);
-                pos9 = [pos8[0], pos5[1], pos5[2], pos7[3]];
-                value += gradient(&self.perm_table, vertex, pos9);
-            }
+        // Contribution at (1, 0, 1, 0)
+        let pos6;
+        {
+            let vertex = math::add4(stretched_floor, [1.0, 0.0, 1.0, 0.0]);
+            pos6 = [pos5[0], pos5[1] + 1.0, pos5[2] - 1.0, pos5[3]];
+            visitor.visit(vertex, pos6);
+        }
 
-            // Contribution at (0, 0, 1, 1)
-            let pos10;
-            {
-                let vertex = math::add4(stretched_floor, [0.0, 0.0, 1.0, 1.0]);
-                pos10 = [pos8[0], pos6[1], pos6[2], pos7[3]];
-                value += gradient(&self.perm_table, vertex, pos10);
-            }
+        // Contribution at (1, 0, 0, 1)
+        let pos7;
+        {
+            let vertex = math::add4(stretched_floor, [1.0, 0.0, 0.0, 1.0]);
+            pos7 = [pos5[0], pos6[1], pos5[2], pos5[3] - 1.0];
+            visitor.visit(vertex, pos7);
+        }
+
+        // Contribution at (0, 1, 1, 0)
+        let pos8;
+        {
+            let vertex = math::add4(stretched_floor, [0.0, 1.0, 1.0, 0.0]);
+            pos8 = [pos5[0] + 1.0, pos5[1], pos6[2], pos5[3]];
+            visitor.visit(vertex, pos8);
+        }
+
+        // Contribution at (0, 1, 0, 1)
+        let pos9;
+        {
+            let vertex = math::add4(stretched_floor, [0.0, 1.0, 0.0, 1.0]);
+            pos9 = [pos8[0], pos5[1], pos5[2], pos7[3]];
+            visitor.visit(vertex, pos9);
+        }
+
+        // Contribution at (0, 0, 1, 1)
+        let pos10;
+        {
+            let vertex = math::add4(stretched_floor, [0.0, 0.0, 1.0, 1.0]);
+            pos10 = [pos8[0], pos6[1], pos6[2], pos7[3]];
+            visitor.visit(vertex, pos10);
+        }
+    } else {
+        // We're inside the second dispentachoron (Rectified 4-Simplex)
+        let squish_constant_3 = 3.0 * SQUISH_CONSTANT_4D;
+
+        // Contribution at (1, 1, 1, 0)
+        let pos4;
+        {
+            let vertex = math::add4(stretched_floor, [1.0, 1.0, 1.0, 0.0]);
+            pos4 = math::sub4(
+                pos0,
+                [
+                    1.0 + squish_constant_3,
+                    1.0 + squish_constant_3,
+                    1.0 + squish_constant_3,
+                    squish_constant_3,
+                ],
+            );
+            visitor.visit(vertex, pos4);
+        }
+
+        // Contribution at (1, 1, 0, 1)
+        let pos3;
+        {
+            let vertex = math::add4(stretched_floor, [1.0, 1.0, 0.0, 1.0]);
+            pos3 = [pos4[0], pos4[1], pos4[2] + 1.0, pos4[3] - 1.0];
+            visitor.visit(vertex, pos3);
+        }
+
+        // Contribution at (1, 0, 1, 1)
+        let pos2;
+        {
+            let vertex = math::add4(stretched_floor, [1.0, 0.0, 1.0, 1.0]);
+            pos2 = [pos4[0], pos4[1] + 1.0, pos4[2], pos3[3]];
+            visitor.visit(vertex, pos2);
+        }
+
+        // Contribution at (0, 1, 1, 1)
+        let pos1;
+        {
+            let vertex = math::add4(stretched_floor, [0.0, 1.0, 1.0, 1.0]);
+            pos1 = [pos4[0] + 1.0, pos4[1], pos4[2], pos3[3]];
+            visitor.visit(vertex, pos1);
+        }
+
+        // Contribution at (1, 1, 0, 0)
+        let pos5;
+        {
+            let vertex = math::add4(stretched_floor, [1.0, 1.0, 0.0, 0.0]);
+            pos5 = [
+                pos4[0] + SQUISH_CONSTANT_4D,
+                pos4[1] + SQUISH_CONSTANT_4D,
+                pos3[2] + SQUISH_CONSTANT_4D,
+                pos4[3] + SQUISH_CONSTANT_4D,
+            ];
+            visitor.visit(vertex, pos5);
+        }
+
+        // Contribution at (1, 0, 1, 0)
+        let pos6;
+        {
+            let vertex = math::add4(stretched_floor, [1.0, 0.0, 1.0, 0.0]);
+            pos6 = [pos5[0], pos5[1] + 1.0, pos5[2] - 1.0, pos5[3]];
+            visitor.visit(vertex, pos6);
+        }
+
+        // Contribution at (1, 0, 0, 1)
+        let pos7;
+        {
+            let vertex = math::add4(stretched_floor, [1.0, 0.0, 0.0, 1.0]);
+            pos7 = [pos5[0], pos6[1], pos5[2], pos5[3] - 1.0];
+            visitor.visit(vertex, pos7);
         }
 
-        value * NORM_CONSTANT_4D
+        // Contribution at (0, 1, 1, 0)
+        let pos8;
+        {
+            let vertex = math::add4(stretched_floor, [0.0, 1.0, 1.0, 0.0]);
+            pos8 = [pos5[0] + 1.0, pos5[1], pos6[2], pos5[3]];
+            visitor.visit(vertex, pos8);
+        }
+
+        // Contribution at (0, 1, 0, 1)
+        let pos9;
+        {
+            let vertex = math::add4(stretched_floor, [0.0, 1.0, 0.0, 1.0]);
+            pos9 = [pos8[0], pos5[1], pos5[2], pos7[3]];
+            visitor.visit(vertex, pos9);
+        }
+
+        // Contribution at (0, 0, 1, 1)
+        let pos10;
+        {
+            let vertex = math::add4(stretched_floor, [0.0, 0.0, 1.0, 1.0]);
+            pos10 = [pos8[0], pos6[1], pos6[2], pos7[3]];
+            visitor.visit(vertex, pos10);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noise_fns::tests::assert_gradients_match;
+
+    #[test]
+    fn test_gradients_match_finite_differences() {
+        assert_gradients_match(&OpenSimplex::new());
+        assert_gradients_match(&OpenSimplex::new().set_period([3; 4]));
     }
 }
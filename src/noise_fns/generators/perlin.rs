@@ -1,13 +1,18 @@
 use math::{Point2, Point3, Point4, Vector2, Vector3, Vector4};
-use noise_fns::{NoiseFn, Seedable};
-use permutationtable::PermutationTable;
+use noise_fns::{NoiseFn, NoiseFnWithGradient, Seedable};
+use permutationtable::{LatticeHash, LatticeHashable, PermutationTable};
 use {gradient, math};
 
+const SCALE_FACTOR_2D: f64 = 3.160_493_827_160_493_7;
+const SCALE_FACTOR_3D: f64 = 3.889_855_325_553_107_4;
+const SCALE_FACTOR_4D: f64 = 4.424_369_240_215_691;
+
 /// Noise function that outputs 2/3/4-dimensional Perlin noise.
 #[derive(Clone, Copy, Debug)]
 pub struct Perlin {
     seed: u32,
     perm_table: PermutationTable,
+    period: [usize; 4],
 }
 
 impl Perlin {
@@ -17,8 +22,21 @@ impl Perlin {
         Self {
             seed: Self::DEFAULT_SEED,
             perm_table: PermutationTable::new(Self::DEFAULT_SEED),
+            period: [0; 4],
         }
     }
+
+    /// Sets the number of lattice cells after which the noise repeats along
+    /// each axis, so that it tiles exactly. A period of zero doesn't repeat.
+    ///
+    /// 2- and 3-dimensional noise use the first two and three periods.
+    pub fn set_period(self, period: [usize; 4]) -> Self {
+        Self { period, ..self }
+    }
+
+    pub fn period(&self) -> [usize; 4] {
+        self.period
+    }
 }
 
 impl Default for Perlin {
@@ -38,7 +56,8 @@ impl Seedable for Perlin {
         // Otherwise, regenerate the permutation table based on the new seed.
         Self {
             seed,
-            perm_table: PermutationTable::new(seed),
+            perm_table: PermutationTable::with_hash(seed, self.perm_table.hash()),
+            ..self
         }
     }
 
@@ -47,11 +66,22 @@ impl Seedable for Perlin {
     }
 }
 
+impl LatticeHashable for Perlin {
+    fn set_lattice_hash(self, hash: LatticeHash) -> Self {
+        Self {
+            perm_table: PermutationTable::with_hash(self.seed, hash),
+            ..self
+        }
+    }
+
+    fn lattice_hash(&self) -> LatticeHash {
+        self.perm_table.hash()
+    }
+}
+
 /// 2-dimensional perlin noise
 impl NoiseFn<Point2<f64>> for Perlin {
     fn get(&self, point: Point2<f64>) -> f64 {
-        const SCALE_FACTOR: f64 = 3.160_493_827_160_493_7;
-
         #[inline(always)]
         fn surflet(
             perm_table: &PermutationTable,
@@ -67,8 +97,10 @@ impl NoiseFn<Point2<f64>> for Perlin {
         }
 
         let floored = math::map2(point, f64::floor);
-        let near_corner = math::to_isize2(floored);
-        let far_corner = math::add2(near_corner, math::one2());
+        let period = [self.period[0], self.period[1]];
+        let corner = math::to_isize2(floored);
+        let near_corner = math::wrap2(corner, period);
+        let far_corner = math::wrap2(math::add2(corner, math::one2()), period);
         let near_distance = math::sub2(point, floored);
         let far_distance = math::sub2(near_distance, math::one2());
 
@@ -94,15 +126,13 @@ impl NoiseFn<Point2<f64>> for Perlin {
         );
 
         // Multiply by arbitrary value to scale to -1..1
-        math::clamp((f00 + f10 + f01 + f11) * SCALE_FACTOR, -1.0, 1.0)
+        math::clamp((f00 + f10 + f01 + f11) * SCALE_FACTOR_2D, -1.0, 1.0)
     }
 }
 
 /// 3-dimensional perlin noise
 impl NoiseFn<Point3<f64>> for Perlin {
     fn get(&self, point: Point3<f64>) -> f64 {
-        const SCALE_FACTOR: f64 = 3.889_855_325_553_107_4;
-
         #[inline(always)]
         fn surflet(
             perm_table: &PermutationTable,
@@ -118,8 +148,10 @@ impl NoiseFn<Point3<f64>> for Perlin {
         }
 
         let floored = math::map3(point, f64::floor);
-        let near_corner = math::to_isize3(floored);
-        let far_corner = math::add3(near_corner, math::one3());
+        let period = [self.period[0], self.period[1], self.period[2]];
+        let corner = math::to_isize3(floored);
+        let near_corner = math::wrap3(corner, period);
+        let far_corner = math::wrap3(math::add3(corner, math::one3()), period);
         let near_distance = math::sub3(point, floored);
         let far_distance = math::sub3(near_distance, math::one3());
 
@@ -166,7 +198,7 @@ impl NoiseFn<Point3<f64>> for Perlin {
 
         // Multiply by arbitrary value to scale to -1..1
         math::clamp(
-            (f000 + f100 + f010 + f110 + f001 + f101 + f011 + f111) * SCALE_FACTOR,
+            (f000 + f100 + f010 + f110 + f001 + f101 + f011 + f111) * SCALE_FACTOR_3D,
             -1.0,
             1.0,
         )
@@ -176,8 +208,6 @@ impl NoiseFn<Point3<f64>> for Perlin {
 /// 4-dimensional perlin noise
 impl NoiseFn<Point4<f64>> for Perlin {
     fn get(&self, point: Point4<f64>) -> f64 {
-        const SCALE_FACTOR: f64 = 4.424_369_240_215_691;
-
         #[inline(always)]
         fn surflet(
             perm_table: &PermutationTable,
@@ -193,8 +223,10 @@ impl NoiseFn<Point4<f64>> for Perlin {
         }
 
         let floored = math::map4(point, f64::floor);
-        let near_corner = math::to_isize4(floored);
-        let far_corner = math::add4(near_corner, math::one4());
+        let period = self.period;
+        let corner = math::to_isize4(floored);
+        let near_corner = math::wrap4(corner, period);
+        let far_corner = math::wrap4(math::add4(corner, math::one4()), period);
         let near_distance = math::sub4(point, floored);
         let far_distance = math::sub4(near_distance, math::one4());
 
@@ -402,9 +434,189 @@ impl NoiseFn<Point4<f64>> for Perlin {
                 + f1011
                 + f0111
                 + f1111)
-                * SCALE_FACTOR,
+                * SCALE_FACTOR_4D,
             -1.0,
             1.0,
         )
     }
 }
+
+/// 2-dimensional perlin noise, along with its gradient
+impl NoiseFnWithGradient<Point2<f64>> for Perlin {
+    fn get_with_gradient(&self, point: Point2<f64>) -> (f64, Vector2<f64>) {
+        #[inline(always)]
+        fn surflet(
+            perm_table: &PermutationTable,
+            corner: Point2<isize>,
+            distance: Vector2<f64>,
+        ) -> (f64, Vector2<f64>) {
+            let attn = 1.0 - math::dot2(distance, distance);
+            if attn > 0.0 {
+                let gradient = gradient::get2(perm_table.get2(corner));
+                let dot = math::dot2(distance, gradient);
+                let derivative = math::add2(
+                    math::mul2(gradient, attn.powi(4)),
+                    math::mul2(distance, -8.0 * attn.powi(3) * dot),
+                );
+                (attn.powi(4) * dot, derivative)
+            } else {
+                (0.0, [0.0; 2])
+            }
+        }
+
+        let floored = math::map2(point, f64::floor);
+        let period = [self.period[0], self.period[1]];
+        let near_corner = math::to_isize2(floored);
+        let near_distance = math::sub2(point, floored);
+
+        let mut value = 0.0;
+        let mut derivative = [0.0; 2];
+
+        // Visit the corners in the same order as `get`, so the values match exactly.
+        for i in 0..4 {
+            let offset = [i & 1, (i >> 1) & 1];
+            let (surflet_value, surflet_derivative) = surflet(
+                &self.perm_table,
+                math::wrap2(math::add2(near_corner, offset), period),
+                math::sub2(near_distance, math::to_f64_2(offset)),
+            );
+            value += surflet_value;
+            derivative = math::add2(derivative, surflet_derivative);
+        }
+
+        let value = value * SCALE_FACTOR_2D;
+        if value.abs() > 1.0 {
+            // The output is clamped here, so it's flat.
+            (math::clamp(value, -1.0, 1.0), [0.0; 2])
+        } else {
+            (value, math::mul2(derivative, SCALE_FACTOR_2D))
+        }
+    }
+}
+
+/// 3-dimensional perlin noise, along with its gradient
+impl NoiseFnWithGradient<Point3<f64>> for Perlin {
+    fn get_with_gradient(&self, point: Point3<f64>) -> (f64, Vector3<f64>) {
+        #[inline(always)]
+        fn surflet(
+            perm_table: &PermutationTable,
+            corner: Point3<isize>,
+            distance: Vector3<f64>,
+        ) -> (f64, Vector3<f64>) {
+            let attn = 1.0 - math::dot3(distance, distance);
+            if attn > 0.0 {
+                let gradient = gradient::get3(perm_table.get3(corner));
+                let dot = math::dot3(distance, gradient);
+                let derivative = math::add3(
+                    math::mul3(gradient, attn.powi(4)),
+                    math::mul3(distance, -8.0 * attn.powi(3) * dot),
+                );
+                (attn.powi(4) * dot, derivative)
+            } else {
+                (0.0, [0.0; 3])
+            }
+        }
+
+        let floored = math::map3(point, f64::floor);
+        let period = [self.period[0], self.period[1], self.period[2]];
+        let near_corner = math::to_isize3(floored);
+        let near_distance = math::sub3(point, floored);
+
+        let mut value = 0.0;
+        let mut derivative = [0.0; 3];
+
+        // Visit the corners in the same order as `get`, so the values match exactly.
+        for i in 0..8 {
+            let offset = [i & 1, (i >> 1) & 1, (i >> 2) & 1];
+            let (surflet_value, surflet_derivative) = surflet(
+                &self.perm_table,
+                math::wrap3(math::add3(near_corner, offset), period),
+                math::sub3(near_distance, math::to_f64_3(offset)),
+            );
+            value += surflet_value;
+            derivative = math::add3(derivative, surflet_derivative);
+        }
+
+        let value = value * SCALE_FACTOR_3D;
+        if value.abs() > 1.0 {
+            // The output is clamped here, so it's flat.
+            (math::clamp(value, -1.0, 1.0), [0.0; 3])
+        } else {
+            (value, math::mul3(derivative, SCALE_FACTOR_3D))
+        }
+    }
+}
+
+/// 4-dimensional perlin noise, along with its gradient
+impl NoiseFnWithGradient<Point4<f64>> for Perlin {
+    fn get_with_gradient(&self, point: Point4<f64>) -> (f64, Vector4<f64>) {
+        #[inline(always)]
+        fn surflet(
+            perm_table: &PermutationTable,
+            corner: Point4<isize>,
+            distance: Vector4<f64>,
+        ) -> (f64, Vector4<f64>) {
+            let attn = 1.0 - math::dot4(distance, distance);
+            if attn > 0.0 {
+                let gradient = gradient::get4(perm_table.get4(corner));
+                let dot = math::dot4(distance, gradient);
+                let derivative = math::add4(
+                    math::mul4(gradient, attn.powi(4)),
+                    math::mul4(distance, -8.0 * attn.powi(3) * dot),
+                );
+                (attn.powi(4) * dot, derivative)
+            } else {
+                (0.0, [0.0; 4])
+            }
+        }
+
+        let floored = math::map4(point, f64::floor);
+        let period = self.period;
+        let near_corner = math::to_isize4(floored);
+        let near_distance = math::sub4(point, floored);
+
+        let mut value = 0.0;
+        let mut derivative = [0.0; 4];
+
+        // Visit the corners in the same order as `get`, so the values match exactly.
+        for i in 0..16 {
+            let offset = [i & 1, (i >> 1) & 1, (i >> 2) & 1, (i >> 3) & 1];
+            let (surflet_value, surflet_derivative) = surflet(
+                &self.perm_table,
+                math::wrap4(math::add4(near_corner, offset), period),
+                math::sub4(near_distance, math::to_f64_4(offset)),
+            );
+            value += surflet_value;
+            derivative = math::add4(derivative, surflet_derivative);
+        }
+
+        let value = value * SCALE_FACTOR_4D;
+        if value.abs() > 1.0 {
+            // The output is clamped here, so it's flat.
+            (math::clamp(value, -1.0, 1.0), [0.0; 4])
+        } else {
+            (value, math::mul4(derivative, SCALE_FACTOR_4D))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noise_fns::tests::{assert_gradients_match, assert_tiles};
+
+    #[test]
+    fn test_gradients_match_finite_differences() {
+        assert_gradients_match(&Perlin::new());
+        assert_gradients_match(&Perlin::new().set_period([3; 4]));
+        assert_gradients_match(&Perlin::new().set_lattice_hash(LatticeHash::Integer));
+    }
+
+    #[test]
+    fn test_periodic_noise_tiles() {
+        let perlin = Perlin::new().set_seed(1);
+        assert_tiles(&perlin.set_period([3; 4]), [3.0; 2]);
+        assert_tiles(&perlin.set_period([4; 4]), [4.0; 3]);
+        assert_tiles(&perlin.set_period([2; 4]), [2.0; 4]);
+    }
+}
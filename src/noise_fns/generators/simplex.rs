@@ -0,0 +1,476 @@
+//! Ken Perlin's simplex noise, following Stefan Gustavson's reference
+//! implementation, as detailed here:
+//! <http://staffwww.itn.liu.se/~stegu/simplexnoise/simplexnoise.pdf>
+//!
+//! The patent covering simplex noise in 3 and more dimensions expired in
+//! January 2022.
+
+use math;
+use math::{Point2, Point3, Point4, Vector2, Vector3, Vector4};
+use noise_fns::{NoiseFn, Seedable};
+use permutationtable::{LatticeHash, LatticeHashable, PermutationTable};
+use std::ops::Add;
+
+const SKEW_FACTOR_2D: f64 = 0.366_025_403_784_438_6; // (sqrt(2 + 1) - 1) / 2
+const UNSKEW_FACTOR_2D: f64 = 0.211_324_865_405_187_1; // (3 - sqrt(2 + 1)) / 6
+const SKEW_FACTOR_3D: f64 = 1.0 / 3.0; // (sqrt(3 + 1) - 1) / 3
+const UNSKEW_FACTOR_3D: f64 = 1.0 / 6.0; // (4 - sqrt(3 + 1)) / 12
+const SKEW_FACTOR_4D: f64 = 0.309_016_994_374_947_4; // (sqrt(4 + 1) - 1) / 4
+const UNSKEW_FACTOR_4D: f64 = 0.138_196_601_125_010_5; // (5 - sqrt(4 + 1)) / 20
+
+// Squared radius of the contribution of each simplex corner, as in the
+// reference implementation.
+const KERNEL_RADIUS_SQUARED_2D: f64 = 0.5;
+const KERNEL_RADIUS_SQUARED_3D: f64 = 0.6;
+const KERNEL_RADIUS_SQUARED_4D: f64 = 0.6;
+
+// Scales the sum of the corner contributions to roughly the -1.0 to 1.0 range.
+// These are the reference implementation's factors, which go with its
+// unnormalized gradients.
+const NORM_CONSTANT_2D: f64 = 70.0;
+const NORM_CONSTANT_3D: f64 = 32.0;
+const NORM_CONSTANT_4D: f64 = 27.0;
+
+/// Noise function that outputs 2/3/4-dimensional Simplex noise.
+///
+/// The lattice, the corner traversal, the kernel, the gradients and the
+/// scaling all match the reference implementation. The gradients are picked by
+/// hashing the corners with the `PermutationTable` the way the reference hashes
+/// them with its `perm` array, so a table holding the reference permutation
+/// gives the reference's output. Other seeds shuffle the table, and the
+/// `LatticeHash::Integer` hash replaces it.
+#[derive(Clone, Copy, Debug)]
+pub struct Simplex {
+    seed: u32,
+    perm_table: PermutationTable,
+}
+
+impl Simplex {
+    pub const DEFAULT_SEED: u32 = 0;
+
+    pub fn new() -> Self {
+        Self {
+            seed: Self::DEFAULT_SEED,
+            perm_table: PermutationTable::new(Self::DEFAULT_SEED),
+        }
+    }
+}
+
+impl Default for Simplex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Seedable for Simplex {
+    /// Sets the seed value for Simplex noise
+    fn set_seed(self, seed: u32) -> Self {
+        // If the new seed is the same as the current seed, just return self.
+        if self.seed == seed {
+            return self;
+        }
+
+        // Otherwise, regenerate the permutation table based on the new seed.
+        Self {
+            seed,
+            perm_table: PermutationTable::with_hash(seed, self.perm_table.hash()),
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+impl LatticeHashable for Simplex {
+    fn set_lattice_hash(self, hash: LatticeHash) -> Self {
+        Self {
+            perm_table: PermutationTable::with_hash(self.seed, hash),
+            ..self
+        }
+    }
+
+    fn lattice_hash(&self) -> LatticeHash {
+        self.perm_table.hash()
+    }
+}
+
+// The reference gradients, the midpoints of the 12 edges of a cube.
+#[rustfmt::skip]
+fn grad3(index: usize) -> Vector3<f64> {
+    match index % 12 {
+        0  => [ 1.0,  1.0,  0.0],
+        1  => [-1.0,  1.0,  0.0],
+        2  => [ 1.0, -1.0,  0.0],
+        3  => [-1.0, -1.0,  0.0],
+        4  => [ 1.0,  0.0,  1.0],
+        5  => [-1.0,  0.0,  1.0],
+        6  => [ 1.0,  0.0, -1.0],
+        7  => [-1.0,  0.0, -1.0],
+        8  => [ 0.0,  1.0,  1.0],
+        9  => [ 0.0, -1.0,  1.0],
+        10 => [ 0.0,  1.0, -1.0],
+        11 => [ 0.0, -1.0, -1.0],
+        _ => unreachable!(),
+    }
+}
+
+// The reference uses the first two coordinates of the 3D gradients in 2D.
+fn grad2(index: usize) -> Vector2<f64> {
+    let [x, y, _] = grad3(index);
+    [x, y]
+}
+
+// The reference gradients, the midpoints of the 32 edges of a hypercube.
+#[rustfmt::skip]
+fn grad4(index: usize) -> Vector4<f64> {
+    match index % 32 {
+        0  => [ 0.0,  1.0,  1.0,  1.0],
+        1  => [ 0.0,  1.0,  1.0, -1.0],
+        2  => [ 0.0,  1.0, -1.0,  1.0],
+        3  => [ 0.0,  1.0, -1.0, -1.0],
+        4  => [ 0.0, -1.0,  1.0,  1.0],
+        5  => [ 0.0, -1.0,  1.0, -1.0],
+        6  => [ 0.0, -1.0, -1.0,  1.0],
+        7  => [ 0.0, -1.0, -1.0, -1.0],
+        8  => [ 1.0,  0.0,  1.0,  1.0],
+        9  => [ 1.0,  0.0,  1.0, -1.0],
+        10 => [ 1.0,  0.0, -1.0,  1.0],
+        11 => [ 1.0,  0.0, -1.0, -1.0],
+        12 => [-1.0,  0.0,  1.0,  1.0],
+        13 => [-1.0,  0.0,  1.0, -1.0],
+        14 => [-1.0,  0.0, -1.0,  1.0],
+        15 => [-1.0,  0.0, -1.0, -1.0],
+        16 => [ 1.0,  1.0,  0.0,  1.0],
+        17 => [ 1.0,  1.0,  0.0, -1.0],
+        18 => [ 1.0, -1.0,  0.0,  1.0],
+        19 => [ 1.0, -1.0,  0.0, -1.0],
+        20 => [-1.0,  1.0,  0.0,  1.0],
+        21 => [-1.0,  1.0,  0.0, -1.0],
+        22 => [-1.0, -1.0,  0.0,  1.0],
+        23 => [-1.0, -1.0,  0.0, -1.0],
+        24 => [ 1.0,  1.0,  1.0,  0.0],
+        25 => [ 1.0,  1.0, -1.0,  0.0],
+        26 => [ 1.0, -1.0,  1.0,  0.0],
+        27 => [ 1.0, -1.0, -1.0,  0.0],
+        28 => [-1.0,  1.0,  1.0,  0.0],
+        29 => [-1.0,  1.0, -1.0,  0.0],
+        30 => [-1.0, -1.0,  1.0,  0.0],
+        31 => [-1.0, -1.0, -1.0,  0.0],
+        _ => unreachable!(),
+    }
+}
+
+/// 2-dimensional Simplex noise
+impl NoiseFn<Point2<f64>> for Simplex {
+    fn get(&self, point: Point2<f64>) -> f64 {
+        #[inline(always)]
+        fn surflet(
+            perm_table: &PermutationTable,
+            corner: Point2<isize>,
+            distance: Vector2<f64>,
+        ) -> f64 {
+            let attn = KERNEL_RADIUS_SQUARED_2D - math::dot2(distance, distance);
+            if attn > 0.0 {
+                attn.powi(4) * math::dot2(distance, grad2(perm_table.get_additive(&corner)))
+            } else {
+                0.0
+            }
+        }
+
+        // Skew the input space to find which cell of simplices we're in.
+        let skew = math::fold2(point, Add::add) * SKEW_FACTOR_2D;
+        let cell = math::map2(point, |v| (v + skew).floor());
+        let corner = math::to_isize2(cell);
+
+        // Unskew the cell origin back to find the distance from it.
+        let unskew = math::fold2(cell, Add::add) * UNSKEW_FACTOR_2D;
+        let distance0 = math::sub2(point, math::map2(cell, |v| v - unskew));
+
+        // The cell is split into two triangles along its diagonal. Whichever
+        // coordinate is larger decides which one the point is in.
+        let offset1 = if distance0[0] > distance0[1] {
+            [1, 0]
+        } else {
+            [0, 1]
+        };
+
+        let distance1 = math::map2(math::sub2(distance0, math::to_f64_2(offset1)), |v| {
+            v + UNSKEW_FACTOR_2D
+        });
+        let distance2 = math::map2(distance0, |v| v - 1.0 + 2.0 * UNSKEW_FACTOR_2D);
+
+        let f0 = surflet(&self.perm_table, corner, distance0);
+        let f1 = surflet(&self.perm_table, math::add2(corner, offset1), distance1);
+        let f2 = surflet(&self.perm_table, math::add2(corner, [1, 1]), distance2);
+
+        (f0 + f1 + f2) * NORM_CONSTANT_2D
+    }
+}
+
+/// 3-dimensional Simplex noise
+impl NoiseFn<Point3<f64>> for Simplex {
+    fn get(&self, point: Point3<f64>) -> f64 {
+        #[inline(always)]
+        fn surflet(
+            perm_table: &PermutationTable,
+            corner: Point3<isize>,
+            distance: Vector3<f64>,
+        ) -> f64 {
+            let attn = KERNEL_RADIUS_SQUARED_3D - math::dot3(distance, distance);
+            if attn > 0.0 {
+                attn.powi(4) * math::dot3(distance, grad3(perm_table.get_additive(&corner)))
+            } else {
+                0.0
+            }
+        }
+
+        // Skew the input space to find which cell of simplices we're in.
+        let skew = math::fold3(point, Add::add) * SKEW_FACTOR_3D;
+        let cell = math::map3(point, |v| (v + skew).floor());
+        let corner = math::to_isize3(cell);
+
+        // Unskew the cell origin back to find the distance from it.
+        let unskew = math::fold3(cell, Add::add) * UNSKEW_FACTOR_3D;
+        let distance0 = math::sub3(point, math::map3(cell, |v| v - unskew));
+
+        // The cell is split into six tetrahedra. Ranking the coordinates tells
+        // which one the point is in: the path from the first corner to the
+        // last steps along the axes in order of decreasing coordinate.
+        let mut rank = [0; 3];
+        if distance0[0] > distance0[1] {
+            rank[0] += 1;
+        } else {
+            rank[1] += 1;
+        }
+        if distance0[0] > distance0[2] {
+            rank[0] += 1;
+        } else {
+            rank[2] += 1;
+        }
+        if distance0[1] > distance0[2] {
+            rank[1] += 1;
+        } else {
+            rank[2] += 1;
+        }
+
+        let offset1 = math::map3(rank, |r| (r >= 2) as isize);
+        let offset2 = math::map3(rank, |r| (r >= 1) as isize);
+
+        let distance1 = math::map3(math::sub3(distance0, math::to_f64_3(offset1)), |v| {
+            v + UNSKEW_FACTOR_3D
+        });
+        let distance2 = math::map3(math::sub3(distance0, math::to_f64_3(offset2)), |v| {
+            v + 2.0 * UNSKEW_FACTOR_3D
+        });
+        let distance3 = math::map3(distance0, |v| v - 1.0 + 3.0 * UNSKEW_FACTOR_3D);
+
+        let f0 = surflet(&self.perm_table, corner, distance0);
+        let f1 = surflet(&self.perm_table, math::add3(corner, offset1), distance1);
+        let f2 = surflet(&self.perm_table, math::add3(corner, offset2), distance2);
+        let f3 = surflet(&self.perm_table, math::add3(corner, [1, 1, 1]), distance3);
+
+        (f0 + f1 + f2 + f3) * NORM_CONSTANT_3D
+    }
+}
+
+/// 4-dimensional Simplex noise
+impl NoiseFn<Point4<f64>> for Simplex {
+    fn get(&self, point: Point4<f64>) -> f64 {
+        #[inline(always)]
+        fn surflet(
+            perm_table: &PermutationTable,
+            corner: Point4<isize>,
+            distance: Vector4<f64>,
+        ) -> f64 {
+            let attn = KERNEL_RADIUS_SQUARED_4D - math::dot4(distance, distance);
+            if attn > 0.0 {
+                attn.powi(4) * math::dot4(distance, grad4(perm_table.get_additive(&corner)))
+            } else {
+                0.0
+            }
+        }
+
+        // Skew the input space to find which cell of simplices we're in.
+        let skew = math::fold4(point, Add::add) * SKEW_FACTOR_4D;
+        let cell = math::map4(point, |v| (v + skew).floor());
+        let corner = math::to_isize4(cell);
+
+        // Unskew the cell origin back to find the distance from it.
+        let unskew = math::fold4(cell, Add::add) * UNSKEW_FACTOR_4D;
+        let distance0 = math::sub4(point, math::map4(cell, |v| v - unskew));
+
+        // The cell is split into 24 simplices. Ranking the coordinates tells
+        // which one the point is in: the path from the first corner to the
+        // last steps along the axes in order of decreasing coordinate.
+        let mut rank = [0; 4];
+        if distance0[0] > distance0[1] {
+            rank[0] += 1;
+        } else {
+            rank[1] += 1;
+        }
+        if distance0[0] > distance0[2] {
+            rank[0] += 1;
+        } else {
+            rank[2] += 1;
+        }
+        if distance0[0] > distance0[3] {
+            rank[0] += 1;
+        } else {
+            rank[3] += 1;
+        }
+        if distance0[1] > distance0[2] {
+            rank[1] += 1;
+        } else {
+            rank[2] += 1;
+        }
+        if distance0[1] > distance0[3] {
+            rank[1] += 1;
+        } else {
+            rank[3] += 1;
+        }
+        if distance0[2] > distance0[3] {
+            rank[2] += 1;
+        } else {
+            rank[3] += 1;
+        }
+
+        let offset1 = math::map4(rank, |r| (r >= 3) as isize);
+        let offset2 = math::map4(rank, |r| (r >= 2) as isize);
+        let offset3 = math::map4(rank, |r| (r >= 1) as isize);
+
+        let distance1 = math::map4(math::sub4(distance0, math::to_f64_4(offset1)), |v| {
+            v + UNSKEW_FACTOR_4D
+        });
+        let distance2 = math::map4(math::sub4(distance0, math::to_f64_4(offset2)), |v| {
+            v + 2.0 * UNSKEW_FACTOR_4D
+        });
+        let distance3 = math::map4(math::sub4(distance0, math::to_f64_4(offset3)), |v| {
+            v + 3.0 * UNSKEW_FACTOR_4D
+        });
+        let distance4 = math::map4(distance0, |v| v - 1.0 + 4.0 * UNSKEW_FACTOR_4D);
+
+        let f0 = surflet(&self.perm_table, corner, distance0);
+        let f1 = surflet(&self.perm_table, math::add4(corner, offset1), distance1);
+        let f2 = surflet(&self.perm_table, math::add4(corner, offset2), distance2);
+        let f3 = surflet(&self.perm_table, math::add4(corner, offset3), distance3);
+        let f4 = surflet(
+            &self.perm_table,
+            math::add4(corner, [1, 1, 1, 1]),
+            distance4,
+        );
+
+        (f0 + f1 + f2 + f3 + f4) * NORM_CONSTANT_4D
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_range() {
+        let simplex = Simplex::new().set_seed(1);
+        let mut max_value: f64 = 0.0;
+
+        for x in 0..64 {
+            for y in 0..64 {
+                let (x, y) = (x as f64 * 0.173 - 5.0, y as f64 * 0.191 - 5.0);
+                for &value in &[
+                    simplex.get([x, y]),
+                    simplex.get([x, y, x * 0.37 - y]),
+                    simplex.get([x, y, y * 0.53, x * 0.29 + y]),
+                ] {
+                    assert!((-1.0..=1.0).contains(&value), "{}", value);
+                    max_value = max_value.max(value.abs());
+                }
+            }
+        }
+
+        // The normalization shouldn't squash the output towards zero either.
+        assert!(max_value > 0.5);
+    }
+
+    #[test]
+    fn test_reference_values() {
+        // Ken Perlin's permutation, which the reference implementation uses.
+        #[rustfmt::skip]
+        const PERM: [u8; 256] = [
+            151, 160, 137, 91, 90, 15, 131, 13, 201, 95, 96, 53, 194, 233, 7, 225,
+            140, 36, 103, 30, 69, 142, 8, 99, 37, 240, 21, 10, 23, 190, 6, 148,
+            247, 120, 234, 75, 0, 26, 197, 62, 94, 252, 219, 203, 117, 35, 11, 32,
+            57, 177, 33, 88, 237, 149, 56, 87, 174, 20, 125, 136, 171, 168, 68, 175,
+            74, 165, 71, 134, 139, 48, 27, 166, 77, 146, 158, 231, 83, 111, 229, 122,
+            60, 211, 133, 230, 220, 105, 92, 41, 55, 46, 245, 40, 244, 102, 143, 54,
+            65, 25, 63, 161, 1, 216, 80, 73, 209, 76, 132, 187, 208, 89, 18, 169,
+            200, 196, 135, 130, 116, 188, 159, 86, 164, 100, 109, 198, 173, 186, 3, 64,
+            52, 217, 226, 250, 124, 123, 5, 202, 38, 147, 118, 126, 255, 82, 85, 212,
+            207, 206, 59, 227, 47, 16, 58, 17, 182, 189, 28, 42, 223, 183, 170, 213,
+            119, 248, 152, 2, 44, 154, 163, 70, 221, 153, 101, 155, 167, 43, 172, 9,
+            129, 22, 39, 253, 19, 98, 108, 110, 79, 113, 224, 232, 178, 185, 112, 104,
+            218, 246, 97, 228, 251, 34, 242, 193, 238, 210, 144, 12, 191, 179, 162, 241,
+            81, 51, 145, 235, 249, 14, 239, 107, 49, 192, 214, 31, 181, 199, 106, 157,
+            184, 84, 204, 176, 115, 121, 50, 45, 127, 4, 150, 254, 138, 236, 205, 93,
+            222, 114, 67, 29, 24, 72, 243, 141, 128, 195, 78, 66, 215, 61, 156, 180,
+        ];
+
+        // Output of the reference implementation at the same points.
+        let simplex = Simplex {
+            seed: 0,
+            perm_table: PermutationTable::from_values(PERM),
+        };
+        let values2 = [
+            ([0.3, 0.7], 0.255_220_633_420_134_8),
+            ([-1.7, 2.9], 0.025_222_867_720_045_766),
+            ([12.25, -4.5], -0.658_394_550_571_321_2),
+            ([100.1, 55.6], -0.355_518_788_180_508_7),
+        ];
+        let values3 = [
+            ([0.3, 0.7, -0.2], -0.323_612_658_041_152_25),
+            ([-1.7, 2.9, 5.3], -0.463_374_327_308_642_25),
+            ([12.25, -4.5, 0.125], 0.195_936_887_232_458_6),
+            ([100.1, 55.6, -31.7], -0.451_428_792_493_807_96),
+        ];
+        let values4 = [
+            ([0.3, 0.7, -0.2, 1.1], -0.032_372_304_854_882_995),
+            ([-1.7, 2.9, 5.3, -0.6], -0.208_987_834_764_608_42),
+            ([12.25, -4.5, 0.125, 7.75], -0.253_353_203_081_650_8),
+            ([100.1, 55.6, -31.7, 9.9], -0.071_817_113_261_680_57),
+        ];
+
+        for &(point, value) in &values2 {
+            assert!((simplex.get(point) - value).abs() < 1e-12);
+        }
+        for &(point, value) in &values3 {
+            assert!((simplex.get(point) - value).abs() < 1e-12);
+        }
+        for &(point, value) in &values4 {
+            assert!((simplex.get(point) - value).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_zero_at_corners() {
+        let simplex = Simplex::new();
+
+        // Each corner's contribution vanishes at the corner itself, and the
+        // other corners are too far away to reach it.
+        for x in -4..4 {
+            for y in -4..4 {
+                let (x, y) = (x as f64, y as f64);
+
+                let unskew = (x + y) * UNSKEW_FACTOR_2D;
+                assert!(simplex.get([x - unskew, y - unskew]).abs() < 1e-12);
+
+                let z = x - y;
+                let unskew = (x + y + z) * UNSKEW_FACTOR_3D;
+                assert!(simplex.get([x - unskew, y - unskew, z - unskew]).abs() < 1e-12);
+
+                let w = y * 2.0;
+                let unskew = (x + y + z + w) * UNSKEW_FACTOR_4D;
+                let corner = [x - unskew, y - unskew, z - unskew, w - unskew];
+                assert!(simplex.get(corner).abs() < 1e-12);
+            }
+        }
+    }
+}
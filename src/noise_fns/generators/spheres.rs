@@ -0,0 +1,79 @@
+use math::{Point2, Point3, Point4};
+use noise_fns::{NoiseFn, Seedable};
+
+/// Noise function that outputs concentric spheres.
+///
+/// This noise function outputs concentric spheres centered on the origin like
+/// the concentric rings of an onion. For 2D input the spheres are circles, and
+/// for 4D input they are hyperspheres.
+#[derive(Clone, Copy, Debug)]
+pub struct Spheres {
+    /// Frequency of the concentric objects.
+    pub frequency: f64,
+}
+
+impl Spheres {
+    pub const DEFAULT_FREQUENCY: f64 = 1.0;
+
+    pub fn new() -> Self {
+        Self {
+            frequency: Self::DEFAULT_FREQUENCY,
+        }
+    }
+
+    pub fn set_frequency(self, frequency: f64) -> Self {
+        Self { frequency }
+    }
+}
+
+impl Default for Spheres {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Seedable for Spheres {
+    /// There are no random values, so the seed is ignored and stays zero.
+    fn set_seed(self, _seed: u32) -> Self {
+        self
+    }
+
+    fn seed(&self) -> u32 {
+        0
+    }
+}
+
+impl NoiseFn<Point2<f64>> for Spheres {
+    fn get(&self, point: Point2<f64>) -> f64 {
+        calculate_spheres(&point, self.frequency)
+    }
+}
+
+impl NoiseFn<Point3<f64>> for Spheres {
+    fn get(&self, point: Point3<f64>) -> f64 {
+        calculate_spheres(&point, self.frequency)
+    }
+}
+
+impl NoiseFn<Point4<f64>> for Spheres {
+    fn get(&self, point: Point4<f64>) -> f64 {
+        calculate_spheres(&point, self.frequency)
+    }
+}
+
+fn calculate_spheres(point: &[f64], frequency: f64) -> f64 {
+    // Scale the inputs by the frequency, and calculate the distance of the
+    // point from the origin.
+    let dist_from_center = point
+        .iter()
+        .map(|&x| (x * frequency).powi(2))
+        .fold(0.0, |acc, x| acc + x)
+        .sqrt();
+
+    let dist_from_smaller_sphere = dist_from_center - dist_from_center.floor();
+    let dist_from_larger_sphere = 1.0 - dist_from_smaller_sphere;
+    let nearest_dist = dist_from_smaller_sphere.min(dist_from_larger_sphere);
+
+    // Shift the result to be in the -1.0 to +1.0 range.
+    1.0 - (nearest_dist * 4.0)
+}
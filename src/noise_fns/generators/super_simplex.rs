@@ -1,16 +1,26 @@
-use math::{Point2, Point3};
-use noise_fns::{NoiseFn, Seedable};
-use permutationtable::PermutationTable;
+use math::{Point2, Point3, Point4, Vector2, Vector3, Vector4};
+use noise_fns::{NoiseFn, NoiseFnWithGradient, Seedable};
+use permutationtable::{LatticeHash, LatticeHashable, PermutationTable};
 use std::ops::Add;
 use {gradient, math};
 
 const TO_REAL_CONSTANT_2D: f64 = -0.211_324_865_405_187; // (1 / sqrt(2 + 1) - 1) / 2
 const TO_SIMPLEX_CONSTANT_2D: f64 = 0.366_025_403_784_439; // (sqrt(2 + 1) - 1) / 2
 const TO_SIMPLEX_CONSTANT_3D: f64 = -2.0 / 3.0;
+const TO_REAL_CONSTANT_4D: f64 = -0.138_196_601_125_011; // (1 / sqrt(4 + 1) - 1) / 4
+const TO_SIMPLEX_CONSTANT_4D: f64 = 0.309_016_994_374_947; // (sqrt(4 + 1) - 1) / 4
+
+// Squared radius of each lattice point's contribution. In 4D this is the squared distance between
+// neighbouring lattice points, the same choice as the 2D and 3D kernels.
+const KERNEL_RADIUS_SQUARED_2D: f64 = 2.0 / 3.0;
+const KERNEL_RADIUS_SQUARED_3D: f64 = 0.75;
+const KERNEL_RADIUS_SQUARED_4D: f64 = 0.8;
 
 // Determined using the Mathematica code listed in the super_simplex example and find_maximum_super_simplex.nb
+// The 4D constant is found the same way, by the Newton iteration in the super_simplex example itself.
 const NORM_CONSTANT_2D: f64 = 1.0 / 0.054_282_952_886_616_23;
 const NORM_CONSTANT_3D: f64 = 1.0 / 0.086_766_400_165_536_9;
+const NORM_CONSTANT_4D: f64 = 1.0 / 0.115_917_763_953_590_02;
 
 // Points taken into account for 2D:
 //              (-1,  0)
@@ -87,7 +97,19 @@ const LATTICE_LOOKUP_3D: [[i8; 3]; 4 * 16] =
      [0, 0, 0],[0, 1, 1],[1, 0, 1],[1, 1, 0],
      [1, 1, 1],[0, 1, 1],[1, 0, 1],[1, 1, 0]];
 
-/// Noise function that outputs 2/3-dimensional Super Simplex noise.
+// Points taken into account for 4D, for the simplex where x >= y >= z >= w in simplex space. These
+// are all the lattice points closer than the kernel radius to some point of that simplex. The other
+// 23 simplices of the hypercube are covered by permuting the axes of these points.
+#[rustfmt::skip]
+const LATTICE_LOOKUP_4D: [[i8; 4]; 30] =
+    [[0, 0, 0, 0],[1, 0, 0, 0],[1, 1, 0, 0],[1, 1, 1, 0],[1, 1, 1, 1],
+     [2, 1, 1, 1],[0, 0, 0, -1],[0, 1, 0, 0],[1, 0, 1, 0],[1, 1, 0, 1],
+     [0, 0, -1, -1],[2, 2, 1, 1],[0, 0, -1, 0],[1, 0, 0, -1],[1, 2, 1, 1],
+     [2, 1, 1, 0],[0, 0, 1, 0],[0, 1, 1, 0],[1, 0, 0, 1],[1, 0, 1, 1],
+     [2, 2, 2, 1],[0, -1, -1, -1],[1, 1, 0, -1],[1, 1, 2, 1],[0, -1, 0, 0],
+     [1, 0, -1, 0],[1, 2, 1, 0],[2, 1, 0, 0],[0, 0, 0, 1],[0, 1, 1, 1]];
+
+/// Noise function that outputs 2/3/4-dimensional Super Simplex noise.
 #[derive(Clone, Copy, Debug)]
 pub struct SuperSimplex {
     seed: u32,
@@ -122,7 +144,7 @@ impl Seedable for SuperSimplex {
         // Otherwise, regenerate the permutation table based on the new seed.
         Self {
             seed,
-            perm_table: PermutationTable::new(seed),
+            perm_table: PermutationTable::with_hash(seed, self.perm_table.hash()),
         }
     }
 
@@ -131,43 +153,31 @@ impl Seedable for SuperSimplex {
     }
 }
 
+impl LatticeHashable for SuperSimplex {
+    fn set_lattice_hash(self, hash: LatticeHash) -> Self {
+        Self {
+            perm_table: PermutationTable::with_hash(self.seed, hash),
+            ..self
+        }
+    }
+
+    fn lattice_hash(&self) -> LatticeHash {
+        self.perm_table.hash()
+    }
+}
+
 /// 2-dimensional Super Simplex noise
 impl NoiseFn<Point2<f64>> for SuperSimplex {
     fn get(&self, point: Point2<f64>) -> f64 {
         let mut value = 0.0;
 
-        // Transform point from real space to simplex space
-        let to_simplex_offset = math::fold2(point, Add::add) * TO_SIMPLEX_CONSTANT_2D;
-        let simplex_point = math::map2(point, |v| v + to_simplex_offset);
-
-        // Get base point of simplex and barycentric coordinates in simplex space
-        let simplex_base_point = math::map2(simplex_point, f64::floor);
-        let simplex_base_point_i = math::to_isize2(simplex_base_point);
-        let simplex_rel_coords = math::sub2(simplex_point, simplex_base_point);
-
-        // Create index to lookup table from barycentric coordinates
-        let region_sum = math::fold2(simplex_rel_coords, Add::add).floor();
-        let index = ((region_sum >= 1.0) as usize) << 2
-            | ((simplex_rel_coords[0] - simplex_rel_coords[1] * 0.5 + 1.0 - region_sum * 0.5 >= 1.0)
-                as usize)
-                << 3
-            | ((simplex_rel_coords[1] - simplex_rel_coords[0] * 0.5 + 1.0 - region_sum * 0.5 >= 1.0)
-                as usize)
-                << 4;
-
-        // Transform barycentric coordinates to real space
-        let to_real_offset = math::fold2(simplex_rel_coords, Add::add) * TO_REAL_CONSTANT_2D;
-        let real_rel_coords = math::map2(simplex_rel_coords, |v| v + to_real_offset);
-
-        for lattice_lookup in &LATTICE_LOOKUP_2D[index..index + 4] {
-            let dpos = math::add2(real_rel_coords, math::cast2(lattice_lookup.1));
-            let attn = (2.0 / 3.0) - math::dot2(dpos, dpos);
+        for_each_lattice_point2(point, |lattice_point, dpos| {
+            let attn = KERNEL_RADIUS_SQUARED_2D - math::dot2(dpos, dpos);
             if attn > 0.0 {
-                let lattice_point = math::add2(simplex_base_point_i, math::cast2(lattice_lookup.0));
                 let gradient = gradient::get2(self.perm_table.get2(lattice_point));
                 value += attn.powi(4) * math::dot2(gradient, dpos);
             }
-        }
+        });
 
         value * NORM_CONSTANT_2D
     }
@@ -178,74 +188,328 @@ impl NoiseFn<Point3<f64>> for SuperSimplex {
     fn get(&self, point: Point3<f64>) -> f64 {
         let mut value = 0.0;
 
-        // Transform point from real space to simplex space
-        let to_simplex_offset = math::fold3(point, Add::add) * TO_SIMPLEX_CONSTANT_3D;
-        let simplex_point = math::map3(point, |v| -(v + to_simplex_offset));
-        let second_simplex_point = math::map3(simplex_point, |v| v + 512.5);
-
-        // Get base point of simplex and barycentric coordinates in simplex space
-        let simplex_base_point = math::map3(simplex_point, f64::floor);
-        let simplex_base_point_i = math::to_isize3(simplex_base_point);
-        let simplex_rel_coords = math::sub3(simplex_point, simplex_base_point);
-        let second_simplex_base_point = math::map3(second_simplex_point, f64::floor);
-        let second_simplex_base_point_i = math::to_isize3(second_simplex_base_point);
-        let second_simplex_rel_coords = math::sub3(second_simplex_point, second_simplex_base_point);
-
-        // Create indices to lookup table from barycentric coordinates
-        let index = ((simplex_rel_coords[0] + simplex_rel_coords[1] + simplex_rel_coords[2] >= 1.5)
-            as usize)
-            << 2
-            | ((-simplex_rel_coords[0] + simplex_rel_coords[1] + simplex_rel_coords[2] >= 0.5)
-                as usize)
-                << 3
-            | ((simplex_rel_coords[0] - simplex_rel_coords[1] + simplex_rel_coords[2] >= 0.5)
-                as usize)
-                << 4
-            | ((simplex_rel_coords[0] + simplex_rel_coords[1] - simplex_rel_coords[2] >= 0.5)
-                as usize)
-                << 5;
-        let second_index = ((second_simplex_rel_coords[0]
-            + second_simplex_rel_coords[1]
-            + second_simplex_rel_coords[2]
-            >= 1.5) as usize)
-            << 2
-            | ((-second_simplex_rel_coords[0]
-                + second_simplex_rel_coords[1]
-                + second_simplex_rel_coords[2]
-                >= 0.5) as usize)
-                << 3
-            | ((second_simplex_rel_coords[0] - second_simplex_rel_coords[1]
-                + second_simplex_rel_coords[2]
-                >= 0.5) as usize)
-                << 4
-            | ((second_simplex_rel_coords[0] + second_simplex_rel_coords[1]
-                - second_simplex_rel_coords[2]
-                >= 0.5) as usize)
-                << 5;
-
-        // Sum contributions from first lattice
-        for &lattice_lookup in &LATTICE_LOOKUP_3D[index..index + 4] {
-            let dpos = math::sub3(simplex_rel_coords, math::cast3(lattice_lookup));
-            let attn = 0.75 - math::dot3(dpos, dpos);
+        for_each_lattice_point3(point, |lattice_point, dpos| {
+            let attn = KERNEL_RADIUS_SQUARED_3D - math::dot3(dpos, dpos);
             if attn > 0.0 {
-                let lattice_point = math::add3(simplex_base_point_i, math::cast3(lattice_lookup));
                 let gradient = gradient::get3(self.perm_table.get3(lattice_point));
                 value += attn.powi(4) * math::dot3(gradient, dpos);
             }
-        }
+        });
 
-        // Sum contributions from second lattice
-        for &lattice_lookup in &LATTICE_LOOKUP_3D[second_index..second_index + 4] {
-            let dpos = math::sub3(second_simplex_rel_coords, math::cast3(lattice_lookup));
-            let attn = 0.75 - math::dot3(dpos, dpos);
+        value * NORM_CONSTANT_3D
+    }
+}
+
+/// 4-dimensional Super Simplex noise
+impl NoiseFn<Point4<f64>> for SuperSimplex {
+    fn get(&self, point: Point4<f64>) -> f64 {
+        let mut value = 0.0;
+
+        for_each_lattice_point4(point, |lattice_point, dpos| {
+            let attn = KERNEL_RADIUS_SQUARED_4D - math::dot4(dpos, dpos);
+            if attn > 0.0 {
+                let gradient = gradient::get4(self.perm_table.get4(lattice_point));
+                value += attn.powi(4) * math::dot4(gradient, dpos);
+            }
+        });
+
+        value * NORM_CONSTANT_4D
+    }
+}
+
+/// 2-dimensional Super Simplex noise, along with its gradient
+impl NoiseFnWithGradient<Point2<f64>> for SuperSimplex {
+    fn get_with_gradient(&self, point: Point2<f64>) -> (f64, Vector2<f64>) {
+        let mut value = 0.0;
+        let mut derivative = [0.0; 2];
+
+        for_each_lattice_point2(point, |lattice_point, dpos| {
+            let attn = KERNEL_RADIUS_SQUARED_2D - math::dot2(dpos, dpos);
+            if attn > 0.0 {
+                let gradient = gradient::get2(self.perm_table.get2(lattice_point));
+                let dot = math::dot2(gradient, dpos);
+                value += attn.powi(4) * dot;
+                derivative = math::add2(
+                    derivative,
+                    math::add2(
+                        math::mul2(gradient, attn.powi(4)),
+                        math::mul2(dpos, -8.0 * attn.powi(3) * dot),
+                    ),
+                );
+            }
+        });
+
+        (
+            value * NORM_CONSTANT_2D,
+            math::mul2(derivative, NORM_CONSTANT_2D),
+        )
+    }
+}
+
+/// 3-dimensional Super Simplex noise, along with its gradient
+impl NoiseFnWithGradient<Point3<f64>> for SuperSimplex {
+    fn get_with_gradient(&self, point: Point3<f64>) -> (f64, Vector3<f64>) {
+        let mut value = 0.0;
+        let mut derivative = [0.0; 3];
+
+        for_each_lattice_point3(point, |lattice_point, dpos| {
+            let attn = KERNEL_RADIUS_SQUARED_3D - math::dot3(dpos, dpos);
             if attn > 0.0 {
-                let lattice_point =
-                    math::add3(second_simplex_base_point_i, math::cast3(lattice_lookup));
                 let gradient = gradient::get3(self.perm_table.get3(lattice_point));
-                value += attn.powi(4) * math::dot3(gradient, dpos);
+                let dot = math::dot3(gradient, dpos);
+                value += attn.powi(4) * dot;
+                derivative = math::add3(
+                    derivative,
+                    math::add3(
+                        math::mul3(gradient, attn.powi(4)),
+                        math::mul3(dpos, -8.0 * attn.powi(3) * dot),
+                    ),
+                );
+            }
+        });
+
+        // The derivative is in simplex space. The transform from real space is symmetric, so
+        // applying it again takes the derivative back to real space.
+        let to_simplex_offset = math::fold3(derivative, Add::add) * TO_SIMPLEX_CONSTANT_3D;
+        let derivative = math::map3(derivative, |v| -(v + to_simplex_offset));
+
+        (
+            value * NORM_CONSTANT_3D,
+            math::mul3(derivative, NORM_CONSTANT_3D),
+        )
+    }
+}
+
+/// 4-dimensional Super Simplex noise, along with its gradient
+impl NoiseFnWithGradient<Point4<f64>> for SuperSimplex {
+    fn get_with_gradient(&self, point: Point4<f64>) -> (f64, Vector4<f64>) {
+        let mut value = 0.0;
+        let mut derivative = [0.0; 4];
+
+        for_each_lattice_point4(point, |lattice_point, dpos| {
+            let attn = KERNEL_RADIUS_SQUARED_4D - math::dot4(dpos, dpos);
+            if attn > 0.0 {
+                let gradient = gradient::get4(self.perm_table.get4(lattice_point));
+                let dot = math::dot4(gradient, dpos);
+                value += attn.powi(4) * dot;
+                derivative = math::add4(
+                    derivative,
+                    math::add4(
+                        math::mul4(gradient, attn.powi(4)),
+                        math::mul4(dpos, -8.0 * attn.powi(3) * dot),
+                    ),
+                );
+            }
+        });
+
+        (
+            value * NORM_CONSTANT_4D,
+            math::mul4(derivative, NORM_CONSTANT_4D),
+        )
+    }
+}
+
+/// Calls `f` with each 2-dimensional lattice point that can contribute to the point, along with
+/// the position of the point relative to the lattice point.
+#[inline(always)]
+fn for_each_lattice_point2<F>(point: Point2<f64>, mut f: F)
+where
+    F: FnMut(Point2<isize>, Vector2<f64>),
+{
+    // Transform point from real space to simplex space
+    let to_simplex_offset = math::fold2(point, Add::add) * TO_SIMPLEX_CONSTANT_2D;
+    let simplex_point = math::map2(point, |v| v + to_simplex_offset);
+
+    // Get base point of simplex and barycentric coordinates in simplex space
+    let simplex_base_point = math::map2(simplex_point, f64::floor);
+    let simplex_base_point_i = math::to_isize2(simplex_base_point);
+    let simplex_rel_coords = math::sub2(simplex_point, simplex_base_point);
+
+    // Create index to lookup table from barycentric coordinates
+    let region_sum = math::fold2(simplex_rel_coords, Add::add).floor();
+    let index = ((region_sum >= 1.0) as usize) << 2
+        | ((simplex_rel_coords[0] - simplex_rel_coords[1] * 0.5 + 1.0 - region_sum * 0.5 >= 1.0)
+            as usize)
+            << 3
+        | ((simplex_rel_coords[1] - simplex_rel_coords[0] * 0.5 + 1.0 - region_sum * 0.5 >= 1.0)
+            as usize)
+            << 4;
+
+    // Transform barycentric coordinates to real space
+    let to_real_offset = math::fold2(simplex_rel_coords, Add::add) * TO_REAL_CONSTANT_2D;
+    let real_rel_coords = math::map2(simplex_rel_coords, |v| v + to_real_offset);
+
+    for lattice_lookup in &LATTICE_LOOKUP_2D[index..index + 4] {
+        let lattice_point = math::add2(simplex_base_point_i, math::cast2(lattice_lookup.0));
+        let dpos = math::add2(real_rel_coords, math::cast2(lattice_lookup.1));
+        f(lattice_point, dpos);
+    }
+}
+
+/// Calls `f` with each 3-dimensional lattice point that can contribute to the point, along with
+/// the position of the point relative to the lattice point in simplex space.
+#[inline(always)]
+fn for_each_lattice_point3<F>(point: Point3<f64>, mut f: F)
+where
+    F: FnMut(Point3<isize>, Vector3<f64>),
+{
+    // Transform point from real space to simplex space
+    let to_simplex_offset = math::fold3(point, Add::add) * TO_SIMPLEX_CONSTANT_3D;
+    let simplex_point = math::map3(point, |v| -(v + to_simplex_offset));
+    let second_simplex_point = math::map3(simplex_point, |v| v + 512.5);
+
+    // Get base point of simplex and barycentric coordinates in simplex space
+    let simplex_base_point = math::map3(simplex_point, f64::floor);
+    let simplex_base_point_i = math::to_isize3(simplex_base_point);
+    let simplex_rel_coords = math::sub3(simplex_point, simplex_base_point);
+    let second_simplex_base_point = math::map3(second_simplex_point, f64::floor);
+    let second_simplex_base_point_i = math::to_isize3(second_simplex_base_point);
+    let second_simplex_rel_coords = math::sub3(second_simplex_point, second_simplex_base_point);
+
+    // Create indices to lookup table from barycentric coordinates
+    let index = ((simplex_rel_coords[0] + simplex_rel_coords[1] + simplex_rel_coords[2] >= 1.5)
+        as usize)
+        << 2
+        | ((-simplex_rel_coords[0] + simplex_rel_coords[1] + simplex_rel_coords[2] >= 0.5)
+            as usize)
+            << 3
+        | ((simplex_rel_coords[0] - simplex_rel_coords[1] + simplex_rel_coords[2] >= 0.5) as usize)
+            << 4
+        | ((simplex_rel_coords[0] + simplex_rel_coords[1] - simplex_rel_coords[2] >= 0.5) as usize)
+            << 5;
+    let second_index = ((second_simplex_rel_coords[0]
+        + second_simplex_rel_coords[1]
+        + second_simplex_rel_coords[2]
+        >= 1.5) as usize)
+        << 2
+        | ((-second_simplex_rel_coords[0]
+            + second_simplex_rel_coords[1]
+            + second_simplex_rel_coords[2]
+            >= 0.5) as usize)
+            << 3
+        | ((second_simplex_rel_coords[0] - second_simplex_rel_coords[1]
+            + second_simplex_rel_coords[2]
+            >= 0.5) as usize)
+            << 4
+        | ((second_simplex_rel_coords[0] + second_simplex_rel_coords[1]
+            - second_simplex_rel_coords[2]
+            >= 0.5) as usize)
+            << 5;
+
+    // Visit the first lattice
+    for &lattice_lookup in &LATTICE_LOOKUP_3D[index..index + 4] {
+        let lattice_point = math::add3(simplex_base_point_i, math::cast3(lattice_lookup));
+        let dpos = math::sub3(simplex_rel_coords, math::cast3(lattice_lookup));
+        f(lattice_point, dpos);
+    }
+
+    // Visit the second lattice
+    for &lattice_lookup in &LATTICE_LOOKUP_3D[second_index..second_index + 4] {
+        let lattice_point = math::add3(second_simplex_base_point_i, math::cast3(lattice_lookup));
+        let dpos = math::sub3(second_simplex_rel_coords, math::cast3(lattice_lookup));
+        f(lattice_point, dpos);
+    }
+}
+
+/// Calls `f` with each 4-dimensional lattice point that can contribute to the point, along with
+/// the position of the point relative to the lattice point.
+#[inline(always)]
+fn for_each_lattice_point4<F>(point: Point4<f64>, mut f: F)
+where
+    F: FnMut(Point4<isize>, Vector4<f64>),
+{
+    // Transform point from real space to simplex space
+    let to_simplex_offset = math::fold4(point, Add::add) * TO_SIMPLEX_CONSTANT_4D;
+    let simplex_point = math::map4(point, |v| v + to_simplex_offset);
+
+    // Get base point of simplex and barycentric coordinates in simplex space
+    let simplex_base_point = math::map4(simplex_point, f64::floor);
+    let simplex_base_point_i = math::to_isize4(simplex_base_point);
+    let simplex_rel_coords = math::sub4(simplex_point, simplex_base_point);
+
+    // Rank the barycentric coordinates to find which simplex of the hypercube we're in. The
+    // axis with rank 3 has the largest coordinate, and so on down to rank 0.
+    let mut rank = [0usize; 4];
+    for i in 0..4 {
+        for j in (i + 1)..4 {
+            if simplex_rel_coords[i] >= simplex_rel_coords[j] {
+                rank[i] += 1;
+            } else {
+                rank[j] += 1;
             }
         }
+    }
 
-        value * NORM_CONSTANT_3D
+    // Transform barycentric coordinates to real space
+    let to_real_offset = math::fold4(simplex_rel_coords, Add::add) * TO_REAL_CONSTANT_4D;
+    let real_rel_coords = math::map4(simplex_rel_coords, |v| v + to_real_offset);
+
+    for lattice_lookup in LATTICE_LOOKUP_4D.iter() {
+        // Permute the lookup point from the x >= y >= z >= w simplex onto ours.
+        let lattice_offset = math::map4(rank, |r| lattice_lookup[3 - r]);
+        let lattice_offset_f = math::cast4::<i8, f64>(lattice_offset);
+        let to_real_offset = math::fold4(lattice_offset_f, Add::add) * TO_REAL_CONSTANT_4D;
+        let real_offset = math::map4(lattice_offset_f, |v| v + to_real_offset);
+
+        let lattice_point = math::add4(simplex_base_point_i, math::cast4(lattice_offset));
+        let dpos = math::sub4(real_rel_coords, real_offset);
+        f(lattice_point, dpos);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noise_fns::tests::assert_gradients_match;
+
+    #[test]
+    fn test_gradients_match_finite_differences() {
+        assert_gradients_match(&SuperSimplex::new().set_seed(2));
+        assert_gradients_match(&SuperSimplex::new().set_lattice_hash(LatticeHash::Integer));
+    }
+
+    #[test]
+    fn test_output_range_4d() {
+        let super_simplex = SuperSimplex::new();
+        let mut max_value: f64 = 0.0;
+
+        for x in 0..16 {
+            for y in 0..16 {
+                for z in 0..16 {
+                    for w in 0..16 {
+                        let point = [
+                            x as f64 * 0.37 - 3.0,
+                            y as f64 * 0.41 - 3.0,
+                            z as f64 * 0.29 - 2.0,
+                            w as f64 * 0.53 - 4.0,
+                        ];
+                        let value = super_simplex.get(point);
+
+                        assert!((-1.0..=1.0).contains(&value), "{} at {:?}", value, point);
+                        max_value = max_value.max(value.abs());
+                    }
+                }
+            }
+        }
+
+        // The normalization shouldn't squash the output towards zero either,
+        // so the samples should reach most of the range.
+        assert!(max_value > 0.85, "{}", max_value);
+    }
+
+    #[test]
+    fn test_output_range_seeded() {
+        let super_simplex = SuperSimplex::new().set_seed(1);
+
+        for i in 0..4096 {
+            let t = i as f64 * 0.113;
+            let value2 = super_simplex.get([t, -t * 0.7]);
+            let value3 = super_simplex.get([t, -t * 0.7, t * 0.3]);
+            let value4 = super_simplex.get([t, -t * 0.7, t * 0.3, -t * 1.9]);
+
+            assert!((-1.0..=1.0).contains(&value2));
+            assert!((-1.0..=1.0).contains(&value3));
+            assert!((-1.0..=1.0).contains(&value4));
+        }
     }
 }
@@ -1,13 +1,14 @@
 use math;
 use math::{interpolate, Point2, Point3, Point4};
-use noise_fns::{NoiseFn, Seedable};
-use permutationtable::PermutationTable;
+use noise_fns::{NoiseFn, NoiseFnWithGradient, Seedable};
+use permutationtable::{LatticeHash, LatticeHashable, PermutationTable};
 
 /// Noise function that outputs 2/3/4-dimensional Value noise.
 #[derive(Clone, Copy, Debug)]
 pub struct Value {
     seed: u32,
     perm_table: PermutationTable,
+    period: [usize; 4],
 }
 
 impl Value {
@@ -17,8 +18,21 @@ impl Value {
         Self {
             seed: Self::DEFAULT_SEED,
             perm_table: PermutationTable::new(Self::DEFAULT_SEED),
+            period: [0; 4],
         }
     }
+
+    /// Sets the number of lattice cells after which the noise repeats along
+    /// each axis, so that it tiles exactly. A period of zero doesn't repeat.
+    ///
+    /// 2- and 3-dimensional noise use the first two and three periods.
+    pub fn set_period(self, period: [usize; 4]) -> Self {
+        Self { period, ..self }
+    }
+
+    pub fn period(&self) -> [usize; 4] {
+        self.period
+    }
 }
 
 impl Default for Value {
@@ -38,7 +52,8 @@ impl Seedable for Value {
         // Otherwise, regenerate the permutation table based on the new seed.
         Self {
             seed,
-            perm_table: PermutationTable::new(seed),
+            perm_table: PermutationTable::with_hash(seed, self.perm_table.hash()),
+            ..self
         }
     }
 
@@ -47,6 +62,19 @@ impl Seedable for Value {
     }
 }
 
+impl LatticeHashable for Value {
+    fn set_lattice_hash(self, hash: LatticeHash) -> Self {
+        Self {
+            perm_table: PermutationTable::with_hash(self.seed, hash),
+            ..self
+        }
+    }
+
+    fn lattice_hash(&self) -> LatticeHash {
+        self.perm_table.hash()
+    }
+}
+
 /// 2-dimensional value noise
 impl NoiseFn<Point2<f64>> for Value {
     fn get(&self, point: Point2<f64>) -> f64 {
@@ -55,8 +83,10 @@ impl NoiseFn<Point2<f64>> for Value {
         }
 
         let floored = math::map2(point, f64::floor);
-        let near_corner = math::to_isize2(floored);
-        let far_corner = math::add2(near_corner, math::one2());
+        let period = [self.period[0], self.period[1]];
+        let corner = math::to_isize2(floored);
+        let near_corner = math::wrap2(corner, period);
+        let far_corner = math::wrap2(math::add2(corner, math::one2()), period);
         let weight = math::map2(math::sub2(point, floored), interpolate::s_curve5);
 
         let f00 = get(&self.perm_table, [near_corner[0], near_corner[1]]);
@@ -80,8 +110,10 @@ impl NoiseFn<Point3<f64>> for Value {
         }
 
         let floored = math::map3(point, f64::floor);
-        let near_corner = math::to_isize3(floored);
-        let far_corner = math::add3(near_corner, math::one3());
+        let period = [self.period[0], self.period[1], self.period[2]];
+        let corner = math::to_isize3(floored);
+        let near_corner = math::wrap3(corner, period);
+        let far_corner = math::wrap3(math::add3(corner, math::one3()), period);
         let weight = math::map3(math::sub3(point, floored), interpolate::s_curve5);
 
         let f000 = get(
@@ -137,8 +169,10 @@ impl NoiseFn<Point4<f64>> for Value {
         }
 
         let floored = math::map4(point, f64::floor);
-        let near_corner = math::to_isize4(floored);
-        let far_corner = math::add4(near_corner, math::one4());
+        let period = self.period;
+        let corner = math::to_isize4(floored);
+        let near_corner = math::wrap4(corner, period);
+        let far_corner = math::wrap4(math::add4(corner, math::one4()), period);
         let weight = math::map4(math::sub4(point, floored), interpolate::s_curve5);
 
         let f0000 = get(
@@ -250,3 +284,145 @@ impl NoiseFn<Point4<f64>> for Value {
         d * 2.0 - 1.0
     }
 }
+
+/// 2-dimensional value noise, along with its gradient
+impl NoiseFnWithGradient<Point2<f64>> for Value {
+    fn get_with_gradient(&self, point: Point2<f64>) -> (f64, Point2<f64>) {
+        let floored = math::map2(point, f64::floor);
+        let period = [self.period[0], self.period[1]];
+        let near_corner = math::to_isize2(floored);
+        let distance = math::sub2(point, floored);
+
+        let mut corners = [(0.0, [0.0; 2]); 4];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            let i = i as isize;
+            let offset = [i & 1, (i >> 1) & 1];
+            corner.0 = self
+                .perm_table
+                .get2(math::wrap2(math::add2(near_corner, offset), period))
+                as f64
+                / 255.0;
+        }
+
+        let (value, derivative) = interpolate_with_gradient(
+            &mut corners,
+            math::map2(distance, interpolate::s_curve5),
+            math::map2(distance, interpolate::s_curve5_derivative),
+        );
+
+        (value * 2.0 - 1.0, math::mul2(derivative, 2.0))
+    }
+}
+
+/// 3-dimensional value noise, along with its gradient
+impl NoiseFnWithGradient<Point3<f64>> for Value {
+    fn get_with_gradient(&self, point: Point3<f64>) -> (f64, Point3<f64>) {
+        let floored = math::map3(point, f64::floor);
+        let period = [self.period[0], self.period[1], self.period[2]];
+        let near_corner = math::to_isize3(floored);
+        let distance = math::sub3(point, floored);
+
+        let mut corners = [(0.0, [0.0; 3]); 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            let i = i as isize;
+            let offset = [i & 1, (i >> 1) & 1, (i >> 2) & 1];
+            corner.0 = self
+                .perm_table
+                .get3(math::wrap3(math::add3(near_corner, offset), period))
+                as f64
+                / 255.0;
+        }
+
+        let (value, derivative) = interpolate_with_gradient(
+            &mut corners,
+            math::map3(distance, interpolate::s_curve5),
+            math::map3(distance, interpolate::s_curve5_derivative),
+        );
+
+        (value * 2.0 - 1.0, math::mul3(derivative, 2.0))
+    }
+}
+
+/// 4-dimensional value noise, along with its gradient
+impl NoiseFnWithGradient<Point4<f64>> for Value {
+    fn get_with_gradient(&self, point: Point4<f64>) -> (f64, Point4<f64>) {
+        let floored = math::map4(point, f64::floor);
+        let period = self.period;
+        let near_corner = math::to_isize4(floored);
+        let distance = math::sub4(point, floored);
+
+        let mut corners = [(0.0, [0.0; 4]); 16];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            let i = i as isize;
+            let offset = [i & 1, (i >> 1) & 1, (i >> 2) & 1, (i >> 3) & 1];
+            corner.0 = self
+                .perm_table
+                .get4(math::wrap4(math::add4(near_corner, offset), period))
+                as f64
+                / 255.0;
+        }
+
+        let (value, derivative) = interpolate_with_gradient(
+            &mut corners,
+            math::map4(distance, interpolate::s_curve5),
+            math::map4(distance, interpolate::s_curve5_derivative),
+        );
+
+        (value * 2.0 - 1.0, math::mul4(derivative, 2.0))
+    }
+}
+
+/// Interpolates the values at the corners of a cell in the same order as `get`, along with the
+/// partial derivatives of the result. The index of each corner has bit `i` set if it lies on the
+/// far side of the cell along axis `i`, and the corners are overwritten as the interpolation runs.
+fn interpolate_with_gradient<const N: usize>(
+    corners: &mut [(f64, [f64; N])],
+    weights: [f64; N],
+    weight_derivatives: [f64; N],
+) -> (f64, [f64; N]) {
+    let mut len = corners.len();
+
+    for axis in 0..N {
+        len /= 2;
+
+        for i in 0..len {
+            let (near, near_derivative) = corners[2 * i];
+            let (far, far_derivative) = corners[2 * i + 1];
+
+            let mut derivative = [0.0; N];
+            for ((d, &n), &f) in derivative
+                .iter_mut()
+                .zip(near_derivative.iter())
+                .zip(far_derivative.iter())
+            {
+                *d = interpolate::linear(n, f, weights[axis]);
+            }
+            derivative[axis] = (far - near) * weight_derivatives[axis];
+
+            corners[i] = (interpolate::linear(near, far, weights[axis]), derivative);
+        }
+    }
+
+    corners[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noise_fns::tests::{assert_gradients_match, assert_tiles};
+
+    #[test]
+    fn test_gradients_match_finite_differences() {
+        assert_gradients_match(&Value::new().set_seed(1));
+        assert_gradients_match(&Value::new().set_period([3; 4]));
+        assert_gradients_match(&Value::new().set_lattice_hash(LatticeHash::Integer));
+    }
+
+    #[test]
+    fn test_periodic_noise_tiles() {
+        let value = Value::new().set_period([3; 4]);
+        assert_tiles(&value, [3.0; 2]);
+        assert_tiles(&value, [3.0; 3]);
+        assert_tiles(&value, [3.0; 4]);
+    }
+}
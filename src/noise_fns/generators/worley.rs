@@ -1,7 +1,7 @@
 use math;
 use math::{Point2, Point3, Point4};
 use noise_fns::{NoiseFn, Seedable};
-use permutationtable::PermutationTable;
+use permutationtable::{self, LatticeHash, LatticeHashable, PermutationTable};
 use std;
 
 /// Noise function that outputs Worley noise.
@@ -11,8 +11,13 @@ pub struct Worley {
     /// the cell.
     pub range_function: RangeFunction,
 
-    /// Determines if the distance from the nearest seed point is applied to
-    /// the output value.
+    /// Determines which combination of the cell value and the distances to
+    /// the nearest seed points is output.
+    pub return_type: ReturnType,
+
+    /// Outputs the distance to the nearest seed point instead of the cell
+    /// value, when the return type is `ReturnType::Value`.
+    #[deprecated(note = "set `return_type` to `ReturnType::Distance` instead")]
     pub enable_range: bool,
 
     /// Frequency of the seed points.
@@ -26,24 +31,46 @@ pub struct Worley {
     /// the displacement value.
     pub displacement: f64,
 
+    /// Amount of random offset applied to the seed points, between 0 and 1.
+    /// At 0 the seed points form a regular grid, and at 1 they are spread out
+    /// as far as they go from the centres of their cells.
+    pub jitter: f64,
+
+    /// Arrangement of the cells that hold the seed points.
+    pub lattice: Lattice,
+
+    /// Number of seed points in each cell.
+    pub points_per_cell: usize,
+
     seed: u32,
     perm_table: PermutationTable,
+    period: [usize; 4],
 }
 
 impl Worley {
     pub const DEFAULT_SEED: u32 = 0;
     pub const DEFAULT_RANGEFUNCTION: RangeFunction = RangeFunction::Euclidean;
+    pub const DEFAULT_RETURN_TYPE: ReturnType = ReturnType::Value;
     pub const DEFAULT_FREQUENCY: f64 = 1.0;
     pub const DEFAULT_DISPLACEMENT: f64 = 1.0;
+    pub const DEFAULT_JITTER: f64 = 1.0;
+    pub const DEFAULT_LATTICE: Lattice = Lattice::Square;
+    pub const DEFAULT_POINTS_PER_CELL: usize = 1;
 
+    #[allow(deprecated)]
     pub fn new() -> Self {
         Self {
             perm_table: PermutationTable::new(Self::DEFAULT_SEED),
             seed: Self::DEFAULT_SEED,
             range_function: Self::DEFAULT_RANGEFUNCTION,
+            return_type: Self::DEFAULT_RETURN_TYPE,
             enable_range: false,
             frequency: Self::DEFAULT_FREQUENCY,
             displacement: Self::DEFAULT_DISPLACEMENT,
+            jitter: Self::DEFAULT_JITTER,
+            lattice: Self::DEFAULT_LATTICE,
+            points_per_cell: Self::DEFAULT_POINTS_PER_CELL,
+            period: [0; 4],
         }
     }
 
@@ -57,9 +84,27 @@ impl Worley {
 
     /// Enables or disables applying the distance from the nearest seed point
     /// to the output value.
+    ///
+    /// This is shorthand for setting the return type to `ReturnType::Distance`
+    /// or `ReturnType::Value`.
+    #[allow(deprecated)]
     pub fn enable_range(self, enable_range: bool) -> Self {
+        let return_type = if enable_range {
+            ReturnType::Distance
+        } else {
+            ReturnType::Value
+        };
+
         Self {
             enable_range,
+            ..self.set_return_type(return_type)
+        }
+    }
+
+    /// Sets the combination of cell value and seed point distances to output.
+    pub fn set_return_type(self, return_type: ReturnType) -> Self {
+        Self {
+            return_type,
             ..self
         }
     }
@@ -75,6 +120,283 @@ impl Worley {
             ..self
         }
     }
+
+    /// Sets the amount of random offset applied to the seed points, clamped
+    /// to [0, 1].
+    pub fn set_jitter(self, jitter: f64) -> Self {
+        Self {
+            jitter: jitter.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Sets the arrangement of the cells that hold the seed points.
+    pub fn set_lattice(self, lattice: Lattice) -> Self {
+        Self { lattice, ..self }
+    }
+
+    /// Sets the number of seed points in each cell. There is always at least
+    /// one.
+    pub fn set_points_per_cell(self, points_per_cell: usize) -> Self {
+        Self {
+            points_per_cell: points_per_cell.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the number of cells after which the seed points repeat along each
+    /// axis, so that the noise tiles exactly. A period of zero doesn't repeat.
+    ///
+    /// The period is counted in cells, so the noise repeats every
+    /// `period / frequency` units. On the hexagonal lattice, the rows are
+    /// `sqrt(3) / 2` cells apart, and the period along y has to be even for the
+    /// shifted rows to line up, so an odd period is rounded up to the next even
+    /// one.
+    ///
+    /// 2- and 3-dimensional noise use the first two and three periods.
+    pub fn set_period(self, period: [usize; 4]) -> Self {
+        Self { period, ..self }
+    }
+
+    pub fn period(&self) -> [usize; 4] {
+        self.period
+    }
+
+    /// The range function, with the exponent of `RangeFunction::Minkowski`
+    /// clamped to at least 1.
+    fn effective_range_function(&self) -> RangeFunction {
+        match self.range_function {
+            RangeFunction::Minkowski(p) => RangeFunction::Minkowski(p.max(1.0)),
+            range_function => range_function,
+        }
+    }
+
+    /// The return type, after applying the deprecated `enable_range` field.
+    #[allow(deprecated)]
+    fn effective_return_type(&self) -> ReturnType {
+        if self.enable_range && self.return_type == ReturnType::Value {
+            ReturnType::Distance
+        } else {
+            self.return_type
+        }
+    }
+
+    /// Returns the cell containing the given 2D point.
+    pub fn query2(&self, point: Point2<f64>) -> WorleyCell<2> {
+        self.query(point, |cell, number| self.seed_point2(cell, number))
+    }
+
+    /// Returns the cell containing the given 3D point.
+    pub fn query3(&self, point: Point3<f64>) -> WorleyCell<3> {
+        self.query(point, |cell, number| self.seed_point3(cell, number))
+    }
+
+    /// Returns the cell containing the given 4D point.
+    pub fn query4(&self, point: Point4<f64>) -> WorleyCell<4> {
+        self.query(point, |cell, number| self.seed_point4(cell, number))
+    }
+
+    fn seed_point2(&self, cell: Point2<isize>, number: usize) -> SeedPoint<2> {
+        let wrapped = self.wrap_cell(cell);
+        let index = self.seed_index(self.perm_table.get2(wrapped), &wrapped, number);
+        let position = math::add2(
+            lattice_center(self.lattice, math::to_f64_2(cell)),
+            math::mul2(get_vec2(index), self.jitter),
+        );
+
+        SeedPoint {
+            cell,
+            number,
+            index,
+            position,
+        }
+    }
+
+    fn seed_point3(&self, cell: Point3<isize>, number: usize) -> SeedPoint<3> {
+        let wrapped = self.wrap_cell(cell);
+        let index = self.seed_index(self.perm_table.get3(wrapped), &wrapped, number);
+        let position = math::add3(
+            lattice_center(self.lattice, math::to_f64_3(cell)),
+            math::mul3(get_vec3(index), self.jitter),
+        );
+
+        SeedPoint {
+            cell,
+            number,
+            index,
+            position,
+        }
+    }
+
+    fn seed_point4(&self, cell: Point4<isize>, number: usize) -> SeedPoint<4> {
+        let wrapped = self.wrap_cell(cell);
+        let index = self.seed_index(self.perm_table.get4(wrapped), &wrapped, number);
+        let position = math::add4(
+            lattice_center(self.lattice, math::to_f64_4(cell)),
+            math::mul4(get_vec4(index), self.jitter),
+        );
+
+        SeedPoint {
+            cell,
+            number,
+            index,
+            position,
+        }
+    }
+
+    /// Wraps the coordinates of a cell by the period, giving the cell whose
+    /// seed points it repeats.
+    fn wrap_cell<const N: usize>(&self, mut cell: [isize; N]) -> [isize; N] {
+        // Every other row of the hexagonal lattice is shifted, so the rows
+        // only line up again after an even number of them.
+        let mut period = self.period;
+        if self.lattice == Lattice::Hexagonal {
+            period[1] += period[1] % 2;
+        }
+
+        for (x, &period) in cell.iter_mut().zip(period.iter()) {
+            *x = math::wrap(*x, period);
+        }
+        cell
+    }
+
+    /// Derives the index of a seed point from its wrapped cell and the index of
+    /// the cell. The first point keeps the cell's index, so that a single point
+    /// per cell lands in the same place whatever the number of points. The
+    /// others are hashed from the full cell coordinates, as the cell's index
+    /// only takes 256 values, and would repeat the same points in many cells.
+    fn seed_index(&self, cell_index: usize, cell: &[isize], number: usize) -> usize {
+        if number == 0 {
+            cell_index
+        } else {
+            (hash_seed_point(self.seed, cell, number) & 0xff) as usize
+        }
+    }
+
+    /// Calls `visit` with each cell around `point` that could hold a seed
+    /// point within the range returned by the previous call.
+    fn search_cells<const N: usize, F>(
+        &self,
+        range_function: RangeFunction,
+        point: &[f64; N],
+        visit: F,
+    ) where
+        F: FnMut([isize; N]) -> f64,
+    {
+        match self.lattice {
+            Lattice::Square => search_cells(range_function, point, visit),
+            Lattice::Hexagonal => search_hex_cells(range_function, point, visit),
+        }
+    }
+
+    /// Finds the seed points nearest to `point`, which has already been scaled
+    /// by the frequency.
+    fn find_nearest<const N: usize, F>(
+        &self,
+        point: &[f64; N],
+        return_type: ReturnType,
+        seed_point: F,
+    ) -> Nearest<SeedPoint<N>>
+    where
+        F: Fn([isize; N], usize) -> SeedPoint<N>,
+    {
+        let range_function = self.effective_range_function();
+        let mut nearest = Nearest::new(SeedPoint::new(), return_type.features());
+
+        self.search_cells(range_function, point, |cell| {
+            for number in 0..self.points_per_cell {
+                let seed = seed_point(cell, number);
+                let range = calculate_range(range_function, point, &seed.position);
+                nearest.add(range, seed);
+            }
+
+            nearest.cutoff()
+        });
+
+        nearest
+    }
+
+    /// Distance from `point` to the nearest border of the cell of the seed
+    /// point `nearest`, using the Euclidean range function.
+    fn euclidean_border_distance<const N: usize, F>(
+        &self,
+        point: &[f64; N],
+        nearest: SeedPoint<N>,
+        seed_point: F,
+    ) -> f64
+    where
+        F: Fn([isize; N], usize) -> SeedPoint<N>,
+    {
+        let range = range_euclidean(point, &nearest.position);
+        let mut border_distance = f64::MAX;
+
+        // The border with another cell lies halfway between the two seed
+        // points, at a distance of at least half the difference of their
+        // ranges from the point. That gives the cutoff for cells that can't
+        // hold a closer border.
+        self.search_cells(RangeFunction::Euclidean, point, |cell| {
+            for number in 0..self.points_per_cell {
+                let other = seed_point(cell, number);
+                if other.cell == nearest.cell && other.number == nearest.number {
+                    continue;
+                }
+
+                let mut distance = 0.0;
+                let mut length = 0.0;
+                let positions = nearest.position.iter().zip(&other.position);
+                for (x, (a, b)) in point.iter().zip(positions) {
+                    let normal = b - a;
+                    let midpoint = (a + b) * 0.5;
+                    distance += (midpoint - x) * normal;
+                    length += normal * normal;
+                }
+
+                // Seed points can coincide, in which case there is no border
+                // between them.
+                if length > 0.0 {
+                    border_distance = border_distance.min(distance / length.sqrt());
+                }
+            }
+
+            border_distance * 2.0 + range
+        });
+
+        border_distance
+    }
+
+    fn query<const N: usize, F>(&self, point: [f64; N], seed_point: F) -> WorleyCell<N>
+    where
+        F: Fn([isize; N], usize) -> SeedPoint<N>,
+    {
+        let mut scaled = point;
+        for x in scaled.iter_mut() {
+            *x *= self.frequency;
+        }
+
+        let nearest = self.find_nearest(&scaled, ReturnType::Distance2, &seed_point);
+        let seed = nearest.seed;
+
+        let range_function = self.effective_range_function();
+        let border_distance = match range_function {
+            RangeFunction::Euclidean | RangeFunction::EuclideanSquared => {
+                self.euclidean_border_distance(&scaled, seed, &seed_point)
+            }
+            _ => (nearest.ranges[1] - nearest.ranges[0]) * 0.5,
+        };
+
+        let mut feature_point = seed.position;
+        for x in feature_point.iter_mut() {
+            *x /= self.frequency;
+        }
+
+        WorleyCell {
+            cell: seed.cell,
+            feature_point,
+            id: hash_seed_point(self.seed, &self.wrap_cell(seed.cell), seed.number),
+            distance: calculate_range(range_function, &point, &feature_point),
+            border_distance: border_distance / self.frequency,
+        }
+    }
 }
 
 impl Default for Worley {
@@ -93,7 +415,7 @@ impl Seedable for Worley {
 
         // Otherwise, regenerate the permutation table based on the new seed.
         Self {
-            perm_table: PermutationTable::new(seed),
+            perm_table: PermutationTable::with_hash(seed, self.perm_table.hash()),
             seed,
             ..self
         }
@@ -104,6 +426,19 @@ impl Seedable for Worley {
     }
 }
 
+impl LatticeHashable for Worley {
+    fn set_lattice_hash(self, hash: LatticeHash) -> Self {
+        Self {
+            perm_table: PermutationTable::with_hash(self.seed, hash),
+            ..self
+        }
+    }
+
+    fn lattice_hash(&self) -> LatticeHash {
+        self.perm_table.hash()
+    }
+}
+
 /// Set of distance functions that can be used in the Worley noise function.
 #[derive(Clone, Copy, Debug)]
 pub enum RangeFunction {
@@ -127,7 +462,460 @@ pub enum RangeFunction {
 
     /// Experimental function where all values are multiplied together and then
     /// added up like a quadratic equation.
+    ///
+    /// The range can be small even far from the seed point, so cells can't be
+    /// skipped, and only the neighbourhood described for `Custom` is searched.
     Quadratic,
+
+    /// Generalization of the Manhattan (p = 1) and Euclidean (p = 2) distances,
+    /// approaching the Chebyshev distance as p grows. p is clamped to at least
+    /// 1, as smaller values don't give a true distance.
+    Minkowski(f64),
+
+    /// User-defined distance between two points of the same dimension.
+    ///
+    /// Cells can't be skipped based on an unknown function, so every cell in
+    /// the neighbourhood searched around the input point is tested: the cell
+    /// nearest to the point, and the two cells on either side of it along
+    /// each axis, which makes 5<sup>n</sup> cells in n dimensions. This is
+    /// slower than the built-in functions. Seed points outside the
+    /// neighbourhood are never found, so the function should grow with the
+    /// distance along each axis for the nearest seed point to be found.
+    ///
+    /// Distances should be non-negative. Negative distances are treated as
+    /// zero.
+    Custom(fn(&[f64], &[f64]) -> f64),
+}
+
+/// Arrangements of the cells holding the Worley seed points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Lattice {
+    /// Cells on a square grid with unit spacing.
+    Square,
+
+    /// Cells on a hexagonal grid with unit spacing in the x-y plane, where
+    /// every other row along y is shifted by half a cell. The cells are
+    /// stacked in columns along any further axes. With little jitter this
+    /// gives honeycomb patterns.
+    Hexagonal,
+}
+
+/// Set of values that the Worley noise function can output.
+///
+/// F1 is the distance from the input point to the nearest seed point, F2 the
+/// distance to the second nearest seed point, and so on up to Fn, as measured
+/// by the range function. Every output is scaled and shifted by `value * 2.0 - 1.0`, so the
+/// distance based outputs only fall within [-1, 1] for short range functions
+/// and the default frequency.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReturnType {
+    /// The random value assigned to the cell of the nearest seed point, scaled
+    /// by the displacement.
+    Value,
+
+    /// F1, the distance to the nearest seed point.
+    Distance,
+
+    /// F2, the distance to the second nearest seed point.
+    Distance2,
+
+    /// F1 + F2.
+    Distance2Add,
+
+    /// F2 - F1. This is zero along the edges between cells, making it the
+    /// usual choice for cell outlines and crackle patterns.
+    Distance2Sub,
+
+    /// F1 * F2.
+    Distance2Mul,
+
+    /// F1 / F2.
+    Distance2Div,
+
+    /// The cell value with F1 added on top, as libnoise's Voronoi module does
+    /// when its distance is enabled.
+    ValueAndDistance,
+
+    /// Fn, the distance to the nth nearest seed point, counting from one, so
+    /// `DistanceN(1)` is F1 and `DistanceN(2)` is F2. n is clamped to
+    /// [1, `ReturnType::MAX_FEATURES`].
+    DistanceN(usize),
+}
+
+impl ReturnType {
+    /// The most seed points whose distances can be output, the highest n of
+    /// `DistanceN`.
+    pub const MAX_FEATURES: usize = 8;
+
+    /// Number of nearest seed points that have to be found.
+    fn features(self) -> usize {
+        match self {
+            ReturnType::Value | ReturnType::Distance | ReturnType::ValueAndDistance => 1,
+            ReturnType::Distance2
+            | ReturnType::Distance2Add
+            | ReturnType::Distance2Sub
+            | ReturnType::Distance2Mul
+            | ReturnType::Distance2Div => 2,
+            ReturnType::DistanceN(n) => n.clamp(1, Self::MAX_FEATURES),
+        }
+    }
+}
+
+/// A cell of the Worley noise function, as returned by `Worley::query2`,
+/// `query3` and `query4`.
+///
+/// The cells are the same ones `get` uses, so a cell covers exactly the region
+/// where `get` outputs the cell's value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WorleyCell<const N: usize> {
+    /// Integer coordinates of the lattice cell holding the seed point, after
+    /// scaling by the frequency.
+    pub cell: [isize; N],
+
+    /// Position of the seed point, in the same space as the input point.
+    pub feature_point: [f64; N],
+
+    /// Hash of the cell coordinates, the number of the seed point within the
+    /// cell, and the seed. It stays the same for a given seed point and seed
+    /// across runs and platforms, and repeats along with the seed points when
+    /// a period is set.
+    pub id: u64,
+
+    /// Range from the input point to the seed point, as measured by the range
+    /// function.
+    pub distance: f64,
+
+    /// Distance from the input point to the nearest border of the cell.
+    ///
+    /// This is only exact for the Euclidean range functions, `Euclidean` and
+    /// `EuclideanSquared`, and is always a Euclidean distance, even when
+    /// `distance` is squared. The borders of the cells of the other range
+    /// functions aren't straight, so for those it is only approximated, as
+    /// half the difference between the ranges to the nearest and second
+    /// nearest seed points, and can be too large or too small.
+    pub border_distance: f64,
+}
+
+/// A seed point, along with the cell that holds it.
+#[derive(Clone, Copy, Debug)]
+struct SeedPoint<const N: usize> {
+    cell: [isize; N],
+
+    /// Number of the seed point within its cell.
+    number: usize,
+
+    /// Index into the permutation table, which gives the seed point's value
+    /// and offset from the centre of the cell.
+    index: usize,
+
+    position: [f64; N],
+}
+
+impl<const N: usize> SeedPoint<N> {
+    fn new() -> Self {
+        Self {
+            cell: [0; N],
+            number: 0,
+            index: 0,
+            position: [0.0; N],
+        }
+    }
+}
+
+/// The nearest seed points found so far while searching the cells around the
+/// input point.
+#[derive(Clone, Copy, Debug)]
+struct Nearest<S> {
+    /// The nearest seed point.
+    seed: S,
+
+    /// Ranges to the nearest seed points, from the nearest (F1) to the
+    /// farthest. Only the first `features` ranges are tracked.
+    ranges: [f64; ReturnType::MAX_FEATURES],
+
+    features: usize,
+}
+
+impl<S: Copy> Nearest<S> {
+    /// Tracks the given number of nearest seed points, between 1 and
+    /// `ReturnType::MAX_FEATURES`.
+    fn new(seed: S, features: usize) -> Self {
+        Self {
+            seed,
+            ranges: [f64::MAX; ReturnType::MAX_FEATURES],
+            features,
+        }
+    }
+
+    fn add(&mut self, range: f64, seed: S) {
+        let mut i = self.features - 1;
+        if range < self.ranges[i] {
+            // Insertion sort, moving the farther ranges down by one.
+            while i > 0 && range < self.ranges[i - 1] {
+                self.ranges[i] = self.ranges[i - 1];
+                i -= 1;
+            }
+
+            self.ranges[i] = range;
+            if i == 0 {
+                self.seed = seed;
+            }
+        }
+    }
+
+    /// The range a seed point has to beat to change the output, the range to
+    /// the farthest of the tracked seed points.
+    fn cutoff(&self) -> f64 {
+        self.ranges[self.features - 1]
+    }
+
+    /// Combines the ranges and the value of the nearest seed point into the
+    /// output value.
+    fn output(&self, return_type: ReturnType, cell_value: f64) -> f64 {
+        let [f1, f2] = [self.ranges[0], self.ranges[1]];
+        let value = match return_type {
+            ReturnType::Value => cell_value,
+            ReturnType::Distance => f1,
+            ReturnType::Distance2 => f2,
+            ReturnType::Distance2Add => f1 + f2,
+            ReturnType::Distance2Sub => f2 - f1,
+            ReturnType::Distance2Mul => f1 * f2,
+            ReturnType::Distance2Div => f1 / f2,
+            ReturnType::ValueAndDistance => cell_value + f1,
+            ReturnType::DistanceN(_) => self.cutoff(),
+        };
+
+        value * 2.0 - 1.0
+    }
+}
+
+/// Calls `search` with the offsets of the cells along an axis, relative to the
+/// cell nearest to the input point, nearest first, so that the ranges found
+/// early on let the farther cells be skipped. `search` returns whether the
+/// cell could still hold a seed point within the cutoff, and the search moves
+/// outwards until neither side of the axis can.
+///
+/// How far that is depends on the range function, the dimension and the number
+/// of seed points looked for, so it isn't fixed. For example, with the
+/// Manhattan range the eighth nearest seed point can be more than two cells
+/// away. The quadratic and custom ranges don't bound the cells, so for those
+/// only the cells up to two away are searched.
+fn search_offsets<F>(range_function: RangeFunction, mut search: F)
+where
+    F: FnMut(isize) -> bool,
+{
+    let radius = match range_function {
+        RangeFunction::Quadratic | RangeFunction::Custom(_) => 2,
+        _ => isize::MAX,
+    };
+
+    if !search(0) {
+        return;
+    }
+
+    for offset in 1..=radius {
+        // Both sides are searched, even when the first is ruled out.
+        let lower = search(-offset);
+        let upper = search(offset);
+        if !lower && !upper {
+            break;
+        }
+    }
+}
+
+/// Whether a cell whose bound from `add_axis_gap` is `bound` could hold a seed
+/// point within `cutoff`.
+fn within_cutoff(range_function: RangeFunction, bound: f64, cutoff: f64) -> bool {
+    // A NaN bound rules the cell out, so that the search always ends.
+    bound < bound_cutoff(range_function, cutoff)
+}
+
+/// Adds the gap along another axis to a lower bound on the range to any seed
+/// point in a cell. Gaps are combined the way the range function combines the
+/// differences along each axis, but without taking the final root of the
+/// Euclidean and Minkowski ranges.
+fn add_axis_gap(range_function: RangeFunction, bound: f64, gap: f64) -> f64 {
+    match range_function {
+        RangeFunction::Euclidean | RangeFunction::EuclideanSquared => bound + gap * gap,
+        RangeFunction::Manhattan => bound + gap,
+        RangeFunction::Chebyshev => bound.max(gap),
+        RangeFunction::Minkowski(p) => bound + gap.powf(p),
+        // The quadratic range can be small even when every axis is far apart,
+        // and nothing is known about custom ranges, so no cell can be ruled
+        // out for either.
+        RangeFunction::Quadratic | RangeFunction::Custom(_) => 0.0,
+    }
+}
+
+/// Raises a range to the power that the bounds from `add_axis_gap` are kept
+/// at, so the two can be compared without taking a root per cell.
+fn bound_cutoff(range_function: RangeFunction, cutoff: f64) -> f64 {
+    match range_function {
+        RangeFunction::Euclidean => cutoff * cutoff,
+        RangeFunction::Minkowski(p) => cutoff.powf(p),
+        _ => cutoff,
+    }
+}
+
+/// Cell of the square lattice whose centre is nearest to `point`.
+fn near_cell<const N: usize>(point: &[f64; N]) -> [isize; N] {
+    let mut near = [0; N];
+    for (n, &x) in near.iter_mut().zip(point) {
+        let whole = x.floor();
+        *n = whole as isize + ((x - whole > 0.5) as isize);
+    }
+
+    near
+}
+
+/// Calls `visit` with each cell around `point` that could hold a seed point
+/// within the range returned by the previous call, starting out unbounded.
+/// Cells are skipped as soon as the axes visited so far rule them out.
+fn search_cells<const N: usize, F>(range_function: RangeFunction, point: &[f64; N], mut visit: F)
+where
+    F: FnMut([isize; N]) -> f64,
+{
+    let near = near_cell(point);
+    let mut cell = near;
+    let mut cutoff = f64::MAX;
+
+    search_axis(
+        range_function,
+        point,
+        &near,
+        0,
+        0.0,
+        &mut cell,
+        &mut cutoff,
+        &mut visit,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn search_axis<const N: usize, F>(
+    range_function: RangeFunction,
+    point: &[f64; N],
+    near: &[isize; N],
+    axis: usize,
+    bound: f64,
+    cell: &mut [isize; N],
+    cutoff: &mut f64,
+    visit: &mut F,
+) where
+    F: FnMut([isize; N]) -> f64,
+{
+    search_offsets(range_function, |offset| {
+        cell[axis] = near[axis] + offset;
+
+        // Seed points never lie more than half a unit from the centre of their
+        // cell, so this is as close as the cell can get along this axis.
+        let gap = ((point[axis] - cell[axis] as f64).abs() - 0.5).max(0.0);
+        let cur_bound = add_axis_gap(range_function, bound, gap);
+        if !within_cutoff(range_function, cur_bound, *cutoff) {
+            return false;
+        }
+
+        if axis + 1 < N {
+            search_axis(
+                range_function,
+                point,
+                near,
+                axis + 1,
+                cur_bound,
+                cell,
+                cutoff,
+                visit,
+            );
+        } else {
+            *cutoff = visit(*cell);
+        }
+
+        true
+    });
+}
+
+// Distance between the rows of the hexagonal lattice, sqrt(3) / 2.
+const HEX_ROW_HEIGHT: f64 = 0.866_025_403_784_438_6;
+
+/// Moves the centre of a cell from its place on the square lattice to its
+/// place on the given lattice.
+fn lattice_center<const N: usize>(lattice: Lattice, mut center: [f64; N]) -> [f64; N] {
+    if lattice == Lattice::Hexagonal {
+        // Odd rows are shifted by half a cell.
+        center[0] += (center[1] as isize & 1) as f64 * 0.5;
+        center[1] *= HEX_ROW_HEIGHT;
+    }
+
+    center
+}
+
+/// Same as `search_cells`, but for the cells of the hexagonal lattice. Rows
+/// are searched before columns, since the horizontal position of the cells
+/// depends on the row.
+fn search_hex_cells<const N: usize, F>(
+    range_function: RangeFunction,
+    point: &[f64; N],
+    mut visit: F,
+) where
+    F: FnMut([isize; N]) -> f64,
+{
+    // Any further axes are searched the same way as for the square lattice.
+    let near = near_cell(point);
+    let mut cell = near;
+
+    let near_row = (point[1] / HEX_ROW_HEIGHT).round() as isize;
+    let mut cutoff = f64::MAX;
+
+    search_offsets(range_function, |row_offset| {
+        let row = near_row + row_offset;
+
+        let y_gap = ((point[1] - row as f64 * HEX_ROW_HEIGHT).abs() - 0.5).max(0.0);
+        let y_bound = add_axis_gap(range_function, 0.0, y_gap);
+        if !within_cutoff(range_function, y_bound, cutoff) {
+            return false;
+        }
+
+        let shift = (row & 1) as f64 * 0.5;
+        let near_column = (point[0] - shift).round() as isize;
+
+        search_offsets(range_function, |column_offset| {
+            let column = near_column + column_offset;
+
+            let x_gap = ((point[0] - column as f64 - shift).abs() - 0.5).max(0.0);
+            let bound = add_axis_gap(range_function, y_bound, x_gap);
+            if !within_cutoff(range_function, bound, cutoff) {
+                return false;
+            }
+
+            cell[0] = column;
+            cell[1] = row;
+
+            if N > 2 {
+                search_axis(
+                    range_function,
+                    point,
+                    &near,
+                    2,
+                    bound,
+                    &mut cell,
+                    &mut cutoff,
+                    &mut visit,
+                );
+            } else {
+                cutoff = visit(cell);
+            }
+
+            true
+        });
+
+        true
+    });
+}
+
+/// Hashes the cell and number of a seed point together with the seed.
+fn hash_seed_point(seed: u32, cell: &[isize], number: usize) -> u64 {
+    let values = cell.iter().map(|&x| x as i64 as u64);
+    permutationtable::hash_integers(seed, values.chain(Some(number as u64)))
 }
 
 fn calculate_range(range_function: RangeFunction, p1: &[f64], p2: &[f64]) -> f64 {
@@ -137,6 +925,10 @@ fn calculate_range(range_function: RangeFunction, p1: &[f64], p2: &[f64]) -> f64
         RangeFunction::Manhattan => range_manhattan(p1, p2),
         RangeFunction::Chebyshev => range_chebyshev(p1, p2),
         RangeFunction::Quadratic => range_quadratic(p1, p2),
+        RangeFunction::Minkowski(p) => range_minkowski(p, p1, p2),
+        // A negative range would become the cutoff, and rule out every other
+        // cell.
+        RangeFunction::Custom(range) => range(p1, p2).max(0.0),
     }
 }
 
@@ -170,7 +962,16 @@ fn range_chebyshev(p1: &[f64], p2: &[f64]) -> f64 {
         .zip(p2.iter())
         .map(|(a, b)| *a - *b)
         .map(|a| a.abs())
-        .fold(std::f64::MIN, |a, b| a.max(b))
+        .fold(f64::MIN, |a, b| a.max(b))
+}
+
+fn range_minkowski(p: f64, p1: &[f64], p2: &[f64]) -> f64 {
+    p1.iter()
+        .zip(p2.iter())
+        .map(|(a, b)| *a - *b)
+        .map(|a| a.abs().powf(p))
+        .fold(0.0, |acc, x| acc + x)
+        .powf(1.0 / p)
 }
 
 fn range_quadratic(p1: &[f64], p2: &[f64]) -> f64 {
@@ -189,61 +990,15 @@ fn range_quadratic(p1: &[f64], p2: &[f64]) -> f64 {
 
 impl NoiseFn<Point2<f64>> for Worley {
     fn get(&self, point: Point2<f64>) -> f64 {
-        fn get_point(perm_table: &PermutationTable, whole: Point2<isize>) -> Point2<f64> {
-            math::add2(get_vec2(perm_table.get2(whole)), math::to_f64_2(whole))
-        }
-
-        let point = &math::mul2(point, self.frequency);
-
-        let cell = math::map2(*point, f64::floor);
-        let whole = math::to_isize2(cell);
-        let frac = math::sub2(*point, cell);
-
-        let x_half = frac[0] > 0.5;
-        let y_half = frac[1] > 0.5;
-
-        let near = [whole[0] + (x_half as isize), whole[1] + (y_half as isize)];
-        let far = [whole[0] + (!x_half as isize), whole[1] + (!y_half as isize)];
-
-        let mut seed_cell = near;
-        let seed_point = get_point(&self.perm_table, near);
-        let mut range = calculate_range(self.range_function, point, &seed_point);
-
-        let x_range = (0.5 - frac[0]) * (0.5 - frac[0]); // x-distance squared to center line
-        let y_range = (0.5 - frac[1]) * (0.5 - frac[1]); // y-distance squared to center line
+        let point = math::mul2(point, self.frequency);
+        let return_type = self.effective_return_type();
+        let nearest = self.find_nearest(&point, return_type, |cell, number| {
+            self.seed_point2(cell, number)
+        });
 
-        macro_rules! test_point(
-            [$x:expr, $y:expr] => {
-                {
-                    let cur_point = get_point(&self.perm_table, [$x, $y]);
-                    let cur_range = calculate_range(self.range_function, point, &cur_point);
-                    if cur_range < range {
-                        range = cur_range;
-                        seed_cell = [$x, $y];
-                    }
-                }
-            }
-        );
-
-        if x_range < range {
-            test_point![far[0], near[1]];
-        }
-
-        if y_range < range {
-            test_point![near[0], far[1]];
-        }
-
-        if x_range < range && y_range < range {
-            test_point![far[0], far[1]];
-        }
-
-        let value = if self.enable_range {
-            range
-        } else {
-            self.displacement * self.perm_table.get2(seed_cell) as f64 / 255.0
-        };
+        let cell_value = self.displacement * nearest.seed.index as f64 / 255.0;
 
-        value * 2.0 - 1.0
+        nearest.output(return_type, cell_value)
     }
 }
 
@@ -267,83 +1022,15 @@ fn get_vec2(index: usize) -> Point2<f64> {
 
 impl NoiseFn<Point3<f64>> for Worley {
     fn get(&self, point: Point3<f64>) -> f64 {
-        fn get_point(perm_table: &PermutationTable, whole: Point3<isize>) -> Point3<f64> {
-            math::add3(get_vec3(perm_table.get3(whole)), math::to_f64_3(whole))
-        }
-
-        let point = &math::mul3(point, self.frequency);
-
-        let cell = math::map3(*point, f64::floor);
-        let whole = math::to_isize3(cell);
-        let frac = math::sub3(*point, cell);
-
-        let x_half = frac[0] > 0.5;
-        let y_half = frac[1] > 0.5;
-        let z_half = frac[2] > 0.5;
-
-        let near = [
-            whole[0] + (x_half as isize),
-            whole[1] + (y_half as isize),
-            whole[2] + (z_half as isize),
-        ];
-        let far = [
-            whole[0] + (!x_half as isize),
-            whole[1] + (!y_half as isize),
-            whole[2] + (!z_half as isize),
-        ];
-
-        let mut seed_cell = near;
-        let seed_point = get_point(&self.perm_table, near);
-        let mut range = calculate_range(self.range_function, point, &seed_point);
-
-        let x_range = (0.5 - frac[0]) * (0.5 - frac[0]); // x-distance squared to center line
-        let y_range = (0.5 - frac[1]) * (0.5 - frac[1]); // y-distance squared to center line
-        let z_range = (0.5 - frac[2]) * (0.5 - frac[2]); // z-distance squared to center line
-
-        macro_rules! test_point(
-            [$x:expr, $y:expr, $z:expr] => {
-                {
-                    let cur_point = get_point(&self.perm_table, [$x, $y, $z]);
-                    let cur_range = calculate_range(self.range_function, point, &cur_point);
-                    if cur_range < range {
-                        range = cur_range;
-                        seed_cell = [$x, $y, $z];
-                    }
-                }
-            }
-        );
-
-        if x_range < range {
-            test_point![far[0], near[1], near[2]];
-        }
-        if y_range < range {
-            test_point![near[0], far[1], near[2]];
-        }
-        if z_range < range {
-            test_point![near[0], near[1], far[2]];
-        }
-
-        if x_range < range && y_range < range {
-            test_point![far[0], far[1], near[2]];
-        }
-        if x_range < range && z_range < range {
-            test_point![far[0], near[1], far[2]];
-        }
-        if y_range < range && z_range < range {
-            test_point![near[0], far[1], far[2]];
-        }
-
-        if x_range < range && y_range < range && z_range < range {
-            test_point![far[0], far[1], far[2]];
-        }
+        let point = math::mul3(point, self.frequency);
+        let return_type = self.effective_return_type();
+        let nearest = self.find_nearest(&point, return_type, |cell, number| {
+            self.seed_point3(cell, number)
+        });
 
-        let value = if self.enable_range {
-            range
-        } else {
-            self.displacement * self.perm_table.get3(seed_cell) as f64 / 255.0
-        };
+        let cell_value = self.displacement * nearest.seed.index as f64 / 255.0;
 
-        value * 2.0 - 1.0
+        nearest.output(return_type, cell_value)
     }
 }
 
@@ -377,112 +1064,15 @@ fn get_vec3(index: usize) -> Point3<f64> {
 
 impl NoiseFn<Point4<f64>> for Worley {
     fn get(&self, point: Point4<f64>) -> f64 {
-        fn get_point(perm_table: &PermutationTable, whole: Point4<isize>) -> Point4<f64> {
-            math::add4(get_vec4(perm_table.get4(whole)), math::to_f64_4(whole))
-        }
-
-        let point = &math::mul4(point, self.frequency);
-
-        let cell = math::map4(*point, f64::floor);
-        let whole = math::to_isize4(cell);
-        let frac = math::sub4(*point, cell);
-
-        let x_half = frac[0] > 0.5;
-        let y_half = frac[1] > 0.5;
-        let z_half = frac[2] > 0.5;
-        let w_half = frac[3] > 0.5;
-
-        let near = [
-            whole[0] + (x_half as isize),
-            whole[1] + (y_half as isize),
-            whole[2] + (z_half as isize),
-            whole[3] + (w_half as isize),
-        ];
-        let far = [
-            whole[0] + (!x_half as isize),
-            whole[1] + (!y_half as isize),
-            whole[2] + (!z_half as isize),
-            whole[3] + (!w_half as isize),
-        ];
-
-        let mut seed_cell = near;
-        let seed_point = get_point(&self.perm_table, near);
-        let mut range = calculate_range(self.range_function, point, &seed_point);
-
-        let x_range = (0.5 - frac[0]) * (0.5 - frac[0]); // x-distance squared to center line
-        let y_range = (0.5 - frac[1]) * (0.5 - frac[1]); // y-distance squared to center line
-        let z_range = (0.5 - frac[2]) * (0.5 - frac[2]); // z-distance squared to center line
-        let w_range = (0.5 - frac[3]) * (0.5 - frac[3]); // w-distance squared to center line
-
-        macro_rules! test_point(
-            [$x:expr, $y:expr, $z:expr, $w:expr] => {
-                {
-                    let cur_point = get_point(&self.perm_table, [$x, $y, $z, $w]);
-                    let cur_range = calculate_range(self.range_function, point, &cur_point);
-                    if cur_range < range {
-                        range = cur_range;
-                        seed_cell = [$x, $y, $z, $w];
-                    }
-                }
-            }
-        );
-
-        if x_range < range {
-            test_point![far[0], near[1], near[2], near[3]];
-        }
-        if y_range < range {
-            test_point![near[0], far[1], near[2], near[3]];
-        }
-        if z_range < range {
-            test_point![near[0], near[1], far[2], near[3]];
-        }
-        if w_range < range {
-            test_point![near[0], near[1], near[2], far[3]];
-        }
-
-        if x_range < range && y_range < range {
-            test_point![far[0], far[1], near[2], near[3]];
-        }
-        if x_range < range && z_range < range {
-            test_point![far[0], near[1], far[2], near[3]];
-        }
-        if x_range < range && w_range < range {
-            test_point![far[0], near[1], near[2], far[3]];
-        }
-        if y_range < range && z_range < range {
-            test_point![near[0], far[1], far[2], near[3]];
-        }
-        if y_range < range && w_range < range {
-            test_point![near[0], far[1], near[2], far[3]];
-        }
-        if z_range < range && w_range < range {
-            test_point![near[0], near[1], far[2], far[3]];
-        }
-
-        if x_range < range && y_range < range && z_range < range {
-            test_point![far[0], far[1], far[2], near[3]];
-        }
-        if x_range < range && y_range < range && w_range < range {
-            test_point![far[0], far[1], near[2], far[3]];
-        }
-        if x_range < range && z_range < range && w_range < range {
-            test_point![far[0], near[1], far[2], far[3]];
-        }
-        if y_range < range && z_range < range && w_range < range {
-            test_point![near[0], far[1], far[2], far[3]];
-        }
-
-        if x_range < range && y_range < range && z_range < range && w_range < range {
-            test_point![far[0], far[1], far[2], far[3]];
-        }
+        let point = math::mul4(point, self.frequency);
+        let return_type = self.effective_return_type();
+        let nearest = self.find_nearest(&point, return_type, |cell, number| {
+            self.seed_point4(cell, number)
+        });
 
-        let value = if self.enable_range {
-            range
-        } else {
-            self.displacement * self.perm_table.get4(seed_cell) as f64 / 255.0
-        };
+        let cell_value = self.displacement * nearest.seed.index as f64 / 255.0;
 
-        value * 2.0 - 1.0
+        nearest.output(return_type, cell_value)
     }
 }
 
@@ -527,3 +1117,424 @@ fn get_vec4(index: usize) -> Point4<f64> {
         _ => panic!("Attempt to access 4D gradient {} of 32", index % 32),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noise_fns::tests::assert_tiles;
+
+    #[test]
+    fn test_second_nearest_distances() {
+        let f1 = Worley::new().set_return_type(ReturnType::Distance);
+        let f2 = Worley::new().set_return_type(ReturnType::Distance2);
+        let edges = Worley::new().set_return_type(ReturnType::Distance2Sub);
+
+        for x in 0..32 {
+            for y in 0..32 {
+                for z in 0..4 {
+                    let point = [x as f64 * 0.13 - 2.0, y as f64 * 0.17 - 2.0, z as f64 * 0.7];
+                    let (d1, d2) = ((f1.get(point) + 1.0) / 2.0, (f2.get(point) + 1.0) / 2.0);
+
+                    assert!(d1 <= d2, "F1 {} > F2 {} at {:?}", d1, d2, point);
+                    assert!((edges.get(point) - ((d2 - d1) * 2.0 - 1.0)).abs() < 1e-12);
+
+                    let point = [point[0], point[1]];
+                    let (d1, d2) = ((f1.get(point) + 1.0) / 2.0, (f2.get(point) + 1.0) / 2.0);
+                    assert!(d1 <= d2, "F1 {} > F2 {} at {:?}", d1, d2, point);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_nth_nearest_distances() {
+        let worley = Worley::new().set_points_per_cell(2);
+        let distance = |n| {
+            let worley = worley.set_return_type(ReturnType::DistanceN(n));
+            move |point: [f64; 3]| (worley.get(point) + 1.0) / 2.0
+        };
+        let (f1, f2, f3, f4) = (distance(1), distance(2), distance(3), distance(4));
+
+        let first = worley.set_return_type(ReturnType::Distance);
+        let second = worley.set_return_type(ReturnType::Distance2);
+
+        for x in 0..16 {
+            for y in 0..16 {
+                let point = [x as f64 * 0.23 - 2.0, y as f64 * 0.29 - 2.0, 0.5];
+                assert_eq!(f1(point), (first.get(point) + 1.0) / 2.0);
+                assert_eq!(f2(point), (second.get(point) + 1.0) / 2.0);
+
+                // Compare with the distances to all the seed points nearby.
+                let mut ranges = Vec::new();
+                for i in -4..4 {
+                    for j in -4..4 {
+                        for k in -4..4 {
+                            for number in 0..2 {
+                                let seed = worley.seed_point3([i, j, k], number);
+                                ranges.push(range_euclidean(&point, &seed.position));
+                            }
+                        }
+                    }
+                }
+                ranges.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+                assert!((f3(point) - ranges[2]).abs() < 1e-12);
+                assert!((f4(point) - ranges[3]).abs() < 1e-12);
+            }
+        }
+
+        // Out of range features are clamped.
+        let point = [0.3, 0.7, 0.1];
+        assert_eq!(distance(0)(point), f1(point));
+        assert_eq!(
+            distance(100)(point),
+            distance(ReturnType::MAX_FEATURES)(point)
+        );
+    }
+
+    #[test]
+    fn test_search_matches_brute_force() {
+        // Compares F1, F3 and F8 with the distances to the seed points of every
+        // cell up to four away from the point along each axis.
+        fn assert_matches_brute_force<const N: usize, F>(
+            worley: Worley,
+            point: [f64; N],
+            seed_point: F,
+        ) where
+            Worley: NoiseFn<[f64; N]>,
+            F: Fn(&Worley, [isize; N]) -> SeedPoint<N>,
+        {
+            let mut near = near_cell(&point);
+            if worley.lattice == Lattice::Hexagonal {
+                near[1] = (point[1] / HEX_ROW_HEIGHT).round() as isize;
+            }
+
+            let mut ranges = Vec::new();
+            let mut cell = [-4; N];
+            loop {
+                let mut seed_cell = cell;
+                for (x, near) in seed_cell.iter_mut().zip(&near) {
+                    *x += near;
+                }
+
+                let seed = seed_point(&worley, seed_cell);
+                ranges.push(calculate_range(
+                    worley.range_function,
+                    &point,
+                    &seed.position,
+                ));
+
+                match cell.iter().position(|&x| x < 4) {
+                    Some(axis) => {
+                        cell[axis] += 1;
+                        for x in &mut cell[..axis] {
+                            *x = -4;
+                        }
+                    }
+                    None => break,
+                }
+            }
+            ranges.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            for &n in &[1, 3, ReturnType::MAX_FEATURES] {
+                let worley = worley.set_return_type(ReturnType::DistanceN(n));
+                let range = (worley.get(point) + 1.0) / 2.0;
+                assert!(
+                    (range - ranges[n - 1]).abs() < 1e-9,
+                    "F{} {} != {} for {:?} at {:?}",
+                    n,
+                    range,
+                    ranges[n - 1],
+                    worley.range_function,
+                    point
+                );
+            }
+        }
+
+        let range_functions = [
+            RangeFunction::Euclidean,
+            RangeFunction::EuclideanSquared,
+            RangeFunction::Manhattan,
+            RangeFunction::Chebyshev,
+            RangeFunction::Minkowski(1.5),
+            RangeFunction::Minkowski(3.0),
+        ];
+
+        for &range_function in &range_functions {
+            let worley = Worley::new().set_range_function(range_function);
+            let hexagonal = worley.set_lattice(Lattice::Hexagonal);
+
+            // Some of these points need cells more than two away along an axis
+            // for F8 with the Manhattan range on the hexagonal lattice.
+            for i in 0..24 {
+                let point = [
+                    (i as f64 * 0.618_034).fract() * 40.0,
+                    (i as f64 * 0.754_878).fract() * 40.0,
+                    (i as f64 * 0.569_840).fract() * 40.0,
+                    (i as f64 * 0.414_214).fract() * 40.0,
+                ];
+                assert_matches_brute_force(worley, point, |worley, cell| {
+                    worley.seed_point4(cell, 0)
+                });
+
+                let point = [point[0], point[1]];
+                assert_matches_brute_force(worley, point, |worley, cell| {
+                    worley.seed_point2(cell, 0)
+                });
+                assert_matches_brute_force(hexagonal, point, |worley, cell| {
+                    worley.seed_point2(cell, 0)
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn test_query_matches_get() {
+        let worley = Worley::new().set_frequency(2.0).set_seed(3);
+        let distance = worley.set_return_type(ReturnType::Distance);
+
+        for x in 0..32 {
+            for y in 0..32 {
+                let point = [x as f64 * 0.13 - 2.0, y as f64 * 0.17 - 2.0];
+                let cell = worley.query2(point);
+
+                let value = worley.perm_table.get2(cell.cell) as f64 / 255.0 * 2.0 - 1.0;
+                assert_eq!(worley.get(point), value);
+
+                // The range in `get` is measured after scaling by the frequency.
+                let range = (distance.get(point) + 1.0) / 2.0;
+                assert!((range - cell.distance * 2.0).abs() < 1e-12);
+
+                // Moving less than the border distance stays within the cell, or
+                // at least one sharing its seed point. Points right on a border
+                // can fall either way.
+                if cell.border_distance < 1e-9 {
+                    continue;
+                }
+
+                for i in 0..16 {
+                    let angle = i as f64 * std::f64::consts::PI / 8.0;
+                    let step = cell.border_distance * 0.999;
+                    let moved = [point[0] + angle.cos() * step, point[1] + angle.sin() * step];
+                    assert_eq!(worley.query2(moved).feature_point, cell.feature_point);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_minkowski_and_custom_ranges() {
+        fn euclidean(p1: &[f64], p2: &[f64]) -> f64 {
+            range_euclidean(p1, p2)
+        }
+
+        let pairs = [
+            (RangeFunction::Minkowski(1.0), RangeFunction::Manhattan),
+            (RangeFunction::Minkowski(2.0), RangeFunction::Euclidean),
+            (RangeFunction::Custom(euclidean), RangeFunction::Euclidean),
+        ];
+
+        for &(range_function, expected) in &pairs {
+            let worley = Worley::new().set_return_type(ReturnType::Distance2Sub);
+            let (a, b) = (
+                worley.set_range_function(range_function),
+                worley.set_range_function(expected),
+            );
+
+            for x in 0..16 {
+                for y in 0..16 {
+                    let point = [x as f64 * 0.31 - 2.0, y as f64 * 0.37 - 2.0, 0.5];
+                    assert!((a.get(point) - b.get(point)).abs() < 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_negative_custom_range() {
+        fn negative_euclidean(p1: &[f64], p2: &[f64]) -> f64 {
+            -range_euclidean(p1, p2)
+        }
+
+        let worley = Worley::new()
+            .set_range_function(RangeFunction::Custom(negative_euclidean))
+            .set_return_type(ReturnType::Distance);
+        for x in 0..16 {
+            let point = [x as f64 * 0.31 - 2.0, 0.5];
+            assert_eq!(worley.get(point), -1.0);
+        }
+    }
+
+    #[test]
+    fn test_lattices_without_jitter() {
+        let square = Worley::new().set_jitter(0.0);
+        let hexagonal = square.set_lattice(Lattice::Hexagonal);
+
+        for x in 0..32 {
+            for y in 0..32 {
+                let point = [x as f64 * 0.13 - 2.0, y as f64 * 0.17 - 2.0];
+
+                let cell = square.query2(point);
+                assert_eq!(cell.feature_point, math::to_f64_2(cell.cell));
+
+                // The seed point has to be the nearest of the hexagon centres.
+                let cell = hexagonal.query2(point);
+                assert_eq!(
+                    cell.feature_point,
+                    lattice_center(Lattice::Hexagonal, math::to_f64_2(cell.cell))
+                );
+                for row in -6..6 {
+                    for column in -6..6 {
+                        let center =
+                            lattice_center(Lattice::Hexagonal, [column as f64, row as f64]);
+                        assert!(cell.distance <= range_euclidean(&point, &center) + 1e-12);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_periodic_noise_tiles() {
+        // The period is counted in cells, so it's divided by the frequency.
+        let worley = Worley::new().set_frequency(2.0).set_period([6; 4]);
+        assert_tiles(&worley, [3.0; 2]);
+        assert_tiles(&worley, [3.0; 3]);
+
+        // On the hexagonal lattice, the rows are closer together than the
+        // columns, and the period along y is even so that the shifted rows
+        // line up.
+        let hexagonal = worley
+            .set_lattice(Lattice::Hexagonal)
+            .set_period([6, 4, 6, 6]);
+        let row_height = 3.0_f64.sqrt() / 2.0;
+        assert_tiles(&hexagonal, [3.0, 2.0 * row_height]);
+        assert_tiles(&hexagonal, [3.0, 2.0 * row_height, 3.0]);
+    }
+
+    #[test]
+    fn test_odd_hexagonal_period() {
+        // An odd period along y is rounded up on the hexagonal lattice, however
+        // the lattice and period are set.
+        let even = Worley::new()
+            .set_lattice(Lattice::Hexagonal)
+            .set_period([4, 4, 0, 0]);
+        let odd = Worley::new()
+            .set_period([4, 3, 0, 0])
+            .set_lattice(Lattice::Hexagonal);
+        assert_eq!(odd.period(), [4, 3, 0, 0]);
+
+        for i in 0..32 {
+            let t = f64::from(i) * 0.37;
+            let point = [t, 5.0 - t * 0.6];
+            assert_eq!(odd.get(point), even.get(point));
+        }
+    }
+
+    #[test]
+    fn test_points_per_cell() {
+        let distance = Worley::new().set_return_type(ReturnType::Distance);
+        let more_points = distance.set_points_per_cell(3);
+
+        // The first point of each cell stays in place, so more points can only
+        // bring the nearest one closer.
+        for x in 0..16 {
+            for y in 0..16 {
+                for z in 0..4 {
+                    let point = [x as f64 * 0.23 - 2.0, y as f64 * 0.29 - 2.0, z as f64 * 0.7];
+                    assert!(more_points.get(point) <= distance.get(point));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_partial_jitter() {
+        let full = Worley::new().set_points_per_cell(2);
+        let half = full.set_jitter(0.5);
+
+        for x in 0..16 {
+            for y in 0..16 {
+                // The seed points move halfway towards the centres of their
+                // cells.
+                let cell = [x - 8, y - 8];
+                for number in 0..2 {
+                    let center = math::to_f64_2(cell);
+                    let offset = math::sub2(full.seed_point2(cell, number).position, center);
+                    let position = half.seed_point2(cell, number).position;
+                    let expected = math::add2(center, math::mul2(offset, 0.5));
+                    assert!(range_euclidean(&position, &expected) < 1e-12);
+                }
+
+                // The nearest of them is still found.
+                let point = [x as f64 * 0.23 - 2.0, y as f64 * 0.29 - 2.0];
+                let nearest = half.query2(point);
+                for i in -3..3 {
+                    for j in -3..3 {
+                        for number in 0..2 {
+                            let seed = half.seed_point2([i, j], number);
+                            assert!(nearest.distance <= range_euclidean(&point, &seed.position));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_extra_points_differ_between_cells() {
+        let worley = Worley::new().set_points_per_cell(2);
+
+        // Cells with the same index from the permutation table share their
+        // first seed point, but not the others.
+        let mut pairs = 0;
+        for x in 0..64 {
+            for y in 0..64 {
+                let (a, b) = ([0, 0], [x, y]);
+                if a == b || worley.perm_table.get2(a) != worley.perm_table.get2(b) {
+                    continue;
+                }
+
+                pairs += 1;
+                assert_ne!(
+                    worley.seed_point2(a, 1).index,
+                    worley.seed_point2(b, 1).index
+                );
+            }
+        }
+        assert!(pairs > 0);
+    }
+
+    #[test]
+    fn test_minkowski_exponent_is_clamped() {
+        let manhattan = Worley::new()
+            .set_range_function(RangeFunction::Manhattan)
+            .set_return_type(ReturnType::Distance2Sub);
+
+        for &p in &[0.5, 0.0, -1.0, f64::NAN] {
+            let minkowski = manhattan.set_range_function(RangeFunction::Minkowski(p));
+            for x in 0..16 {
+                let point = [x as f64 * 0.31 - 2.0, 0.5, 0.25];
+                assert!((minkowski.get(point) - manhattan.get(point)).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_enable_range() {
+        let worley = Worley::new();
+        assert_eq!(worley.enable_range(true).return_type, ReturnType::Distance);
+        assert_eq!(worley.enable_range(false).return_type, ReturnType::Value);
+
+        // Setting the field directly still works, as it did before there were
+        // return types.
+        let mut legacy = Worley::new();
+        legacy.enable_range = true;
+        let distance = Worley::new().set_return_type(ReturnType::Distance);
+        for x in 0..16 {
+            let point = [x as f64 * 0.31 - 2.0, 0.5, 0.25];
+            assert_eq!(legacy.get(point), distance.get(point));
+        }
+    }
+}
@@ -1,17 +1,31 @@
 pub use self::cache::*;
 pub use self::combiners::*;
+pub use self::curl::*;
+pub use self::finite_difference::*;
 pub use self::generators::*;
 pub use self::modifiers::*;
+pub use self::noise_fn_ext::*;
+pub use self::operators::*;
 pub use self::selectors::*;
+pub use self::sync_cache::*;
 pub use self::transformers::*;
 
 mod cache;
 mod combiners;
+mod curl;
+mod finite_difference;
 mod generators;
 mod modifiers;
+mod noise_fn_ext;
+mod operators;
 mod selectors;
+mod sync_cache;
 mod transformers;
 
+use math;
+use std::rc::Rc;
+use std::sync::Arc;
+
 /// Base trait for noise functions.
 ///
 /// A noise function is a object that calculates and outputs a value given a
@@ -21,26 +35,617 @@ mod transformers;
 /// value. Some of these methods include:
 ///
 /// * Calculating a value using a coherent-noise function or some other
-///     mathematical function.
+///   mathematical function.
 /// * Mathematically changing the output value from another noise function
-///     in various ways.
+///   in various ways.
 /// * Combining the output values from two noise functions in various ways.
 pub trait NoiseFn<T> {
     fn get(&self, point: T) -> f64;
+
+    /// Returns the value at the point without any detail too fine to be
+    /// represented by samples spaced `filter_width` apart, to avoid aliasing.
+    ///
+    /// The fractals drop or fade out octaves that are too high in frequency,
+    /// and noise functions built from other noise functions pass the filter
+    /// width on to them, scaled to match any change to the input point. Other
+    /// noise functions return the same value as `get`, as does a filter width
+    /// of zero or less.
+    fn get_filtered(&self, point: T, _filter_width: f64) -> f64 {
+        self.get(point)
+    }
 }
 
-impl<'a, T, M: NoiseFn<T>> NoiseFn<T> for &'a M {
+impl<T, M: NoiseFn<T> + ?Sized> NoiseFn<T> for &M {
     #[inline]
     fn get(&self, point: T) -> f64 {
         M::get(*self, point)
     }
+
+    #[inline]
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        M::get_filtered(*self, point, filter_width)
+    }
+}
+
+/// Boxed noise functions, such as `Box<dyn NoiseFn<T> + Send + Sync>`, let
+/// graphs be built from sources whose types are only known at runtime.
+impl<T, M: NoiseFn<T> + ?Sized> NoiseFn<T> for Box<M> {
+    #[inline]
+    fn get(&self, point: T) -> f64 {
+        M::get(self, point)
+    }
+
+    #[inline]
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        M::get_filtered(self, point, filter_width)
+    }
+}
+
+/// Shared noise functions can be used by several parts of a graph, or by
+/// several threads.
+impl<T, M: NoiseFn<T> + ?Sized> NoiseFn<T> for Arc<M> {
+    #[inline]
+    fn get(&self, point: T) -> f64 {
+        M::get(self, point)
+    }
+
+    #[inline]
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        M::get_filtered(self, point, filter_width)
+    }
+}
+
+impl<T, M: NoiseFn<T> + ?Sized> NoiseFn<T> for Rc<M> {
+    #[inline]
+    fn get(&self, point: T) -> f64 {
+        M::get(self, point)
+    }
+
+    #[inline]
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        M::get_filtered(self, point, filter_width)
+    }
+}
+
+/// Trait for noise functions that can calculate the gradient of their output
+/// along with the output itself.
+///
+/// This is cheaper and more accurate than sampling the noise function several
+/// times and taking finite differences, which makes it useful for things like
+/// terrain normals and slopes.
+pub trait NoiseFnWithGradient<T>: NoiseFn<T> {
+    /// Returns the output value at the point, and its partial derivatives
+    /// along each axis.
+    fn get_with_gradient(&self, point: T) -> (f64, T);
+
+    /// Returns the output value and the gradient at the point, without any
+    /// detail too fine to be represented by samples spaced `filter_width`
+    /// apart. See `NoiseFn::get_filtered`.
+    fn get_filtered_with_gradient(&self, point: T, _filter_width: f64) -> (f64, T) {
+        self.get_with_gradient(point)
+    }
+}
+
+impl<T, M: NoiseFnWithGradient<T> + ?Sized> NoiseFnWithGradient<T> for &M {
+    #[inline]
+    fn get_with_gradient(&self, point: T) -> (f64, T) {
+        M::get_with_gradient(*self, point)
+    }
+
+    #[inline]
+    fn get_filtered_with_gradient(&self, point: T, filter_width: f64) -> (f64, T) {
+        M::get_filtered_with_gradient(*self, point, filter_width)
+    }
+}
+
+impl<T, M: NoiseFnWithGradient<T> + ?Sized> NoiseFnWithGradient<T> for Box<M> {
+    #[inline]
+    fn get_with_gradient(&self, point: T) -> (f64, T) {
+        M::get_with_gradient(self, point)
+    }
+
+    #[inline]
+    fn get_filtered_with_gradient(&self, point: T, filter_width: f64) -> (f64, T) {
+        M::get_filtered_with_gradient(self, point, filter_width)
+    }
+}
+
+impl<T, M: NoiseFnWithGradient<T> + ?Sized> NoiseFnWithGradient<T> for Arc<M> {
+    #[inline]
+    fn get_with_gradient(&self, point: T) -> (f64, T) {
+        M::get_with_gradient(self, point)
+    }
+
+    #[inline]
+    fn get_filtered_with_gradient(&self, point: T, filter_width: f64) -> (f64, T) {
+        M::get_filtered_with_gradient(self, point, filter_width)
+    }
+}
+
+impl<T, M: NoiseFnWithGradient<T> + ?Sized> NoiseFnWithGradient<T> for Rc<M> {
+    #[inline]
+    fn get_with_gradient(&self, point: T) -> (f64, T) {
+        M::get_with_gradient(self, point)
+    }
+
+    #[inline]
+    fn get_filtered_with_gradient(&self, point: T, filter_width: f64) -> (f64, T) {
+        M::get_filtered_with_gradient(self, point, filter_width)
+    }
 }
 
 /// Trait for functions that require a seed before generating their values
+///
+/// Seeds are 32-bit internally, so the 64-bit, byte and string seeds are
+/// reduced to a `u32`, and different values of them can give the same seed.
 pub trait Seedable {
     /// Set the seed for the function implementing the `Seedable` trait
     fn set_seed(self, seed: u32) -> Self;
 
     /// Getter to retrieve the seed from the function
     fn seed(&self) -> u32;
+
+    /// Set the seed from a 64-bit value.
+    ///
+    /// Values that fit in a `u32` are used as they are, so they give the same
+    /// output as passing them to `set_seed`. Larger values are hashed with the
+    /// SplitMix64 finalizer, and the high 32 bits of the hash are used as the
+    /// seed, so every bit of the value affects the seed.
+    fn set_seed_u64(self, seed: u64) -> Self
+    where
+        Self: Sized,
+    {
+        if seed <= u64::from(u32::MAX) {
+            self.set_seed(seed as u32)
+        } else {
+            self.set_seed((math::split_mix64(seed) >> 32) as u32)
+        }
+    }
+
+    /// Set the seed from a string of bytes.
+    ///
+    /// The bytes are hashed with 64-bit FNV-1a, and the hash is passed to
+    /// `set_seed_u64`. This derivation is stable across versions and
+    /// platforms.
+    fn set_seed_bytes(self, seed: &[u8]) -> Self
+    where
+        Self: Sized,
+    {
+        let hash = seed.iter().fold(0xcbf2_9ce4_8422_2325, |hash: u64, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        self.set_seed_u64(hash)
+    }
+
+    /// Set the seed from a string, using its UTF-8 bytes as `set_seed_bytes`
+    /// does.
+    fn set_seed_str(self, seed: &str) -> Self
+    where
+        Self: Sized,
+    {
+        self.set_seed_bytes(seed.as_bytes())
+    }
+}
+
+/// Derives the seed of a child of a composite noise function from the seed of
+/// the composite, where `child` is the position of the child among its
+/// siblings.
+///
+/// Composites with several seedable children use this to give each child a
+/// distinct seed, so that setting the seed of the root of a graph of noise
+/// functions reseeds the whole graph. The seeds are mixed, so they don't
+/// overlap with the consecutive seeds that fractals give their octaves.
+pub fn derive_seed(seed: u32, child: u32) -> u32 {
+    // Keep the well-mixed high bits of the hash.
+    let x = ((u64::from(seed) << 32) | u64::from(child)).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    (math::split_mix64(x) >> 32) as u32
+}
+
+/// Returns the filter width to sample a function at `warped_point` with, when
+/// it's the image of `point` under `warp`: the furthest that points
+/// `filter_width` away from `point` along each axis end up from it.
+fn warped_filter_width<F, const N: usize>(
+    warp: F,
+    point: [f64; N],
+    warped_point: [f64; N],
+    filter_width: f64,
+) -> f64
+where
+    F: Fn([f64; N]) -> [f64; N],
+{
+    if filter_width <= 0.0 {
+        return filter_width;
+    }
+
+    let mut warped_filter_width = 0.0_f64;
+    for axis in 0..N {
+        let mut neighbour = point;
+        neighbour[axis] += filter_width;
+
+        let distance = warp(neighbour)
+            .iter()
+            .zip(warped_point.iter())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f64>()
+            .sqrt();
+        warped_filter_width = warped_filter_width.max(distance);
+    }
+
+    warped_filter_width
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn assert_gradient_matches<F, const N: usize>(noise_fn: &F)
+    where
+        F: NoiseFnWithGradient<[f64; N]>,
+    {
+        const STEP: f64 = 1e-6;
+
+        for i in 0..2048 {
+            let t = f64::from(i) * 0.0137 - 10.0;
+            let mut point = [0.0; N];
+            for (axis, coordinate) in point.iter_mut().enumerate() {
+                *coordinate = t * (1.0 - 0.61 * axis as f64) + 0.37 * axis as f64;
+            }
+
+            let (value, gradient) = noise_fn.get_with_gradient(point);
+            assert_eq!(value, noise_fn.get(point));
+
+            for axis in 0..N {
+                let (mut high, mut low) = (point, point);
+                high[axis] += STEP;
+                low[axis] -= STEP;
+                let difference = (noise_fn.get(high) - noise_fn.get(low)) / (2.0 * STEP);
+
+                assert!(
+                    (gradient[axis] - difference).abs() < 1e-5,
+                    "axis {} at {:?}: {} != {}",
+                    axis,
+                    point,
+                    gradient[axis],
+                    difference
+                );
+            }
+        }
+    }
+
+    pub(crate) fn assert_gradients_match<F>(noise_fn: &F)
+    where
+        F: NoiseFnWithGradient<[f64; 2]>
+            + NoiseFnWithGradient<[f64; 3]>
+            + NoiseFnWithGradient<[f64; 4]>,
+    {
+        assert_gradient_matches::<F, 2>(noise_fn);
+        assert_gradient_matches::<F, 3>(noise_fn);
+        assert_gradient_matches::<F, 4>(noise_fn);
+    }
+
+    pub(crate) fn assert_tiles<F, const N: usize>(noise_fn: &F, period: [f64; N])
+    where
+        F: NoiseFn<[f64; N]>,
+    {
+        for i in 0..256 {
+            let t = f64::from(i) * 0.0737 - 10.0;
+            let mut point = [0.0; N];
+            for (axis, coordinate) in point.iter_mut().enumerate() {
+                *coordinate = t * (1.0 - 0.61 * axis as f64) + 0.37 * axis as f64;
+            }
+
+            let value = noise_fn.get(point);
+            for axis in 0..N {
+                let mut shifted = point;
+                shifted[axis] += period[axis];
+                assert!((noise_fn.get(shifted) - value).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_seed_derivations() {
+        let perlin = Perlin::new();
+
+        // Seeds that fit in a u32 are unchanged.
+        for &seed in &[0, 1, 0x89ab_cdef, u32::MAX] {
+            assert_eq!(perlin.set_seed_u64(u64::from(seed)).seed(), seed);
+            assert_eq!(
+                perlin.set_seed_u64(u64::from(seed)).get([0.3, 0.7]),
+                perlin.set_seed(seed).get([0.3, 0.7])
+            );
+        }
+
+        // The high and low halves of the value both change the seed, and
+        // values that differ in the same bits of each half don't collide.
+        let seeds = [0, 3, 1 << 32, 3 << 32, 0x0000_0001_0000_0001, u64::MAX];
+        for (i, &a) in seeds.iter().enumerate() {
+            for &b in &seeds[i + 1..] {
+                assert_ne!(perlin.set_seed_u64(a).seed(), perlin.set_seed_u64(b).seed());
+            }
+        }
+        assert_eq!(
+            perlin.set_seed_u64(0x0123_4567_89ab_cdef).seed(),
+            (math::split_mix64(0x0123_4567_89ab_cdef) >> 32) as u32
+        );
+
+        // FNV-1a of "a" is 0xaf63dc4c8601ec8c.
+        assert_eq!(
+            perlin.set_seed_str("a").seed(),
+            perlin.set_seed_u64(0xaf63_dc4c_8601_ec8c).seed()
+        );
+        assert_eq!(
+            perlin.set_seed_bytes(b"").seed(),
+            perlin.set_seed_u64(0xcbf2_9ce4_8422_2325).seed()
+        );
+        assert_eq!(
+            Fbm::new().set_seed_str("world").seed(),
+            perlin.set_seed_bytes(b"world").seed()
+        );
+    }
+
+    #[test]
+    fn test_seed_propagates_to_children() {
+        let displace = Displace::new(
+            ScalePoint::new(Cache::new(Perlin::new())),
+            Perlin::new(),
+            Fbm::new(),
+            Constant::new(0.0),
+            Constant::new(0.0),
+        )
+        .set_seed(9);
+
+        assert_eq!(displace.seed(), 9);
+        assert_eq!(displace.source.seed(), derive_seed(9, 0));
+        assert_eq!(displace.source.source.source.seed(), derive_seed(9, 0));
+        assert_eq!(displace.x_displace.seed(), derive_seed(9, 1));
+        assert_eq!(displace.y_displace.seed(), derive_seed(9, 2));
+
+        // Seeds near the top of the range wrap around instead of overflowing.
+        // Turbulence only reseeds its distortion functions, not its source.
+        let turbulence = Turbulence::new(Perlin::new()).set_seed(u32::MAX);
+        assert_eq!(turbulence.seed(), u32::MAX);
+        assert_eq!(turbulence.source.seed(), Perlin::DEFAULT_SEED);
+        assert_eq!(Fbm::new().set_seed(u32::MAX).seed(), u32::MAX);
+
+        // Sibling seeds shouldn't collide with each other, or with the octaves
+        // of a fractal seeded by one of them.
+        let seeds: Vec<_> = (0..5).map(|child| derive_seed(9, child)).collect();
+        for (i, &seed) in seeds.iter().enumerate() {
+            for &other in &seeds[i + 1..] {
+                assert!(seed.wrapping_sub(other) > 32 && other.wrapping_sub(seed) > 32);
+            }
+        }
+    }
+
+    #[test]
+    fn test_seed_propagates_through_owned_graph() {
+        fn build_graph() -> Select<Add<Perlin, ScaleBias<Fbm>>, Constant, Abs<Perlin>> {
+            let hills = Add::new(Perlin::new(), ScaleBias::new(Fbm::new()).set_scale(0.5));
+            Select::new(hills, Constant::new(0.0), Abs::new(Perlin::new()))
+        }
+
+        let graph = build_graph().set_seed(3);
+        let hills_seed = derive_seed(3, 0);
+        assert_eq!(graph.seed(), 3);
+        assert_eq!(graph.source1.source1.seed(), derive_seed(hills_seed, 0));
+        assert_eq!(graph.source1.source2.seed(), derive_seed(hills_seed, 1));
+        assert_eq!(graph.control.seed(), derive_seed(3, 2));
+
+        // Every operator keeps its own seed, and operators with one source
+        // pass it on unchanged.
+        assert_eq!(
+            graph.source1.source2.source.seed(),
+            derive_seed(hills_seed, 1)
+        );
+        assert_eq!(graph.control.source.seed(), derive_seed(3, 2));
+        assert_eq!(Abs::new(Perlin::new().set_seed(5)).seed(), 0);
+        assert_eq!(Add::new(Perlin::new(), Perlin::new().set_seed(5)).seed(), 0);
+
+        // Borrowed sources still work, through the impl for references.
+        let perlin = Perlin::new().set_seed(derive_seed(3, 2));
+        let borrowed = Abs::new(&perlin);
+        for i in 0..16 {
+            let point = [f64::from(i) * 0.37, 0.5];
+            assert_eq!(borrowed.get(point), graph.control.get(point));
+        }
+    }
+
+    #[test]
+    fn test_chaining_in_any_dimension() {
+        // Functions that can only be sampled in two or four dimensions chain
+        // the same way, as do trait objects.
+        let ramp = NoiseFnFromClosure::new(|point: [f64; 2]| point[0]);
+        let chained = ramp.abs_output().scale_bias(0.5, 0.25);
+        assert_eq!(chained.get([-0.5, 3.0]), 0.5);
+
+        let boxed: Box<dyn NoiseFn<[f64; 4]>> = Box::new(Constant::new(-0.5));
+        assert_eq!(
+            boxed.invert().add_noise(Constant::new(0.25)).get([0.0; 4]),
+            0.75
+        );
+    }
+
+    #[test]
+    fn test_chained_graph_matches_nested() {
+        let fbm = Fbm::new().set_seed(4);
+        let chained = (&fbm)
+            .abs_output()
+            .scale_bias(0.5, -0.25)
+            .clamp_output(-0.5, 0.5)
+            .add_noise(Perlin::new())
+            .scale_point(2.0)
+            .turbulence()
+            .set_power(0.25)
+            .cached();
+        let nested = Cache::new(
+            Turbulence::new(
+                ScalePoint::new(Add::new(
+                    Clamp::new(
+                        ScaleBias::new(Abs::new(&fbm))
+                            .set_scale(0.5)
+                            .set_bias(-0.25),
+                    )
+                    .set_bounds(-0.5, 0.5),
+                    Perlin::new(),
+                ))
+                .set_scale(2.0),
+            )
+            .set_power(0.25),
+        );
+
+        for i in 0..32 {
+            let point = [f64::from(i) * 0.37, 0.5, -0.25];
+            assert_eq!(chained.get(point), nested.get(point));
+        }
+    }
+
+    #[test]
+    fn test_filtering_passes_through_wrappers() {
+        let fbm = Fbm::new().set_seed(4);
+        let filter_width = 1.0 / 16.0;
+
+        let pass_through = Noise::new(&fbm)
+            .scale_bias(1.0, 0.0)
+            .translate_point(0.0)
+            .add_noise(Constant::new(0.0))
+            .cached()
+            .sync_cached();
+        let scaled = (&fbm).scale_point(2.0);
+
+        for i in 0..32 {
+            let point = [f64::from(i) * 0.37, 0.5, -0.25];
+            let filtered = fbm.get_filtered(point, filter_width);
+            assert_ne!(filtered, fbm.get(point));
+
+            assert_eq!(pass_through.get_filtered(point, filter_width), filtered);
+            assert_eq!(pass_through.get(point), fbm.get(point));
+
+            // Scaling the point spreads the samples out by the same factor.
+            let scaled_point = [point[0] * 2.0, point[1] * 2.0, point[2] * 2.0];
+            assert_eq!(
+                scaled.get_filtered(point, filter_width),
+                fbm.get_filtered(scaled_point, filter_width * 2.0)
+            );
+        }
+    }
+
+    #[test]
+    fn test_closures_slot_into_graphs() {
+        use utils::{NoiseMapBuilder, PlaneMapBuilder};
+
+        let ramp = NoiseFnFromClosure::new(|point: [f64; 3]| point[0] * 0.5);
+        let graph = Add::new(Perlin::new(), ramp)
+            .map_output(|value| value * value)
+            .zip_with(Fbm::new(), f64::max)
+            .set_seed(5);
+
+        let fbm = Fbm::new().set_seed(derive_seed(5, 1));
+        let perlin = Perlin::new().set_seed(derive_seed(derive_seed(5, 0), 0));
+        let expected = |point: [f64; 3]| {
+            let value = perlin.get(point) + point[0] * 0.5;
+            (value * value).max(fbm.get(point))
+        };
+
+        let noise_map = PlaneMapBuilder::new(&graph).set_size(8, 8).build();
+        let reference = PlaneMapBuilder::new(&NoiseFnFromClosure::new(expected))
+            .set_size(8, 8)
+            .build();
+        for x in 0..8 {
+            for y in 0..8 {
+                assert_eq!(noise_map.get_value(x, y), reference.get_value(x, y));
+            }
+        }
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_noise_fns_are_send_sync() {
+        assert_send_sync::<Checkerboard>();
+        assert_send_sync::<Constant>();
+        assert_send_sync::<Cylinders>();
+        assert_send_sync::<DomainWarp>();
+        assert_send_sync::<Gabor>();
+        assert_send_sync::<OpenSimplex>();
+        assert_send_sync::<Perlin>();
+        assert_send_sync::<Simplex>();
+        assert_send_sync::<Spheres>();
+        assert_send_sync::<SuperSimplex>();
+        assert_send_sync::<Value>();
+        assert_send_sync::<Worley>();
+
+        assert_send_sync::<BasicMulti>();
+        assert_send_sync::<Billow>();
+        assert_send_sync::<DerivativeFbm>();
+        assert_send_sync::<Fbm>();
+        assert_send_sync::<HybridMulti>();
+        assert_send_sync::<JordanTurbulence>();
+        assert_send_sync::<RidgedMulti>();
+        assert_send_sync::<SwissTurbulence>();
+
+        assert_send_sync::<Displace<Perlin, Perlin, Perlin, Perlin, Perlin>>();
+        assert_send_sync::<RotatePoint<Perlin>>();
+        assert_send_sync::<ScalePoint<Perlin>>();
+        assert_send_sync::<TranslatePoint<Perlin>>();
+        assert_send_sync::<Turbulence<Perlin>>();
+
+        assert_send_sync::<Add<Perlin, Perlin>>();
+        assert_send_sync::<Max<Perlin, Perlin>>();
+        assert_send_sync::<Min<Perlin, Perlin>>();
+        assert_send_sync::<Multiply<Perlin, Perlin>>();
+        assert_send_sync::<Power<Perlin, Perlin>>();
+
+        assert_send_sync::<Abs<Perlin>>();
+        assert_send_sync::<Clamp<Perlin>>();
+        assert_send_sync::<Curve<Perlin>>();
+        assert_send_sync::<Exponent<Perlin>>();
+        assert_send_sync::<Invert<Perlin>>();
+        assert_send_sync::<ScaleBias<Perlin>>();
+        assert_send_sync::<Terrace<Perlin>>();
+
+        assert_send_sync::<Blend<Perlin, Perlin, Perlin>>();
+        assert_send_sync::<Select<Perlin, Perlin, Perlin>>();
+
+        assert_send_sync::<Curl<Perlin>>();
+        assert_send_sync::<FiniteDifference<Perlin>>();
+        assert_send_sync::<SyncCache<Perlin>>();
+        assert_send_sync::<Noise<Perlin>>();
+        assert_send_sync::<Map<Perlin, fn(f64) -> f64>>();
+        assert_send_sync::<ZipWith<Perlin, Perlin, fn(f64, f64) -> f64>>();
+        assert_send_sync::<NoiseFnFromClosure<fn([f64; 3]) -> f64>>();
+
+        assert_send_sync::<Box<dyn NoiseFn<[f64; 2]> + Send + Sync>>();
+        assert_send_sync::<Arc<dyn NoiseFn<[f64; 3]> + Send + Sync>>();
+        assert_send_sync::<Add<&Perlin, Box<dyn NoiseFn<[f64; 2]> + Send + Sync>>>();
+    }
+
+    #[test]
+    fn test_shared_between_threads() {
+        let graph: Arc<dyn NoiseFn<[f64; 2]> + Send + Sync> =
+            Arc::new(SyncCache::new(Add::new(Perlin::new(), Fbm::new())));
+        let expected: Vec<_> = (0..64)
+            .map(|i| graph.get([f64::from(i) * 0.37, 0.5]))
+            .collect();
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let graph = Arc::clone(&graph);
+                std::thread::spawn(move || {
+                    (0..64)
+                        .map(|i| graph.get([f64::from(i) * 0.37, 0.5]))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), expected);
+        }
+
+        // Boxed and reference-counted functions can be used as sources too.
+        let boxed: Box<dyn NoiseFn<[f64; 2]>> = Box::new(Perlin::new());
+        let scaled = ScaleBias::new(Rc::new(boxed)).set_scale(2.0);
+        assert_eq!(scaled.get([0.3, 0.7]), 2.0 * Perlin::new().get([0.3, 0.7]));
+    }
 }
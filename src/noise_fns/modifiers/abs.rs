@@ -1,20 +1,46 @@
-use noise_fns::NoiseFn;
+use noise_fns::{NoiseFn, Seedable};
 
 /// Noise function that outputs the absolute value of the output value from the
 /// source function.
-pub struct Abs<'a, T: 'a> {
+#[derive(Clone, Debug)]
+pub struct Abs<Source> {
     /// Outputs a value.
-    pub source: &'a dyn NoiseFn<T>,
+    pub source: Source,
+
+    seed: u32,
+}
+
+impl<Source> Abs<Source> {
+    pub fn new(source: Source) -> Self {
+        Self { source, seed: 0 }
+    }
 }
 
-impl<'a, T> Abs<'a, T> {
-    pub fn new(source: &'a dyn NoiseFn<T>) -> Self {
-        Self { source }
+impl<Source> Seedable for Abs<Source>
+where
+    Source: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source: self.source.set_seed(seed),
+            seed,
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
     }
 }
 
-impl<'a, T> NoiseFn<T> for Abs<'a, T> {
+impl<T, Source> NoiseFn<T> for Abs<Source>
+where
+    Source: NoiseFn<T>,
+{
     fn get(&self, point: T) -> f64 {
-        (self.source.get(point)).abs()
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        (self.source.get_filtered(point, filter_width)).abs()
     }
 }
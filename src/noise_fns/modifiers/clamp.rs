@@ -1,21 +1,25 @@
 use math;
-use noise_fns::NoiseFn;
+use noise_fns::{NoiseFn, Seedable};
 
 /// Noise function that clamps the output value from the source function to a
 /// range of values.
-pub struct Clamp<'a, T: 'a> {
+#[derive(Clone, Debug)]
+pub struct Clamp<Source> {
     /// Outputs a value.
-    pub source: &'a dyn NoiseFn<T>,
+    pub source: Source,
 
     /// Bound of the clamping range. Default is -1.0 to 1.0.
     pub bounds: (f64, f64),
+
+    seed: u32,
 }
 
-impl<'a, T> Clamp<'a, T> {
-    pub fn new(source: &'a dyn NoiseFn<T>) -> Self {
+impl<Source> Clamp<Source> {
+    pub fn new(source: Source) -> Self {
         Self {
             source,
             bounds: (-1.0, 1.0),
+            seed: 0,
         }
     }
 
@@ -41,9 +45,33 @@ impl<'a, T> Clamp<'a, T> {
     }
 }
 
-impl<'a, T> NoiseFn<T> for Clamp<'a, T> {
+impl<Source> Seedable for Clamp<Source>
+where
+    Source: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source: self.source.set_seed(seed),
+            seed,
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+impl<T, Source> NoiseFn<T> for Clamp<Source>
+where
+    Source: NoiseFn<T>,
+{
     fn get(&self, point: T) -> f64 {
-        let value = self.source.get(point);
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        let value = self.source.get_filtered(point, filter_width);
 
         math::clamp(value, self.bounds.0, self.bounds.1)
     }
@@ -1,7 +1,6 @@
 use math;
 use math::interpolate;
-use noise_fns::NoiseFn;
-use std;
+use noise_fns::{NoiseFn, Seedable};
 
 /// Noise function that maps the output value from the source function onto an
 /// arbitrary function curve.
@@ -17,24 +16,29 @@ use std;
 /// four control points to the curve. If there is less than four control
 /// points, the get() method panics. Each control point can have any input
 /// and output value, although no two control points can have the same input.
-pub struct Curve<'a, T: 'a> {
+#[derive(Clone, Debug)]
+pub struct Curve<Source> {
     /// Outputs a value.
-    pub source: &'a dyn NoiseFn<T>,
+    pub source: Source,
 
     /// Vec that stores the control points.
     control_points: Vec<ControlPoint<f64>>,
+
+    seed: u32,
 }
 
+#[derive(Clone, Debug)]
 struct ControlPoint<T> {
     input: T,
     output: T,
 }
 
-impl<'a, T> Curve<'a, T> {
-    pub fn new(source: &'a dyn NoiseFn<T>) -> Self {
+impl<Source> Curve<Source> {
+    pub fn new(source: Source) -> Self {
         Self {
             source,
             control_points: Vec::with_capacity(4),
+            seed: 0,
         }
     }
 
@@ -43,7 +47,7 @@ impl<'a, T> Curve<'a, T> {
         if !self
             .control_points
             .iter()
-            .any(|x| (x.input - input_value).abs() < std::f64::EPSILON)
+            .any(|x| (x.input - input_value).abs() < f64::EPSILON)
         {
             // it doesn't, so find the correct position to insert the new
             // control point.
@@ -51,7 +55,7 @@ impl<'a, T> Curve<'a, T> {
                 .control_points
                 .iter()
                 .position(|x| x.input >= input_value)
-                .unwrap_or_else(|| self.control_points.len());
+                .unwrap_or(self.control_points.len());
 
             // add the new control point at the correct position.
             self.control_points.insert(
@@ -67,13 +71,37 @@ impl<'a, T> Curve<'a, T> {
     }
 }
 
-impl<'a, T> NoiseFn<T> for Curve<'a, T> {
+impl<Source> Seedable for Curve<Source>
+where
+    Source: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source: self.source.set_seed(seed),
+            seed,
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+impl<T, Source> NoiseFn<T> for Curve<Source>
+where
+    Source: NoiseFn<T>,
+{
     fn get(&self, point: T) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
         // confirm that there's at least 4 control points in the vector.
         assert!(self.control_points.len() >= 4);
 
         // get output value from the source function
-        let source_value = self.source.get(point);
+        let source_value = self.source.get_filtered(point, filter_width);
 
         // Find the first element in the control point array that has a input
         // value larger than the output value from the source function
@@ -81,7 +109,7 @@ impl<'a, T> NoiseFn<T> for Curve<'a, T> {
             .control_points
             .iter()
             .position(|x| x.input > source_value)
-            .unwrap_or_else(|| self.control_points.len());
+            .unwrap_or(self.control_points.len());
 
         if index_pos < 2 {
             println!(
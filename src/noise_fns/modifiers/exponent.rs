@@ -1,4 +1,4 @@
-use noise_fns::NoiseFn;
+use noise_fns::{NoiseFn, Seedable};
 
 use math::scale_shift;
 
@@ -9,20 +9,24 @@ use math::scale_shift;
 /// this noise function first normalizes the output value (the range becomes 0.0
 /// to 1.0), maps that value onto an exponential curve, then rescales that
 /// value back to the original range.
-pub struct Exponent<'a, T: 'a> {
+#[derive(Clone, Debug)]
+pub struct Exponent<Source> {
     /// Outputs a value.
-    pub source: &'a dyn NoiseFn<T>,
+    pub source: Source,
 
     /// Exponent to apply to the output value from the source function. Default
     /// is 1.0.
     pub exponent: f64,
+
+    seed: u32,
 }
 
-impl<'a, T> Exponent<'a, T> {
-    pub fn new(source: &'a dyn NoiseFn<T>) -> Self {
+impl<Source> Exponent<Source> {
+    pub fn new(source: Source) -> Self {
         Self {
             source,
             exponent: 1.0,
+            seed: 0,
         }
     }
 
@@ -31,9 +35,33 @@ impl<'a, T> Exponent<'a, T> {
     }
 }
 
-impl<'a, T> NoiseFn<T> for Exponent<'a, T> {
+impl<Source> Seedable for Exponent<Source>
+where
+    Source: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source: self.source.set_seed(seed),
+            seed,
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+impl<T, Source> NoiseFn<T> for Exponent<Source>
+where
+    Source: NoiseFn<T>,
+{
     fn get(&self, point: T) -> f64 {
-        let mut value = self.source.get(point);
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        let mut value = self.source.get_filtered(point, filter_width);
         value = (value + 1.0) / 2.0;
         value = value.abs();
         value = value.powf(self.exponent);
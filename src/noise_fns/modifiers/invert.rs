@@ -1,19 +1,45 @@
-use noise_fns::NoiseFn;
+use noise_fns::{NoiseFn, Seedable};
 
 /// Noise function that inverts the output value from the source function.
-pub struct Invert<'a, T: 'a> {
+#[derive(Clone, Debug)]
+pub struct Invert<Source> {
     /// Outputs a value.
-    pub source: &'a dyn NoiseFn<T>,
+    pub source: Source,
+
+    seed: u32,
+}
+
+impl<Source> Invert<Source> {
+    pub fn new(source: Source) -> Self {
+        Invert { source, seed: 0 }
+    }
 }
 
-impl<'a, T> Invert<'a, T> {
-    pub fn new(source: &'a dyn NoiseFn<T>) -> Self {
-        Invert { source }
+impl<Source> Seedable for Invert<Source>
+where
+    Source: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source: self.source.set_seed(seed),
+            seed,
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
     }
 }
 
-impl<'a, T> NoiseFn<T> for Invert<'a, T> {
+impl<T, Source> NoiseFn<T> for Invert<Source>
+where
+    Source: NoiseFn<T>,
+{
     fn get(&self, point: T) -> f64 {
-        -self.source.get(point)
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        -self.source.get_filtered(point, filter_width)
     }
 }
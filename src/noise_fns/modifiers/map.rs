@@ -0,0 +1,69 @@
+use noise_fns::{NoiseFn, Seedable};
+use std::fmt;
+
+/// Noise function that applies a closure to the output value from the source
+/// function.
+///
+/// This covers any modifier that doesn't have a type of its own, such as a
+/// custom transfer curve or a remapping into another range.
+#[derive(Clone)]
+pub struct Map<Source, F> {
+    /// Outputs a value.
+    pub source: Source,
+
+    /// Maps the output value from the source function to the output value of
+    /// this function.
+    pub map: F,
+
+    seed: u32,
+}
+
+impl<Source, F> Map<Source, F> {
+    pub fn new(source: Source, map: F) -> Self {
+        Self {
+            source,
+            map,
+            seed: 0,
+        }
+    }
+}
+
+impl<Source, F> Seedable for Map<Source, F>
+where
+    Source: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source: self.source.set_seed(seed),
+            seed,
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+impl<T, Source, F> NoiseFn<T> for Map<Source, F>
+where
+    Source: NoiseFn<T>,
+    F: Fn(f64) -> f64,
+{
+    fn get(&self, point: T) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        (self.map)(self.source.get_filtered(point, filter_width))
+    }
+}
+
+impl<Source, F> fmt::Debug for Map<Source, F>
+where
+    Source: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Map {{ source: {:?}, .. }}", self.source)
+    }
+}
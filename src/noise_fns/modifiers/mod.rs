@@ -3,6 +3,7 @@ pub use self::clamp::*;
 pub use self::curve::*;
 pub use self::exponent::*;
 pub use self::invert::*;
+pub use self::map::*;
 pub use self::scale_bias::*;
 pub use self::terrace::*;
 
@@ -11,5 +12,6 @@ mod clamp;
 mod curve;
 mod exponent;
 mod invert;
+mod map;
 mod scale_bias;
 mod terrace;
@@ -1,13 +1,14 @@
-use noise_fns::NoiseFn;
+use noise_fns::{NoiseFn, Seedable};
 
 /// Noise function that applies a scaling factor and a bias to the output value
 /// from the source function.
 ///
 /// The function retrieves the output value from the source function, multiplies
 /// it with the scaling factor, adds the bias to it, then outputs the value.
-pub struct ScaleBias<'a, T: 'a> {
+#[derive(Clone, Debug)]
+pub struct ScaleBias<Source> {
     /// Outputs a value.
-    pub source: &'a dyn NoiseFn<T>,
+    pub source: Source,
 
     /// Scaling factor to apply to the output value from the source function.
     /// The default value is 1.0.
@@ -16,14 +17,17 @@ pub struct ScaleBias<'a, T: 'a> {
     /// Bias to apply to the scaled output value from the source function.
     /// The default value is 0.0.
     pub bias: f64,
+
+    seed: u32,
 }
 
-impl<'a, T> ScaleBias<'a, T> {
-    pub fn new(source: &'a dyn NoiseFn<T>) -> Self {
+impl<Source> ScaleBias<Source> {
+    pub fn new(source: Source) -> Self {
         Self {
             source,
             scale: 1.0,
             bias: 0.0,
+            seed: 0,
         }
     }
 
@@ -36,14 +40,38 @@ impl<'a, T> ScaleBias<'a, T> {
     }
 }
 
-impl<'a, T> NoiseFn<T> for ScaleBias<'a, T> {
-    #[cfg(not(target_os = "emscripten"))]
+impl<Source> Seedable for ScaleBias<Source>
+where
+    Source: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source: self.source.set_seed(seed),
+            seed,
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+impl<T, Source> NoiseFn<T> for ScaleBias<Source>
+where
+    Source: NoiseFn<T>,
+{
     fn get(&self, point: T) -> f64 {
-        (self.source.get(point)).mul_add(self.scale, self.bias)
+        self.get_filtered(point, 0.0)
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        (self.source.get_filtered(point, filter_width)).mul_add(self.scale, self.bias)
     }
 
     #[cfg(target_os = "emscripten")]
-    fn get(&self, point: T) -> f64 {
-        (self.source.get(point) * self.scale) + self.bias
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        (self.source.get_filtered(point, filter_width) * self.scale) + self.bias
     }
 }
@@ -1,5 +1,5 @@
 use math::{clamp, interpolate};
-use noise_fns::NoiseFn;
+use noise_fns::{NoiseFn, Seedable};
 use std;
 
 /// Noise function that maps the output value from the source function onto a
@@ -25,9 +25,10 @@ use std;
 ///
 /// This noise function is often used to generate terrain features such as the
 /// stereotypical desert canyon.
-pub struct Terrace<'a, T: 'a> {
+#[derive(Clone, Debug)]
+pub struct Terrace<Source> {
     /// Outputs a value.
-    pub source: &'a dyn NoiseFn<T>,
+    pub source: Source,
 
     /// Determines if the terrace-forming curve between all control points is
     /// inverted.
@@ -35,14 +36,17 @@ pub struct Terrace<'a, T: 'a> {
 
     /// Vec that stores the control points.
     control_points: Vec<f64>,
+
+    seed: u32,
 }
 
-impl<'a, T> Terrace<'a, T> {
-    pub fn new(source: &'a dyn NoiseFn<T>) -> Self {
+impl<Source> Terrace<Source> {
+    pub fn new(source: Source) -> Self {
         Terrace {
             source,
             invert_terraces: false,
             control_points: Vec::with_capacity(2),
+            seed: 0,
         }
     }
 
@@ -58,7 +62,7 @@ impl<'a, T> Terrace<'a, T> {
         if !self
             .control_points
             .iter()
-            .any(|&x| (x - control_point).abs() < std::f64::EPSILON)
+            .any(|&x| (x - control_point).abs() < f64::EPSILON)
         {
             // it doesn't, so find the correct position to insert the new
             // control point.
@@ -66,7 +70,7 @@ impl<'a, T> Terrace<'a, T> {
                 .control_points
                 .iter()
                 .position(|&x| x >= control_point)
-                .unwrap_or_else(|| self.control_points.len());
+                .unwrap_or(self.control_points.len());
 
             // add the new control point at the correct position.
             self.control_points.insert(insertion_point, control_point);
@@ -86,13 +90,37 @@ impl<'a, T> Terrace<'a, T> {
     }
 }
 
-impl<'a, T> NoiseFn<T> for Terrace<'a, T> {
+impl<Source> Seedable for Terrace<Source>
+where
+    Source: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source: self.source.set_seed(seed),
+            seed,
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+impl<T, Source> NoiseFn<T> for Terrace<Source>
+where
+    Source: NoiseFn<T>,
+{
     fn get(&self, point: T) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
         // confirm that there's at least 2 control points in the vector.
         assert!(self.control_points.len() >= 2);
 
         // get output value from the source function
-        let source_value = self.source.get(point);
+        let source_value = self.source.get_filtered(point, filter_width);
 
         // Find the first element in the control point array that has a input
         // value larger than the output value from the source function
@@ -100,7 +128,7 @@ impl<'a, T> NoiseFn<T> for Terrace<'a, T> {
             .control_points
             .iter()
             .position(|&x| x >= source_value)
-            .unwrap_or_else(|| self.control_points.len());
+            .unwrap_or(self.control_points.len());
 
         // Find the two nearest control points so that we can map their values
         // onto a quadratic curve.
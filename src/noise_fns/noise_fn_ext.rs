@@ -0,0 +1,233 @@
+use noise_fns::*;
+
+/// Extension trait for building graphs of noise functions by chaining method
+/// calls, rather than by nesting constructors.
+///
+/// Each method wraps the noise function in the module type it's named after,
+/// with any other settings left at their defaults, so the result can be
+/// configured further with the usual `set_*` methods. The methods that would
+/// share a name with methods of `f64`, `Iterator` or the `std::ops` traits are
+/// suffixed with `_output` or `_noise`:
+///
+/// ```rust
+/// use noise::{Fbm, NoiseFn, NoiseFnExt, Perlin};
+///
+/// let hills = Fbm::new()
+///     .abs_output()
+///     .scale_bias(0.5, -0.25)
+///     .add_noise(Perlin::new());
+/// let val = hills.turbulence().set_power(0.125).get([42.0, 37.0, 2.0]);
+/// ```
+///
+/// This is implemented for every type, so it works for noise functions from
+/// outside this crate, trait objects of `NoiseFn`, and references and pointers
+/// to any of them. Use `&noise_fn` to chain from a noise function without
+/// moving it.
+///
+/// The trait isn't generic over the point type, as a noise function that can
+/// be sampled in several dimensions would leave the point type of every
+/// chained call ambiguous. So the result is only checked to be a noise
+/// function once it's sampled.
+pub trait NoiseFnExt {
+    /// Outputs the absolute value of this function. See `Abs`.
+    fn abs_output(self) -> Abs<Self>
+    where
+        Self: Sized,
+    {
+        Abs::new(self)
+    }
+
+    /// Clamps the output of this function to the range `lower_bound` to
+    /// `upper_bound`. See `Clamp`.
+    fn clamp_output(self, lower_bound: f64, upper_bound: f64) -> Clamp<Self>
+    where
+        Self: Sized,
+    {
+        Clamp::new(self).set_bounds(lower_bound, upper_bound)
+    }
+
+    /// Maps the output of this function onto a curve, whose control points
+    /// are added with `add_control_point`. See `Curve`.
+    fn curve(self) -> Curve<Self>
+    where
+        Self: Sized,
+    {
+        Curve::new(self)
+    }
+
+    /// Raises the output of this function to a power. See `Exponent`.
+    fn exponent(self, exponent: f64) -> Exponent<Self>
+    where
+        Self: Sized,
+    {
+        Exponent::new(self).set_exponent(exponent)
+    }
+
+    /// Negates the output of this function. See `Invert`.
+    fn invert(self) -> Invert<Self>
+    where
+        Self: Sized,
+    {
+        Invert::new(self)
+    }
+
+    /// Multiplies the output of this function by `scale`, then adds `bias`.
+    /// See `ScaleBias`.
+    fn scale_bias(self, scale: f64, bias: f64) -> ScaleBias<Self>
+    where
+        Self: Sized,
+    {
+        ScaleBias::new(self).set_scale(scale).set_bias(bias)
+    }
+
+    /// Maps the output of this function onto a terrace-forming curve, whose
+    /// control points are added with `add_control_point`. See `Terrace`.
+    fn terrace(self) -> Terrace<Self>
+    where
+        Self: Sized,
+    {
+        Terrace::new(self)
+    }
+
+    /// Applies a closure to the output of this function. See `Map`.
+    fn map_output<F>(self, map: F) -> Map<Self, F>
+    where
+        Self: Sized,
+        F: Fn(f64) -> f64,
+    {
+        Map::new(self, map)
+    }
+
+    /// Adds the output of another function to the output of this one. See
+    /// `Add`.
+    fn add_noise<Other>(self, other: Other) -> Add<Self, Other>
+    where
+        Self: Sized,
+    {
+        Add::new(self, other)
+    }
+
+    /// Outputs the larger of the outputs of this function and another. See
+    /// `Max`.
+    fn max_noise<Other>(self, other: Other) -> Max<Self, Other>
+    where
+        Self: Sized,
+    {
+        Max::new(self, other)
+    }
+
+    /// Outputs the smaller of the outputs of this function and another. See
+    /// `Min`.
+    fn min_noise<Other>(self, other: Other) -> Min<Self, Other>
+    where
+        Self: Sized,
+    {
+        Min::new(self, other)
+    }
+
+    /// Multiplies the output of this function by the output of another. See
+    /// `Multiply`.
+    fn multiply_noise<Other>(self, other: Other) -> Multiply<Self, Other>
+    where
+        Self: Sized,
+    {
+        Multiply::new(self, other)
+    }
+
+    /// Raises the output of this function to the power of the output of
+    /// another. See `Power`.
+    fn power_noise<Other>(self, other: Other) -> Power<Self, Other>
+    where
+        Self: Sized,
+    {
+        Power::new(self, other)
+    }
+
+    /// Combines the outputs of this function and another with a closure. See
+    /// `ZipWith`.
+    fn zip_with<Other, F>(self, other: Other, zip: F) -> ZipWith<Self, Other, F>
+    where
+        Self: Sized,
+        F: Fn(f64, f64) -> f64,
+    {
+        ZipWith::new(self, other, zip)
+    }
+
+    /// Blends between the outputs of this function and another, weighted by
+    /// the output of `control`. See `Blend`.
+    fn blend<Other, Control>(self, other: Other, control: Control) -> Blend<Self, Other, Control>
+    where
+        Self: Sized,
+    {
+        Blend::new(self, other, control)
+    }
+
+    /// Selects between the outputs of this function and another, depending on
+    /// the output of `control`. See `Select`.
+    fn select<Other, Control>(self, other: Other, control: Control) -> Select<Self, Other, Control>
+    where
+        Self: Sized,
+    {
+        Select::new(self, other, control)
+    }
+
+    /// Rotates the input point of this function by the given angles, in
+    /// degrees, around each axis. See `RotatePoint`.
+    fn rotate_point(
+        self,
+        x_angle: f64,
+        y_angle: f64,
+        z_angle: f64,
+        u_angle: f64,
+    ) -> RotatePoint<Self>
+    where
+        Self: Sized,
+    {
+        RotatePoint::new(self).set_angles(x_angle, y_angle, z_angle, u_angle)
+    }
+
+    /// Scales the input point of this function by the same factor along every
+    /// axis. See `ScalePoint`.
+    fn scale_point(self, scale: f64) -> ScalePoint<Self>
+    where
+        Self: Sized,
+    {
+        ScalePoint::new(self).set_scale(scale)
+    }
+
+    /// Translates the input point of this function by the same amount along
+    /// every axis. See `TranslatePoint`.
+    fn translate_point(self, translation: f64) -> TranslatePoint<Self>
+    where
+        Self: Sized,
+    {
+        TranslatePoint::new(self).set_translation(translation)
+    }
+
+    /// Randomly displaces the input point of this function. See `Turbulence`.
+    fn turbulence(self) -> Turbulence<Self>
+    where
+        Self: Sized,
+    {
+        Turbulence::new(self)
+    }
+
+    /// Caches the last output of this function. See `Cache`.
+    fn cached(self) -> Cache<Self>
+    where
+        Self: Sized,
+    {
+        Cache::new(self)
+    }
+
+    /// Caches the last output of this function, in a way that can be shared
+    /// between threads. See `SyncCache`.
+    fn sync_cached(self) -> SyncCache<Self>
+    where
+        Self: Sized,
+    {
+        SyncCache::new(self)
+    }
+}
+
+impl<M: ?Sized> NoiseFnExt for M {}
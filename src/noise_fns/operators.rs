@@ -0,0 +1,188 @@
+use noise_fns::{Add, Invert, Multiply, NoiseFn, NoiseFnWithGradient, ScaleBias, Seedable};
+use std::ops;
+
+/// Wrapper that lets noise functions be combined with arithmetic operators.
+///
+/// Adding, subtracting or multiplying two wrapped noise functions, negating
+/// one, or combining one with an `f64`, builds the equivalent graph out of
+/// `Add`, `Multiply`, `Invert` and `ScaleBias`, and wraps it again so the
+/// operators can be chained:
+///
+/// ```rust
+/// use noise::{Fbm, Noise, NoiseFn, Perlin, RidgedMulti};
+///
+/// let hills = Noise::new(Fbm::new());
+/// let ridges = Noise::new(RidgedMulti::new());
+/// let mask = Noise::new(Perlin::new());
+///
+/// let terrain = (hills * 0.5 + ridges * mask) * 0.75 - 0.1;
+/// let val = terrain.get([42.0, 37.0, 2.0]);
+/// ```
+///
+/// Only wrapped noise functions can be combined, as the operator traits can't
+/// be implemented for every noise function at once.
+#[derive(Clone, Debug)]
+pub struct Noise<Source> {
+    /// Outputs a value.
+    pub source: Source,
+}
+
+impl<Source> Noise<Source> {
+    pub fn new(source: Source) -> Self {
+        Noise { source }
+    }
+
+    /// Unwraps the noise function.
+    pub fn into_inner(self) -> Source {
+        self.source
+    }
+}
+
+impl<Source> Seedable for Noise<Source>
+where
+    Source: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source: self.source.set_seed(seed),
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.source.seed()
+    }
+}
+
+impl<T, Source> NoiseFn<T> for Noise<Source>
+where
+    Source: NoiseFn<T>,
+{
+    #[inline]
+    fn get(&self, point: T) -> f64 {
+        self.source.get(point)
+    }
+
+    #[inline]
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        self.source.get_filtered(point, filter_width)
+    }
+}
+
+impl<T, Source> NoiseFnWithGradient<T> for Noise<Source>
+where
+    Source: NoiseFnWithGradient<T>,
+{
+    #[inline]
+    fn get_with_gradient(&self, point: T) -> (f64, T) {
+        self.source.get_with_gradient(point)
+    }
+
+    #[inline]
+    fn get_filtered_with_gradient(&self, point: T, filter_width: f64) -> (f64, T) {
+        self.source.get_filtered_with_gradient(point, filter_width)
+    }
+}
+
+impl<Source1, Source2> ops::Add<Noise<Source2>> for Noise<Source1> {
+    type Output = Noise<Add<Source1, Source2>>;
+
+    fn add(self, other: Noise<Source2>) -> Self::Output {
+        Noise::new(Add::new(self.source, other.source))
+    }
+}
+
+impl<Source1, Source2> ops::Sub<Noise<Source2>> for Noise<Source1> {
+    type Output = Noise<Add<Source1, Invert<Source2>>>;
+
+    fn sub(self, other: Noise<Source2>) -> Self::Output {
+        Noise::new(Add::new(self.source, Invert::new(other.source)))
+    }
+}
+
+impl<Source1, Source2> ops::Mul<Noise<Source2>> for Noise<Source1> {
+    type Output = Noise<Multiply<Source1, Source2>>;
+
+    fn mul(self, other: Noise<Source2>) -> Self::Output {
+        Noise::new(Multiply::new(self.source, other.source))
+    }
+}
+
+impl<Source> ops::Neg for Noise<Source> {
+    type Output = Noise<Invert<Source>>;
+
+    fn neg(self) -> Self::Output {
+        Noise::new(Invert::new(self.source))
+    }
+}
+
+impl<Source> ops::Add<f64> for Noise<Source> {
+    type Output = Noise<ScaleBias<Source>>;
+
+    fn add(self, bias: f64) -> Self::Output {
+        Noise::new(ScaleBias::new(self.source).set_bias(bias))
+    }
+}
+
+impl<Source> ops::Sub<f64> for Noise<Source> {
+    type Output = Noise<ScaleBias<Source>>;
+
+    fn sub(self, bias: f64) -> Self::Output {
+        Noise::new(ScaleBias::new(self.source).set_bias(-bias))
+    }
+}
+
+impl<Source> ops::Mul<f64> for Noise<Source> {
+    type Output = Noise<ScaleBias<Source>>;
+
+    fn mul(self, scale: f64) -> Self::Output {
+        Noise::new(ScaleBias::new(self.source).set_scale(scale))
+    }
+}
+
+impl<Source> ops::Add<Noise<Source>> for f64 {
+    type Output = Noise<ScaleBias<Source>>;
+
+    fn add(self, noise: Noise<Source>) -> Self::Output {
+        noise + self
+    }
+}
+
+impl<Source> ops::Sub<Noise<Source>> for f64 {
+    type Output = Noise<ScaleBias<Source>>;
+
+    fn sub(self, noise: Noise<Source>) -> Self::Output {
+        Noise::new(ScaleBias::new(noise.source).set_scale(-1.0).set_bias(self))
+    }
+}
+
+impl<Source> ops::Mul<Noise<Source>> for f64 {
+    type Output = Noise<ScaleBias<Source>>;
+
+    fn mul(self, noise: Noise<Source>) -> Self::Output {
+        noise * self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noise_fns::{Fbm, Perlin, Value};
+
+    #[test]
+    fn test_operators_match_modules() {
+        let (a, b) = (Fbm::new().set_seed(1), Perlin::new().set_seed(2));
+        let c = Value::new().set_seed(3);
+
+        let expression =
+            (Noise::new(&a) + Noise::new(&b)) * Noise::new(&c) * 2.0 - Noise::new(&b) + 0.5;
+        let negated = 1.0 - -Noise::new(&a) * 3.0;
+
+        for i in 0..32 {
+            let point = [f64::from(i) * 0.37, 0.5, -0.25];
+            let (a, b, c) = (a.get(point), b.get(point), c.get(point));
+
+            assert_eq!(expression.get(point), (a + b) * c * 2.0 - b + 0.5);
+            assert_eq!(negated.get(point), 1.0 - -a * 3.0);
+        }
+    }
+}
@@ -1,47 +1,75 @@
 use math::interpolate;
-use noise_fns::NoiseFn;
+use noise_fns::{derive_seed, NoiseFn, Seedable};
 
 /// Noise function that outputs a weighted blend of the output values from two
 /// source functions given the output value supplied by a control function.
 ///
 /// This noise function uses linear interpolation to perform the blending
 /// operation.
-pub struct Blend<'a, T: 'a> {
+#[derive(Clone, Debug)]
+pub struct Blend<Source1, Source2, Control> {
     /// Outputs one of the values to blend.
-    pub source1: &'a dyn NoiseFn<T>,
+    pub source1: Source1,
 
     /// Outputs one of the values to blend.
-    pub source2: &'a dyn NoiseFn<T>,
+    pub source2: Source2,
 
     /// Determines the weight of the blending operation. Negative values weight
     /// the blend towards the output value from the `source1` function. Positive
     /// values weight the blend towards the output value from the `source2`
     /// function.
-    pub control: &'a dyn NoiseFn<T>,
+    pub control: Control,
+
+    seed: u32,
 }
 
-impl<'a, T> Blend<'a, T> {
-    pub fn new(
-        source1: &'a dyn NoiseFn<T>,
-        source2: &'a dyn NoiseFn<T>,
-        control: &'a dyn NoiseFn<T>,
-    ) -> Self {
+impl<Source1, Source2, Control> Blend<Source1, Source2, Control> {
+    pub fn new(source1: Source1, source2: Source2, control: Control) -> Self {
         Blend {
             source1,
             source2,
             control,
+            seed: 0,
         }
     }
 }
 
-impl<'a, T> NoiseFn<T> for Blend<'a, T>
+/// Setting the seed reseeds each source with a seed derived from it.
+impl<Source1, Source2, Control> Seedable for Blend<Source1, Source2, Control>
+where
+    Source1: Seedable,
+    Source2: Seedable,
+    Control: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source1: self.source1.set_seed(derive_seed(seed, 0)),
+            source2: self.source2.set_seed(derive_seed(seed, 1)),
+            control: self.control.set_seed(derive_seed(seed, 2)),
+            seed,
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+impl<T, Source1, Source2, Control> NoiseFn<T> for Blend<Source1, Source2, Control>
 where
     T: Copy,
+    Source1: NoiseFn<T>,
+    Source2: NoiseFn<T>,
+    Control: NoiseFn<T>,
 {
     fn get(&self, point: T) -> f64 {
-        let lower = self.source1.get(point);
-        let upper = self.source2.get(point);
-        let control = self.control.get(point);
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        let lower = self.source1.get_filtered(point, filter_width);
+        let upper = self.source2.get_filtered(point, filter_width);
+        let control = self.control.get_filtered(point, filter_width);
 
         interpolate::linear(lower, upper, control)
     }
@@ -1,40 +1,40 @@
 use math::interpolate;
-use noise_fns::NoiseFn;
+use noise_fns::{derive_seed, NoiseFn, Seedable};
 
 /// Noise function that outputs the value selected from one of two source
 /// functions chosen by the output value from a control function.
-pub struct Select<'a, T: 'a> {
+#[derive(Clone, Debug)]
+pub struct Select<Source1, Source2, Control> {
     /// Outputs a value.
-    pub source1: &'a dyn NoiseFn<T>,
+    pub source1: Source1,
 
     /// Outputs a value.
-    pub source2: &'a dyn NoiseFn<T>,
+    pub source2: Source2,
 
     /// Determines the value to select. If the output value from
     /// the control function is within a range of values know as the _selection
     /// range_, this noise function outputs the value from `source2`.
     /// Otherwise, this noise function outputs the value from `source1`.
-    pub control: &'a dyn NoiseFn<T>,
+    pub control: Control,
 
     /// Bounds of the selection range. Default is 0.0 to 1.0.
     pub bounds: (f64, f64),
 
     /// Edge falloff value. Default is 0.0.
     pub falloff: f64,
+
+    seed: u32,
 }
 
-impl<'a, T> Select<'a, T> {
-    pub fn new(
-        source1: &'a dyn NoiseFn<T>,
-        source2: &'a dyn NoiseFn<T>,
-        control: &'a dyn NoiseFn<T>,
-    ) -> Self {
+impl<Source1, Source2, Control> Select<Source1, Source2, Control> {
+    pub fn new(source1: Source1, source2: Source2, control: Control) -> Self {
         Select {
             source1,
             source2,
             control,
             bounds: (0.0, 1.0),
             falloff: 0.0,
+            seed: 0,
         }
     }
 
@@ -50,17 +50,49 @@ impl<'a, T> Select<'a, T> {
     }
 }
 
-impl<'a, T> NoiseFn<T> for Select<'a, T>
+/// Setting the seed reseeds each source with a seed derived from it.
+impl<Source1, Source2, Control> Seedable for Select<Source1, Source2, Control>
+where
+    Source1: Seedable,
+    Source2: Seedable,
+    Control: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source1: self.source1.set_seed(derive_seed(seed, 0)),
+            source2: self.source2.set_seed(derive_seed(seed, 1)),
+            control: self.control.set_seed(derive_seed(seed, 2)),
+            seed,
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+impl<T, Source1, Source2, Control> NoiseFn<T> for Select<Source1, Source2, Control>
 where
     T: Copy,
+    Source1: NoiseFn<T>,
+    Source2: NoiseFn<T>,
+    Control: NoiseFn<T>,
 {
     fn get(&self, point: T) -> f64 {
-        let control_value = self.control.get(point);
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: T, filter_width: f64) -> f64 {
+        let source1 = |point| self.source1.get_filtered(point, filter_width);
+        let source2 = |point| self.source2.get_filtered(point, filter_width);
+
+        let control_value = self.control.get_filtered(point, filter_width);
         let (lower, upper) = self.bounds;
 
         if self.falloff > 0.0 {
             match () {
-                _ if control_value < (lower - self.falloff) => self.source1.get(point),
+                _ if control_value < (lower - self.falloff) => source1(point),
                 _ if control_value < (lower + self.falloff) => {
                     let lower_curve = lower - self.falloff;
                     let upper_curve = lower + self.falloff;
@@ -68,9 +100,9 @@ where
                         (control_value - lower_curve) / (upper_curve - lower_curve),
                     );
 
-                    interpolate::linear(self.source1.get(point), self.source2.get(point), alpha)
+                    interpolate::linear(source1(point), source2(point), alpha)
                 },
-                _ if control_value < (upper - self.falloff) => self.source2.get(point),
+                _ if control_value < (upper - self.falloff) => source2(point),
                 _ if control_value < (upper + self.falloff) => {
                     let lower_curve = upper - self.falloff;
                     let upper_curve = upper + self.falloff;
@@ -78,14 +110,14 @@ where
                         (control_value - lower_curve) / (upper_curve - lower_curve),
                     );
 
-                    interpolate::linear(self.source2.get(point), self.source1.get(point), alpha)
+                    interpolate::linear(source2(point), source1(point), alpha)
                 },
-                _ => self.source1.get(point),
+                _ => source1(point),
             }
         } else if control_value < lower || control_value > upper {
-            self.source1.get(point)
+            source1(point)
         } else {
-            self.source2.get(point)
+            source2(point)
         }
     }
 }
@@ -0,0 +1,176 @@
+use math::{Point2, Point3, Point4};
+use noise_fns::{NoiseFn, Seedable};
+use std::sync::{Mutex, MutexGuard, TryLockError};
+
+/// Noise function that caches the last output value generated by the source
+/// function, and can be shared between threads.
+///
+/// This works the same way as `Cache`, filter widths included, but keeps the
+/// cached value behind a mutex instead of a `Cell`, so it's `Sync` whenever
+/// the source is.
+///
+/// All the threads share the one cached value, so whenever a thread samples a
+/// different point, it evicts the value that the other threads cached. The
+/// cache never makes a thread wait: if another thread holds the lock, the
+/// value is calculated from the source without the cache. Under contention the
+/// cache saves little work, so it's best to give each thread its own cache,
+/// such as a clone of this one or a `Cache`. It helps most when each point is
+/// sampled by several parts of a graph in a row on the same thread.
+#[derive(Debug)]
+pub struct SyncCache<Source> {
+    /// Outputs the value to be cached.
+    pub source: Source,
+
+    cached: Mutex<Option<CachedValue>>,
+}
+
+/// The last point sampled, the filter width it was sampled with, and the
+/// value. Only the first `dimensions` coordinates of the point are used.
+#[derive(Clone, Copy, Debug)]
+struct CachedValue {
+    point: [f64; 4],
+    dimensions: usize,
+    filter_width: f64,
+    value: f64,
+}
+
+impl<Source> SyncCache<Source> {
+    pub fn new(source: Source) -> Self {
+        SyncCache {
+            source,
+            cached: Mutex::new(None),
+        }
+    }
+
+    fn get_cached<const N: usize>(&self, point: [f64; N], filter_width: f64) -> f64
+    where
+        Source: NoiseFn<[f64; N]>,
+    {
+        if let Some(cached) = self.try_lock() {
+            if let Some(cached) = *cached {
+                if cached.dimensions == N
+                    && cached.point[..N] == point[..]
+                    && cached.filter_width == filter_width
+                {
+                    return cached.value;
+                }
+            }
+        }
+
+        // The source is sampled without holding the lock, so other threads
+        // aren't blocked while it runs.
+        let value = self.source.get_filtered(point, filter_width);
+
+        if let Some(mut cached) = self.try_lock() {
+            let mut cached_point = [0.0; 4];
+            cached_point[..N].copy_from_slice(&point);
+            *cached = Some(CachedValue {
+                point: cached_point,
+                dimensions: N,
+                filter_width,
+                value,
+            });
+        }
+
+        value
+    }
+
+    /// Locks the cached value, unless another thread holds the lock.
+    fn try_lock(&self) -> Option<MutexGuard<'_, Option<CachedValue>>> {
+        match self.cached.try_lock() {
+            Ok(cached) => Some(cached),
+            // The lock is never held while anything can panic, and the cached
+            // value is valid either way.
+            Err(TryLockError::Poisoned(error)) => Some(error.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+}
+
+impl<Source> Clone for SyncCache<Source>
+where
+    Source: Clone,
+{
+    /// Clones the source. The clone starts with an empty cache.
+    fn clone(&self) -> Self {
+        Self::new(self.source.clone())
+    }
+}
+
+impl<Source> Seedable for SyncCache<Source>
+where
+    Source: Seedable,
+{
+    /// Reseeds the source, and clears the cached value.
+    fn set_seed(self, seed: u32) -> Self {
+        Self::new(self.source.set_seed(seed))
+    }
+
+    fn seed(&self) -> u32 {
+        self.source.seed()
+    }
+}
+
+impl<Source> NoiseFn<Point2<f64>> for SyncCache<Source>
+where
+    Source: NoiseFn<Point2<f64>>,
+{
+    fn get(&self, point: Point2<f64>) -> f64 {
+        self.get_cached(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point2<f64>, filter_width: f64) -> f64 {
+        self.get_cached(point, filter_width)
+    }
+}
+
+impl<Source> NoiseFn<Point3<f64>> for SyncCache<Source>
+where
+    Source: NoiseFn<Point3<f64>>,
+{
+    fn get(&self, point: Point3<f64>) -> f64 {
+        self.get_cached(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point3<f64>, filter_width: f64) -> f64 {
+        self.get_cached(point, filter_width)
+    }
+}
+
+impl<Source> NoiseFn<Point4<f64>> for SyncCache<Source>
+where
+    Source: NoiseFn<Point4<f64>>,
+{
+    fn get(&self, point: Point4<f64>) -> f64 {
+        self.get_cached(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point4<f64>, filter_width: f64) -> f64 {
+        self.get_cached(point, filter_width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noise_fns::Perlin;
+
+    #[test]
+    fn test_contended_cache_samples_source() {
+        let cache = SyncCache::new(Perlin::new());
+        let point = [0.3, 0.7, 0.1];
+        let value = cache.get(point);
+
+        // While the lock is held, as if by another thread, the value is
+        // calculated without the cache, and the cached value is left alone.
+        let cached = cache.cached.lock().unwrap();
+        assert_eq!(
+            cache.get([0.5, 0.5, 0.5]),
+            Perlin::new().get([0.5, 0.5, 0.5])
+        );
+        drop(cached);
+
+        assert_eq!(cache.cached.lock().unwrap().unwrap().point[..3], point[..]);
+        assert_eq!(cache.get(point), value);
+    }
+}
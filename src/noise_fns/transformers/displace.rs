@@ -1,5 +1,5 @@
 use math::{Point2, Point3, Point4};
-use noise_fns::NoiseFn;
+use noise_fns::{derive_seed, warped_filter_width, NoiseFn, Seedable};
 
 /// Noise function that uses multiple source functions to displace each coordinate
 /// of the input value before returning the output value from the `source` function.
@@ -22,6 +22,8 @@ pub struct Displace<Source, XDisplace, YDisplace, ZDisplace, UDisplace> {
     /// Displacement function that displaces the _u_ coordinate of the input
     /// value. Only needed for 4d or higher noise.
     pub u_displace: UDisplace,
+
+    seed: u32,
 }
 
 impl<Source, XDisplace, YDisplace, ZDisplace, UDisplace>
@@ -40,10 +42,38 @@ impl<Source, XDisplace, YDisplace, ZDisplace, UDisplace>
             y_displace,
             z_displace,
             u_displace,
+            seed: 0,
         }
     }
 }
 
+/// Setting the seed reseeds the source and each displacement function with a
+/// seed derived from it.
+impl<Source, XDisplace, YDisplace, ZDisplace, UDisplace> Seedable
+    for Displace<Source, XDisplace, YDisplace, ZDisplace, UDisplace>
+where
+    Source: Seedable,
+    XDisplace: Seedable,
+    YDisplace: Seedable,
+    ZDisplace: Seedable,
+    UDisplace: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source: self.source.set_seed(derive_seed(seed, 0)),
+            x_displace: self.x_displace.set_seed(derive_seed(seed, 1)),
+            y_displace: self.y_displace.set_seed(derive_seed(seed, 2)),
+            z_displace: self.z_displace.set_seed(derive_seed(seed, 3)),
+            u_displace: self.u_displace.set_seed(derive_seed(seed, 4)),
+            seed,
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
 impl<Source, XDisplace, YDisplace, ZDisplace, UDisplace> NoiseFn<Point2<f64>>
     for Displace<Source, XDisplace, YDisplace, ZDisplace, UDisplace>
 where
@@ -52,15 +82,26 @@ where
     YDisplace: NoiseFn<Point2<f64>>,
 {
     fn get(&self, point: Point2<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point2<f64>, filter_width: f64) -> f64 {
         // Get the output values from the displacement functions and add them to
         // the corresponding coordinate in the input value. Since this is a 2d
         // function, we only need the x_displace and y_displace functions.
-        let x = point[0] + self.x_displace.get(point);
-        let y = point[1] + self.y_displace.get(point);
+        let displace = |point: Point2<f64>| {
+            [
+                point[0] + self.x_displace.get_filtered(point, filter_width),
+                point[1] + self.y_displace.get_filtered(point, filter_width),
+            ]
+        };
+        let displaced = displace(point);
 
         // get the output value using the offset input value instead of the
-        // original input value.
-        self.source.get([x, y])
+        // original input value, filtered to the spacing that the samples end
+        // up with once displaced.
+        let filter_width = warped_filter_width(displace, point, displaced, filter_width);
+        self.source.get_filtered(displaced, filter_width)
     }
 }
 
@@ -73,17 +114,28 @@ where
     ZDisplace: NoiseFn<Point3<f64>>,
 {
     fn get(&self, point: Point3<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point3<f64>, filter_width: f64) -> f64 {
         // Get the output values from the displacement functions and add them to
         // the corresponding coordinate in the input value. Since this is a 3d
         // function, we only need the x_displace, y_displace, and z_displace
         // functions. Also, panic if there is no z_displace function defined.
-        let x = point[0] + self.x_displace.get(point);
-        let y = point[1] + self.y_displace.get(point);
-        let z = point[2] + self.z_displace.get(point);
+        let displace = |point: Point3<f64>| {
+            [
+                point[0] + self.x_displace.get_filtered(point, filter_width),
+                point[1] + self.y_displace.get_filtered(point, filter_width),
+                point[2] + self.z_displace.get_filtered(point, filter_width),
+            ]
+        };
+        let displaced = displace(point);
 
         // get the output value using the offset input value instead of the
-        // original input value.
-        self.source.get([x, y, z])
+        // original input value, filtered to the spacing that the samples end
+        // up with once displaced.
+        let filter_width = warped_filter_width(displace, point, displaced, filter_width);
+        self.source.get_filtered(displaced, filter_width)
     }
 }
 
@@ -97,17 +149,28 @@ where
     UDisplace: NoiseFn<Point4<f64>>,
 {
     fn get(&self, point: Point4<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point4<f64>, filter_width: f64) -> f64 {
         // Get the output values from the displacement functions and add them to
         // the corresponding coordinate in the input value. Since this is a 4d
         // function, we need all of the displace functions. Panic if there is no z-
         // or u-displace function defined.
-        let x = point[0] + self.x_displace.get(point);
-        let y = point[1] + self.y_displace.get(point);
-        let z = point[2] + self.z_displace.get(point);
-        let u = point[3] + self.u_displace.get(point);
+        let displace = |point: Point4<f64>| {
+            [
+                point[0] + self.x_displace.get_filtered(point, filter_width),
+                point[1] + self.y_displace.get_filtered(point, filter_width),
+                point[2] + self.z_displace.get_filtered(point, filter_width),
+                point[3] + self.u_displace.get_filtered(point, filter_width),
+            ]
+        };
+        let displaced = displace(point);
 
         // get the output value using the offset input value instead of the
-        // original input value.
-        self.source.get([x, y, z, u])
+        // original input value, filtered to the spacing that the samples end
+        // up with once displaced.
+        let filter_width = warped_filter_width(displace, point, displaced, filter_width);
+        self.source.get_filtered(displaced, filter_width)
     }
 }
@@ -1,5 +1,5 @@
 use math::{Point2, Point3, Point4};
-use noise_fns::NoiseFn;
+use noise_fns::{NoiseFn, Seedable};
 
 /// Noise function that rotates the input value around the origin before
 /// returning the output value from the source function.
@@ -28,6 +28,8 @@ pub struct RotatePoint<Source> {
     /// _u_ rotation angle applied to the input value, in degrees. The
     /// default angle is set to 0.0 degrees.
     pub u_angle: f64,
+
+    seed: u32,
 }
 
 impl<Source> RotatePoint<Source> {
@@ -38,6 +40,7 @@ impl<Source> RotatePoint<Source> {
             y_angle: 0.0,
             z_angle: 0.0,
             u_angle: 0.0,
+            seed: 0,
         }
     }
 
@@ -78,11 +81,32 @@ impl<Source> RotatePoint<Source> {
     }
 }
 
+impl<Source> Seedable for RotatePoint<Source>
+where
+    Source: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source: self.source.set_seed(seed),
+            seed,
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
 impl<Source> NoiseFn<Point2<f64>> for RotatePoint<Source>
 where
     Source: NoiseFn<Point2<f64>>,
 {
     fn get(&self, point: Point2<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point2<f64>, filter_width: f64) -> f64 {
         // In two dimensions, the plane is _xy_, and we rotate around the
         // z-axis.
         let x = point[0];
@@ -94,7 +118,7 @@ where
 
         // get the output value using the offset input value instead of the
         // original input value.
-        self.source.get([x2, y2])
+        self.source.get_filtered([x2, y2], filter_width)
     }
 }
 
@@ -103,6 +127,10 @@ where
     Source: NoiseFn<Point3<f64>>,
 {
     fn get(&self, point: Point3<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point3<f64>, filter_width: f64) -> f64 {
         // In three dimensions, we could rotate around any of the x, y, or z
         // axes. Need a more complicated function to handle this case.
         let x_cos = self.x_angle.to_radians().cos();
@@ -128,7 +156,7 @@ where
 
         // get the output value using the offset input value instead of the
         // original input value.
-        self.source.get([x, y, z])
+        self.source.get_filtered([x, y, z], filter_width)
     }
 }
 
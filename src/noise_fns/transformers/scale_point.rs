@@ -1,5 +1,5 @@
 use math::{Point2, Point3, Point4};
-use noise_fns::NoiseFn;
+use noise_fns::{NoiseFn, Seedable};
 
 /// Noise function that scales the coordinates of the input value before
 /// returning the output value from the source function.
@@ -25,6 +25,8 @@ pub struct ScalePoint<Source> {
     /// Scaling factor applied to the _u_ coordinate of the input value. The
     /// default scaling factor is set to 1.0.
     pub u_scale: f64,
+
+    seed: u32,
 }
 
 impl<Source> ScalePoint<Source> {
@@ -35,6 +37,7 @@ impl<Source> ScalePoint<Source> {
             y_scale: 1.0,
             z_scale: 1.0,
             u_scale: 1.0,
+            seed: 0,
         }
     }
 
@@ -86,13 +89,39 @@ impl<Source> ScalePoint<Source> {
     }
 }
 
+impl<Source> Seedable for ScalePoint<Source>
+where
+    Source: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source: self.source.set_seed(seed),
+            seed,
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
 impl<Source> NoiseFn<Point2<f64>> for ScalePoint<Source>
 where
     Source: NoiseFn<Point2<f64>>,
 {
     fn get(&self, point: Point2<f64>) -> f64 {
-        self.source
-            .get([point[0] * self.x_scale, point[1] * self.y_scale])
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point2<f64>, filter_width: f64) -> f64 {
+        // Samples spread apart by up to the largest scale.
+        let filter_width = filter_width * self.x_scale.abs().max(self.y_scale.abs());
+
+        self.source.get_filtered(
+            [point[0] * self.x_scale, point[1] * self.y_scale],
+            filter_width,
+        )
     }
 }
 
@@ -101,11 +130,26 @@ where
     Source: NoiseFn<Point3<f64>>,
 {
     fn get(&self, point: Point3<f64>) -> f64 {
-        self.source.get([
-            point[0] * self.x_scale,
-            point[1] * self.y_scale,
-            point[2] * self.z_scale,
-        ])
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point3<f64>, filter_width: f64) -> f64 {
+        // Samples spread apart by up to the largest scale.
+        let filter_width = filter_width
+            * self
+                .x_scale
+                .abs()
+                .max(self.y_scale.abs())
+                .max(self.z_scale.abs());
+
+        self.source.get_filtered(
+            [
+                point[0] * self.x_scale,
+                point[1] * self.y_scale,
+                point[2] * self.z_scale,
+            ],
+            filter_width,
+        )
     }
 }
 
@@ -114,12 +158,28 @@ where
     Source: NoiseFn<Point4<f64>>,
 {
     fn get(&self, point: Point4<f64>) -> f64 {
-        self.source.get([
-            point[0] * self.x_scale,
-            point[1] * self.y_scale,
-            point[2] * self.z_scale,
-            point[3] * self.u_scale,
-        ])
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point4<f64>, filter_width: f64) -> f64 {
+        // Samples spread apart by up to the largest scale.
+        let filter_width = filter_width
+            * self
+                .x_scale
+                .abs()
+                .max(self.y_scale.abs())
+                .max(self.z_scale.abs())
+                .max(self.u_scale.abs());
+
+        self.source.get_filtered(
+            [
+                point[0] * self.x_scale,
+                point[1] * self.y_scale,
+                point[2] * self.z_scale,
+                point[3] * self.u_scale,
+            ],
+            filter_width,
+        )
     }
 }
 
@@ -1,5 +1,5 @@
 use math::{Point2, Point3, Point4};
-use noise_fns::NoiseFn;
+use noise_fns::{NoiseFn, Seedable};
 
 /// Noise function that moves the coordinates of the input value before
 /// returning the output value from the source function.
@@ -25,6 +25,8 @@ pub struct TranslatePoint<Source> {
     /// Translation amount applied to the _u_ coordinate of the input value.
     /// The default translation amount is set to 0.0.
     pub u_translation: f64,
+
+    seed: u32,
 }
 
 impl<Source> TranslatePoint<Source> {
@@ -35,6 +37,7 @@ impl<Source> TranslatePoint<Source> {
             y_translation: 0.0,
             z_translation: 0.0,
             u_translation: 0.0,
+            seed: 0,
         }
     }
 
@@ -104,13 +107,36 @@ impl<Source> TranslatePoint<Source> {
     }
 }
 
+impl<Source> Seedable for TranslatePoint<Source>
+where
+    Source: Seedable,
+{
+    fn set_seed(self, seed: u32) -> Self {
+        Self {
+            source: self.source.set_seed(seed),
+            seed,
+            ..self
+        }
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
 impl<Source> NoiseFn<Point2<f64>> for TranslatePoint<Source>
 where
     Source: NoiseFn<Point2<f64>>,
 {
     fn get(&self, point: Point2<f64>) -> f64 {
-        self.source
-            .get([point[0] + self.x_translation, point[1] + self.y_translation])
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point2<f64>, filter_width: f64) -> f64 {
+        self.source.get_filtered(
+            [point[0] + self.x_translation, point[1] + self.y_translation],
+            filter_width,
+        )
     }
 }
 
@@ -119,11 +145,18 @@ where
     Source: NoiseFn<Point3<f64>>,
 {
     fn get(&self, point: Point3<f64>) -> f64 {
-        self.source.get([
-            point[0] + self.x_translation,
-            point[1] + self.y_translation,
-            point[2] + self.z_translation,
-        ])
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point3<f64>, filter_width: f64) -> f64 {
+        self.source.get_filtered(
+            [
+                point[0] + self.x_translation,
+                point[1] + self.y_translation,
+                point[2] + self.z_translation,
+            ],
+            filter_width,
+        )
     }
 }
 
@@ -132,11 +165,18 @@ where
     Source: NoiseFn<Point4<f64>>,
 {
     fn get(&self, point: Point4<f64>) -> f64 {
-        self.source.get([
-            point[0] + self.x_translation,
-            point[1] + self.y_translation,
-            point[2] + self.z_translation,
-            point[3] + self.u_translation,
-        ])
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point4<f64>, filter_width: f64) -> f64 {
+        self.source.get_filtered(
+            [
+                point[0] + self.x_translation,
+                point[1] + self.y_translation,
+                point[2] + self.z_translation,
+                point[3] + self.u_translation,
+            ],
+            filter_width,
+        )
     }
 }
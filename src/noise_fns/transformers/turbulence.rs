@@ -1,5 +1,5 @@
 use math::{Point2, Point3, Point4};
-use noise_fns::{Fbm, MultiFractal, NoiseFn, Seedable};
+use noise_fns::{warped_filter_width, Fbm, MultiFractal, NoiseFn, Seedable};
 
 /// Noise function that randomly displaces the input value before returning the
 /// output value from the source function.
@@ -90,14 +90,17 @@ impl<Source> Turbulence<Source> {
     }
 }
 
+/// Setting the seed only reseeds the functions that displace the input point.
+/// The source keeps its own seed, since it's often shared with other parts of
+/// a graph of noise functions.
 impl<Source> Seedable for Turbulence<Source> {
     fn set_seed(self, seed: u32) -> Self {
         Self {
             seed,
             x_distort_function: self.x_distort_function.set_seed(seed),
-            y_distort_function: self.y_distort_function.set_seed(seed + 1),
-            z_distort_function: self.z_distort_function.set_seed(seed + 2),
-            u_distort_function: self.u_distort_function.set_seed(seed + 3),
+            y_distort_function: self.y_distort_function.set_seed(seed.wrapping_add(1)),
+            z_distort_function: self.z_distort_function.set_seed(seed.wrapping_add(2)),
+            u_distort_function: self.u_distort_function.set_seed(seed.wrapping_add(3)),
             ..self
         }
     }
@@ -107,11 +110,9 @@ impl<Source> Seedable for Turbulence<Source> {
     }
 }
 
-impl<Source> NoiseFn<Point2<f64>> for Turbulence<Source>
-where
-    Source: NoiseFn<Point2<f64>>,
-{
-    fn get(&self, point: Point2<f64>) -> f64 {
+impl<Source> Turbulence<Source> {
+    /// Returns the point displaced by the distortion functions.
+    fn distort2(&self, point: Point2<f64>, filter_width: f64) -> Point2<f64> {
         // First, create offsets based on the input values to keep the sampled
         // points from being near a integer boundary. This is a result of
         // using perlin noise, which returns zero at integer boundaries.
@@ -121,18 +122,16 @@ where
         let x1 = point[0] + 26519.0 / 65536.0;
         let y1 = point[1] + 18128.0 / 65536.0;
 
-        let x_distort = point[0] + (self.x_distort_function.get([x0, y0]) * self.power);
-        let y_distort = point[1] + (self.y_distort_function.get([x1, y1]) * self.power);
+        let distort =
+            |function: &Fbm, point| function.get_filtered(point, filter_width) * self.power;
+        let x_distort = point[0] + distort(&self.x_distort_function, [x0, y0]);
+        let y_distort = point[1] + distort(&self.y_distort_function, [x1, y1]);
 
-        self.source.get([x_distort, y_distort])
+        [x_distort, y_distort]
     }
-}
 
-impl<Source> NoiseFn<Point3<f64>> for Turbulence<Source>
-where
-    Source: NoiseFn<Point3<f64>>,
-{
-    fn get(&self, point: Point3<f64>) -> f64 {
+    /// Returns the point displaced by the distortion functions.
+    fn distort3(&self, point: Point3<f64>, filter_width: f64) -> Point3<f64> {
         // First, create offsets based on the input values to keep the sampled
         // points from being near a integer boundary. This is a result of
         // using perlin noise, which returns zero at integer boundaries.
@@ -148,19 +147,17 @@ where
         let y2 = point[1] + 11213.0 / 65536.0;
         let z2 = point[2] + 44845.0 / 65536.0;
 
-        let x_distort = point[0] + (self.x_distort_function.get([x0, y0, z0]) * self.power);
-        let y_distort = point[1] + (self.y_distort_function.get([x1, y1, z1]) * self.power);
-        let z_distort = point[2] + (self.z_distort_function.get([x2, y2, z2]) * self.power);
+        let distort =
+            |function: &Fbm, point| function.get_filtered(point, filter_width) * self.power;
+        let x_distort = point[0] + distort(&self.x_distort_function, [x0, y0, z0]);
+        let y_distort = point[1] + distort(&self.y_distort_function, [x1, y1, z1]);
+        let z_distort = point[2] + distort(&self.z_distort_function, [x2, y2, z2]);
 
-        self.source.get([x_distort, y_distort, z_distort])
+        [x_distort, y_distort, z_distort]
     }
-}
 
-impl<Source> NoiseFn<Point4<f64>> for Turbulence<Source>
-where
-    Source: NoiseFn<Point4<f64>>,
-{
-    fn get(&self, point: Point4<f64>) -> f64 {
+    /// Returns the point displaced by the distortion functions.
+    fn distort4(&self, point: Point4<f64>, filter_width: f64) -> Point4<f64> {
         // First, create offsets based on the input values to keep the sampled
         // points from being near a integer boundary. This is a result of
         // using perlin noise, which returns zero at integer boundaries.
@@ -184,12 +181,76 @@ where
         let z3 = point[2] + 12414.0 / 65536.0;
         let u3 = point[3] + 60943.0 / 65536.0;
 
-        let x_distort = point[0] + (self.x_distort_function.get([x0, y0, z0, u0]) * self.power);
-        let y_distort = point[1] + (self.y_distort_function.get([x1, y1, z1, u1]) * self.power);
-        let z_distort = point[2] + (self.z_distort_function.get([x2, y2, z2, u2]) * self.power);
-        let u_distort = point[3] + (self.u_distort_function.get([x3, y3, z3, u3]) * self.power);
+        let distort =
+            |function: &Fbm, point| function.get_filtered(point, filter_width) * self.power;
+        let x_distort = point[0] + distort(&self.x_distort_function, [x0, y0, z0, u0]);
+        let y_distort = point[1] + distort(&self.y_distort_function, [x1, y1, z1, u1]);
+        let z_distort = point[2] + distort(&self.z_distort_function, [x2, y2, z2, u2]);
+        let u_distort = point[3] + distort(&self.u_distort_function, [x3, y3, z3, u3]);
+
+        [x_distort, y_distort, z_distort, u_distort]
+    }
+}
+
+impl<Source> NoiseFn<Point2<f64>> for Turbulence<Source>
+where
+    Source: NoiseFn<Point2<f64>>,
+{
+    fn get(&self, point: Point2<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point2<f64>, filter_width: f64) -> f64 {
+        let distorted = self.distort2(point, filter_width);
+        let filter_width = warped_filter_width(
+            |point| self.distort2(point, filter_width),
+            point,
+            distorted,
+            filter_width,
+        );
+
+        self.source.get_filtered(distorted, filter_width)
+    }
+}
+
+impl<Source> NoiseFn<Point3<f64>> for Turbulence<Source>
+where
+    Source: NoiseFn<Point3<f64>>,
+{
+    fn get(&self, point: Point3<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point3<f64>, filter_width: f64) -> f64 {
+        let distorted = self.distort3(point, filter_width);
+        let filter_width = warped_filter_width(
+            |point| self.distort3(point, filter_width),
+            point,
+            distorted,
+            filter_width,
+        );
+
+        self.source.get_filtered(distorted, filter_width)
+    }
+}
+
+impl<Source> NoiseFn<Point4<f64>> for Turbulence<Source>
+where
+    Source: NoiseFn<Point4<f64>>,
+{
+    fn get(&self, point: Point4<f64>) -> f64 {
+        self.get_filtered(point, 0.0)
+    }
+
+    fn get_filtered(&self, point: Point4<f64>, filter_width: f64) -> f64 {
+        let distorted = self.distort4(point, filter_width);
+        let filter_width = warped_filter_width(
+            |point| self.distort4(point, filter_width),
+            point,
+            distorted,
+            filter_width,
+        );
 
-        self.source
-            .get([x_distort, y_distort, z_distort, u_distort])
+        self.source.get_filtered(distorted, filter_width)
     }
 }
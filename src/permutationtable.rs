@@ -1,3 +1,4 @@
+use math;
 use math::{Point2, Point3, Point4};
 use rand::{
     distributions::{Distribution, Standard},
@@ -7,6 +8,32 @@ use std::fmt;
 
 const TABLE_SIZE: usize = 256;
 
+/// The hash that picks the pseudo-random values at the lattice points of the
+/// noise functions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LatticeHash {
+    /// A shuffled table of 256 values, indexed by each coordinate in turn.
+    /// This is fast, but the noise repeats every 256 lattice cells along each
+    /// axis.
+    PermutationTable,
+
+    /// A 64-bit integer hash of the coordinates and the seed. This is a little
+    /// slower than the table, but the noise doesn't visibly repeat.
+    Integer,
+}
+
+/// Trait for noise functions that pick their pseudo-random values at lattice
+/// points with a `LatticeHash`.
+pub trait LatticeHashable {
+    /// Sets the hash that picks the values at the lattice points. The default
+    /// `LatticeHash::PermutationTable` repeats every 256 lattice cells, which
+    /// `LatticeHash::Integer` avoids.
+    fn set_lattice_hash(self, hash: LatticeHash) -> Self;
+
+    /// Getter to retrieve the hash from the function
+    fn lattice_hash(&self) -> LatticeHash;
+}
+
 /// A seed table, required by all noise functions.
 ///
 /// Table creation is expensive, so in most circumstances you'll only want to
@@ -14,19 +41,23 @@ const TABLE_SIZE: usize = 256;
 #[derive(Copy, Clone)]
 pub struct PermutationTable {
     values: [u8; TABLE_SIZE],
+    seed: u32,
+    hash: LatticeHash,
 }
 
 impl Distribution<PermutationTable> for Standard {
     /// Generates a PermutationTable using a random seed.
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> PermutationTable {
         let mut seq: Vec<u8> = (0..TABLE_SIZE).map(|x| x as u8).collect();
-        rng.shuffle(&mut *seq);
+        rng.shuffle(&mut seq);
 
         // It's unfortunate that this double-initializes the array, but Rust
         // doesn't currently provide a clean way to do this in one pass. Hopefully
         // it won't matter, as Seed creation will usually be a one-time event.
         let mut perm_table = PermutationTable {
             values: [0; TABLE_SIZE],
+            seed: 0,
+            hash: LatticeHash::PermutationTable,
         };
         let seq_it = seq.iter();
         for (x, y) in perm_table.values.iter_mut().zip(seq_it) {
@@ -51,28 +82,114 @@ impl PermutationTable {
             real[(i * 4) + 3] = (seed >> 24) as u8;
         }
         let mut rng: XorShiftRng = SeedableRng::from_seed(real);
-        rng.gen()
+        PermutationTable { seed, ..rng.gen() }
+    }
+
+    /// Generates a new table based on a `u32` seed value, which hashes the
+    /// lattice points with the given hash.
+    pub fn with_hash(seed: u32, hash: LatticeHash) -> Self {
+        PermutationTable {
+            hash,
+            ..Self::new(seed)
+        }
+    }
+
+    /// Makes a table holding the given permutation, such as the one from a
+    /// reference implementation.
+    #[cfg(test)]
+    pub(crate) fn from_values(values: [u8; TABLE_SIZE]) -> Self {
+        PermutationTable {
+            values,
+            seed: 0,
+            hash: LatticeHash::PermutationTable,
+        }
+    }
+
+    pub fn hash(&self) -> LatticeHash {
+        self.hash
     }
 
     pub fn get1(&self, x: isize) -> usize {
+        match self.hash {
+            LatticeHash::PermutationTable => self.permute1(x),
+            LatticeHash::Integer => self.hash_integer(&[x]),
+        }
+    }
+
+    pub fn get2(&self, pos: Point2<isize>) -> usize {
+        match self.hash {
+            LatticeHash::PermutationTable => self.permute2(pos),
+            LatticeHash::Integer => self.hash_integer(&pos),
+        }
+    }
+
+    pub fn get3(&self, pos: Point3<isize>) -> usize {
+        match self.hash {
+            LatticeHash::PermutationTable => self.permute3(pos),
+            LatticeHash::Integer => self.hash_integer(&pos),
+        }
+    }
+
+    pub fn get4(&self, pos: Point4<isize>) -> usize {
+        match self.hash {
+            LatticeHash::PermutationTable => self.permute4(pos),
+            LatticeHash::Integer => self.hash_integer(&pos),
+        }
+    }
+
+    /// Hashes the coordinates the way the reference simplex noise does, as
+    /// `perm[x + perm[y + perm[z]]]`, adding rather than xoring each coordinate
+    /// to the value for the following ones. A table holding the reference
+    /// permutation then gives the reference values.
+    pub(crate) fn get_additive(&self, pos: &[isize]) -> usize {
+        match self.hash {
+            LatticeHash::PermutationTable => pos.iter().rev().fold(0, |hash, &x| {
+                self.values[((x & 0xff) as usize + hash) & 0xff] as usize
+            }),
+            LatticeHash::Integer => self.hash_integer(pos),
+        }
+    }
+
+    fn permute1(&self, x: isize) -> usize {
         let x = (x & 0xff) as usize;
         self.values[x] as usize
     }
 
-    pub fn get2(&self, pos: Point2<isize>) -> usize {
+    fn permute2(&self, pos: Point2<isize>) -> usize {
         let y = (pos[1] & 0xff) as usize;
-        self.values[self.get1(pos[0]) ^ y] as usize
+        self.values[self.permute1(pos[0]) ^ y] as usize
     }
 
-    pub fn get3(&self, pos: Point3<isize>) -> usize {
+    fn permute3(&self, pos: Point3<isize>) -> usize {
         let z = (pos[2] & 0xff) as usize;
-        self.values[self.get2([pos[0], pos[1]]) ^ z] as usize
+        self.values[self.permute2([pos[0], pos[1]]) ^ z] as usize
     }
 
-    pub fn get4(&self, pos: Point4<isize>) -> usize {
+    fn permute4(&self, pos: Point4<isize>) -> usize {
         let w = (pos[3] & 0xff) as usize;
-        self.values[self.get3([pos[0], pos[1], pos[2]]) ^ w] as usize
+        self.values[self.permute3([pos[0], pos[1], pos[2]]) ^ w] as usize
     }
+
+    /// Hashes the coordinates together with the seed. Only the low 8 bits are
+    /// kept, so the result can be used in the same way as a value from the
+    /// table.
+    fn hash_integer(&self, pos: &[isize]) -> usize {
+        let hash = hash_integers(self.seed, pos.iter().map(|&x| x as i64 as u64));
+        (hash & 0xff) as usize
+    }
+}
+
+/// Hashes a sequence of integers together with the seed, mixing in one
+/// integer at a time with the SplitMix64 finalizer.
+pub(crate) fn hash_integers<I>(seed: u32, values: I) -> u64
+where
+    I: IntoIterator<Item = u64>,
+{
+    values
+        .into_iter()
+        .fold(math::split_mix64(u64::from(seed)), |hash, x| {
+            math::split_mix64(hash.wrapping_add(x).wrapping_add(0x9e37_79b9_7f4a_7c15))
+        })
 }
 
 impl fmt::Debug for PermutationTable {
@@ -83,6 +200,7 @@ impl fmt::Debug for PermutationTable {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use rand::random;
     use {NoiseFn, Perlin, Seedable};
 
@@ -97,4 +215,24 @@ mod tests {
         let perlin = Perlin::new();
         let _ = perlin.get([-1.0, 2.0, 3.0]);
     }
+
+    #[test]
+    fn test_integer_hash_does_not_repeat() {
+        let table = PermutationTable::new(7);
+        let integer = PermutationTable::with_hash(7, LatticeHash::Integer);
+
+        let mut repeats = 0;
+        for x in -64..64 {
+            let pos = [x, x * 3 - 5, 11 - x];
+            assert_eq!(table.get3(pos), table.get3([x + 256, pos[1], pos[2]]));
+
+            assert!(integer.get3(pos) < 256);
+            if integer.get3(pos) == integer.get3([x + 256, pos[1], pos[2]]) {
+                repeats += 1;
+            }
+        }
+
+        // Roughly one in 256 values matches by chance.
+        assert!(repeats < 8, "{} of 128 values repeated", repeats);
+    }
 }
@@ -1,5 +1,4 @@
 use math;
-use std;
 
 pub type Color = [u8; 4];
 
@@ -28,7 +27,7 @@ impl ColorGradient {
         if !self
             .gradient_points
             .iter()
-            .any(|&x| (x.pos - pos).abs() < std::f64::EPSILON)
+            .any(|&x| (x.pos - pos).abs() < f64::EPSILON)
         {
             // it doesn't, so find the correct position to insert the new
             // control point.
@@ -46,7 +45,7 @@ impl ColorGradient {
         self.gradient_points
             .iter()
             .position(|x| x.pos >= pos)
-            .unwrap_or_else(|| self.gradient_points.len())
+            .unwrap_or(self.gradient_points.len())
     }
 
     pub fn clear_gradient(mut self) -> Self {
@@ -101,8 +100,8 @@ impl ColorGradient {
         let index = self
             .gradient_points
             .iter()
-            .position(|&x| (x.pos > clamped_pos))
-            .unwrap_or_else(|| self.gradient_points.len());
+            .position(|&x| x.pos > clamped_pos)
+            .unwrap_or(self.gradient_points.len());
 
         if index < 1 {
             println!(
@@ -215,9 +215,9 @@ impl ImageRenderer {
         }
 
         // Clamp color channels to [0..1]
-        red = red.max(0.0).min(1.0);
-        green = green.max(0.0).min(1.0);
-        blue = blue.max(0.0).min(1.0);
+        red = red.clamp(0.0, 1.0);
+        green = green.clamp(0.0, 1.0);
+        blue = blue.clamp(0.0, 1.0);
 
         // Rescale color channels to u8 [0..255] and return the final color
         [
@@ -339,9 +339,9 @@ impl ImageRenderer {
         }
 
         // Clamp color channels to [0..1]
-        red = red.max(0.0).min(1.0);
-        green = green.max(0.0).min(1.0);
-        blue = blue.max(0.0).min(1.0);
+        red = red.clamp(0.0, 1.0);
+        green = green.clamp(0.0, 1.0);
+        blue = blue.clamp(0.0, 1.0);
 
         // Rescale color channels to u8 [0..255] and return the final color
         [
@@ -117,8 +117,8 @@ impl NoiseImage {
         }
 
         let _ = image::save_buffer(
-            &Path::new(&file_path),
-            &*result,
+            Path::new(&file_path),
+            &result,
             self.size.0 as u32,
             self.size.1 as u32,
             image::ColorType::RGBA(8),
@@ -108,8 +108,8 @@ impl NoiseMap {
         }
 
         let _ = image::save_buffer(
-            &Path::new(&file_path),
-            &*pixels,
+            Path::new(&file_path),
+            &pixels,
             self.size.0 as u32,
             self.size.1 as u32,
             image::ColorType::Gray(8),
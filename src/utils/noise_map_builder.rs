@@ -127,6 +127,7 @@ impl<'a> NoiseMapBuilder<'a> for CylinderMapBuilder<'a> {
 }
 
 pub struct PlaneMapBuilder<'a> {
+    is_filtered: bool,
     is_seamless: bool,
     x_bounds: (f64, f64),
     y_bounds: (f64, f64),
@@ -137,6 +138,7 @@ pub struct PlaneMapBuilder<'a> {
 impl<'a> PlaneMapBuilder<'a> {
     pub fn new(source_module: &'a dyn NoiseFn<[f64; 3]>) -> Self {
         PlaneMapBuilder {
+            is_filtered: false,
             is_seamless: false,
             x_bounds: (-1.0, 1.0),
             y_bounds: (-1.0, 1.0),
@@ -145,6 +147,16 @@ impl<'a> PlaneMapBuilder<'a> {
         }
     }
 
+    /// Samples the source module with `get_filtered`, passing the distance
+    /// between pixels as the filter width, so that fractals leave out any
+    /// detail finer than a pixel. Off by default, as it changes the output.
+    pub fn set_is_filtered(self, is_filtered: bool) -> Self {
+        PlaneMapBuilder {
+            is_filtered,
+            ..self
+        }
+    }
+
     pub fn set_is_seamless(self, is_seamless: bool) -> Self {
         PlaneMapBuilder {
             is_seamless,
@@ -205,6 +217,16 @@ impl<'a> NoiseMapBuilder<'a> for PlaneMapBuilder<'a> {
         let x_step = x_extent / width as f64;
         let y_step = y_extent / height as f64;
 
+        // Filter out any detail finer than a pixel, if asked to.
+        let filter_width = x_step.max(y_step);
+        let get = |point| {
+            if self.is_filtered {
+                self.source_module.get_filtered(point, filter_width)
+            } else {
+                self.source_module.get(point)
+            }
+        };
+
         for y in 0..height {
             let current_y = self.y_bounds.0 + y_step * y as f64;
 
@@ -212,16 +234,10 @@ impl<'a> NoiseMapBuilder<'a> for PlaneMapBuilder<'a> {
                 let current_x = self.x_bounds.0 + x_step * x as f64;
 
                 let final_value = if self.is_seamless {
-                    let sw_value = self.source_module.get([current_x, current_y, 0.0]);
-                    let se_value = self
-                        .source_module
-                        .get([current_x + x_extent, current_y, 0.0]);
-                    let nw_value = self
-                        .source_module
-                        .get([current_x, current_y + y_extent, 0.0]);
-                    let ne_value =
-                        self.source_module
-                            .get([current_x + x_extent, current_y + y_extent, 0.0]);
+                    let sw_value = get([current_x, current_y, 0.0]);
+                    let se_value = get([current_x + x_extent, current_y, 0.0]);
+                    let nw_value = get([current_x, current_y + y_extent, 0.0]);
+                    let ne_value = get([current_x + x_extent, current_y + y_extent, 0.0]);
 
                     let x_blend = 1.0 - ((current_x - self.x_bounds.0) / x_extent);
                     let y_blend = 1.0 - ((current_y - self.y_bounds.0) / y_extent);
@@ -231,7 +247,7 @@ impl<'a> NoiseMapBuilder<'a> for PlaneMapBuilder<'a> {
 
                     interpolate::linear(y0, y1, y_blend)
                 } else {
-                    self.source_module.get([current_x, current_y, 0.0])
+                    get([current_x, current_y, 0.0])
                 };
 
                 result_map.set_value(x, y, final_value);
@@ -243,6 +259,7 @@ impl<'a> NoiseMapBuilder<'a> for PlaneMapBuilder<'a> {
 }
 
 pub struct SphereMapBuilder<'a> {
+    is_filtered: bool,
     latitude_bounds: (f64, f64),
     longitude_bounds: (f64, f64),
     size: (usize, usize),
@@ -252,6 +269,7 @@ pub struct SphereMapBuilder<'a> {
 impl<'a> SphereMapBuilder<'a> {
     pub fn new(source_module: &'a dyn NoiseFn<[f64; 3]>) -> Self {
         SphereMapBuilder {
+            is_filtered: false,
             latitude_bounds: (-1.0, 1.0),
             longitude_bounds: (-1.0, 1.0),
             size: (100, 100),
@@ -259,6 +277,16 @@ impl<'a> SphereMapBuilder<'a> {
         }
     }
 
+    /// Samples the source module with `get_filtered`, passing the distance
+    /// between pixels, as an arc on the unit sphere, as the filter width. Off
+    /// by default, as it changes the output.
+    pub fn set_is_filtered(self, is_filtered: bool) -> Self {
+        SphereMapBuilder {
+            is_filtered,
+            ..self
+        }
+    }
+
     pub fn set_latitude_bounds(self, min_lat_bound: f64, max_lat_bound: f64) -> Self {
         SphereMapBuilder {
             latitude_bounds: (min_lat_bound, max_lat_bound),
@@ -326,6 +354,10 @@ impl<'a> NoiseMapBuilder<'a> for SphereMapBuilder<'a> {
         let x_step = lon_extent / width as f64;
         let y_step = lat_extent / height as f64;
 
+        // Filter out any detail finer than a pixel, measured as an arc on the
+        // unit sphere, if asked to.
+        let filter_width = x_step.max(y_step).to_radians();
+
         for y in 0..height {
             let current_lat = self.latitude_bounds.0 + y_step * y as f64;
 
@@ -334,7 +366,13 @@ impl<'a> NoiseMapBuilder<'a> for SphereMapBuilder<'a> {
 
                 let point = lat_lon_to_xyz(current_lat, current_lon);
 
-                result_map.set_value(x, y, self.source_module.get(point));
+                let value = if self.is_filtered {
+                    self.source_module.get_filtered(point, filter_width)
+                } else {
+                    self.source_module.get(point)
+                };
+
+                result_map.set_value(x, y, value);
             }
         }
 
@@ -350,3 +388,29 @@ fn lat_lon_to_xyz(lat: f64, lon: f64) -> [f64; 3] {
 
     [x, y, z]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noise_fns::Fbm;
+
+    #[test]
+    fn filtering_is_opt_in() {
+        let fbm = Fbm::new();
+        let builder = PlaneMapBuilder::new(&fbm)
+            .set_size(8, 8)
+            .set_x_bounds(-4.0, 4.0)
+            .set_y_bounds(-4.0, 4.0);
+
+        let unfiltered = builder.build();
+        let filtered = builder.set_is_filtered(true).build();
+
+        for y in 0..8 {
+            for x in 0..8 {
+                let point = [x as f64 - 4.0, y as f64 - 4.0, 0.0];
+                assert_eq!(unfiltered.get_value(x, y), fbm.get(point));
+                assert_eq!(filtered.get_value(x, y), fbm.get_filtered(point, 1.0));
+            }
+        }
+    }
+}